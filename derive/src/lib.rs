@@ -0,0 +1,303 @@
+//! Derive macro for the [`json-ld`](https://crates.io/crates/json-ld) crate.
+//!
+//! The `LinkedData` derive macro maps a struct with named fields to and from a JSON-LD
+//! node object (`json_ld::Node<T>`), by generating the following implementations:
+//!   - `TryFrom<&Node<T>>`, failing with a `json_ld::linked_data::Error` when a property is
+//!     missing or has an unexpected value,
+//!   - `From<Struct> for Node<T>` (hence `Into<Node<T>>`),
+//!   - `json_ld::linked_data::FromObject<T>` and `json_ld::linked_data::IntoObject<T>`, so
+//!     that the struct can itself be used as the type of a field of another struct.
+//!
+//! It is reexported by `json-ld` with its `derive` feature enabled.
+//!
+//! ## Attributes
+//!
+//! On the struct:
+//!   - `#[ld(type = "iri")]` requires the node to have the given type, and adds it to the
+//!     generated node. It can be repeated.
+//!   - `#[ld(id_type = "Type")]` sets the identifier type `T` of the node.
+//!     By default, the implementations are generic over any `T: json_ld::Id`, which is
+//!     not possible if some field type depends on a specific identifier type
+//!     (such as `Reference`, that is `Reference<IriBuf>`).
+//!
+//! On each field:
+//!   - `#[ld(iri = "iri")]` maps the field to the given property.
+//!     A field of type `Option<V>` is mapped to an optional property, a field of type
+//!     `Vec<V>` to all the values of the property, any other type to a property with exactly
+//!     one value.
+//!   - `#[ld(id)]` maps the field to the node identifier (`@id`).
+//!     It must be of type `Reference<T>` or `Option<Reference<T>>`.
+//!   - `#[ld(skip)]` ignores the field. It is initialized with `Default::default()` when
+//!     converting a node.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{
+	Span,
+	TokenStream as TokenStream2
+};
+use quote::quote;
+use syn::{
+	parse_macro_input,
+	spanned::Spanned,
+	DeriveInput,
+	Data,
+	Fields,
+	Field,
+	Attribute,
+	Meta,
+	NestedMeta,
+	Lit,
+	LitStr,
+	Type,
+	GenericParam,
+	Lifetime,
+	LifetimeDef,
+	TypeParam
+};
+
+/// Number of values expected for a property.
+enum Cardinality {
+	/// Exactly one value.
+	One,
+
+	/// At most one value (`Option<V>` field).
+	Optional,
+
+	/// Any number of values (`Vec<V>` field).
+	Many
+}
+
+impl Cardinality {
+	fn of(ty: &Type) -> Cardinality {
+		if let Type::Path(path) = ty {
+			if let Some(segment) = path.path.segments.last() {
+				if segment.ident == "Option" {
+					return Cardinality::Optional
+				} else if segment.ident == "Vec" {
+					return Cardinality::Many
+				}
+			}
+		}
+
+		Cardinality::One
+	}
+}
+
+/// Field mapping.
+enum Mapping {
+	/// Node identifier.
+	Id,
+
+	/// Property.
+	Property(LitStr),
+
+	/// Ignored field.
+	Skip
+}
+
+/// Struct level attributes.
+#[derive(Default)]
+struct StructAttributes {
+	types: Vec<LitStr>,
+	id_type: Option<Type>
+}
+
+/// Collect the `ld` meta items of the given attributes.
+fn ld_meta(attrs: &[Attribute]) -> syn::Result<Vec<Meta>> {
+	let mut result = Vec::new();
+	for attr in attrs {
+		if attr.path.is_ident("ld") {
+			match attr.parse_meta()? {
+				Meta::List(list) => {
+					for nested in list.nested {
+						match nested {
+							NestedMeta::Meta(meta) => result.push(meta),
+							NestedMeta::Lit(lit) => return Err(syn::Error::new(lit.span(), "unexpected literal"))
+						}
+					}
+				},
+				meta => return Err(syn::Error::new(meta.span(), "expected `#[ld(...)]`"))
+			}
+		}
+	}
+
+	Ok(result)
+}
+
+/// Get the IRI string literal of a `key = "iri"` meta item.
+fn iri_value(lit: Lit) -> syn::Result<LitStr> {
+	match lit {
+		Lit::Str(s) => {
+			if iref::Iri::new(&s.value()).is_err() {
+				return Err(syn::Error::new(s.span(), "invalid IRI"))
+			}
+
+			Ok(s)
+		},
+		lit => Err(syn::Error::new(lit.span(), "expected an IRI string"))
+	}
+}
+
+fn struct_attributes(attrs: &[Attribute]) -> syn::Result<StructAttributes> {
+	let mut result = StructAttributes::default();
+	for meta in ld_meta(attrs)? {
+		match meta {
+			Meta::NameValue(nv) if nv.path.is_ident("type") => {
+				result.types.push(iri_value(nv.lit)?)
+			},
+			Meta::NameValue(nv) if nv.path.is_ident("id_type") => {
+				match nv.lit {
+					Lit::Str(s) => result.id_type = Some(s.parse()?),
+					lit => return Err(syn::Error::new(lit.span(), "expected a type string"))
+				}
+			},
+			meta => return Err(syn::Error::new(meta.span(), "unknown attribute, expected `type` or `id_type`"))
+		}
+	}
+
+	Ok(result)
+}
+
+fn field_mapping(field: &Field) -> syn::Result<Mapping> {
+	let mut mapping = None;
+	for meta in ld_meta(&field.attrs)? {
+		let span = meta.span();
+		let m = match meta {
+			Meta::Path(path) if path.is_ident("id") => Mapping::Id,
+			Meta::Path(path) if path.is_ident("skip") => Mapping::Skip,
+			Meta::NameValue(nv) if nv.path.is_ident("iri") => Mapping::Property(iri_value(nv.lit)?),
+			_ => return Err(syn::Error::new(span, "unknown attribute, expected `id`, `iri` or `skip`"))
+		};
+
+		if mapping.replace(m).is_some() {
+			return Err(syn::Error::new(span, "conflicting field mappings"))
+		}
+	}
+
+	mapping.ok_or_else(|| syn::Error::new(field.span(), "missing field mapping, expected `#[ld(id)]`, `#[ld(iri = \"...\")]` or `#[ld(skip)]`"))
+}
+
+#[proc_macro_derive(LinkedData, attributes(ld))]
+pub fn derive_linked_data(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	match derive(input) {
+		Ok(tokens) => tokens.into(),
+		Err(e) => e.to_compile_error().into()
+	}
+}
+
+fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+	let attrs = struct_attributes(&input.attrs)?;
+
+	let fields = match &input.data {
+		Data::Struct(data) => match &data.fields {
+			Fields::Named(fields) => &fields.named,
+			_ => return Err(syn::Error::new(input.ident.span(), "`LinkedData` can only be derived for structs with named fields"))
+		},
+		_ => return Err(syn::Error::new(input.ident.span(), "`LinkedData` can only be derived for structs"))
+	};
+
+	let mut id_field = None;
+	let mut from_fields = Vec::new();
+	let mut into_fields = Vec::new();
+	for field in fields {
+		let name = field.ident.as_ref().ok_or_else(|| syn::Error::new_spanned(field, "`LinkedData` cannot be derived for this field: only named fields are supported"))?;
+		match field_mapping(field)? {
+			Mapping::Id => {
+				if id_field.is_some() {
+					return Err(syn::Error::new(field.span(), "duplicate `#[ld(id)]` field"))
+				}
+
+				match Cardinality::of(&field.ty) {
+					Cardinality::One => {
+						from_fields.push(quote! { #name: ::json_ld::linked_data::id(node)? });
+						id_field = Some(quote! { ::std::option::Option::Some(value.#name) })
+					},
+					Cardinality::Optional => {
+						from_fields.push(quote! { #name: ::json_ld::linked_data::optional_id(node)? });
+						id_field = Some(quote! { value.#name })
+					},
+					Cardinality::Many => return Err(syn::Error::new(field.ty.span(), "expected `Reference<T>` or `Option<Reference<T>>`"))
+				}
+			},
+			Mapping::Property(iri) => {
+				let (get, insert) = match Cardinality::of(&field.ty) {
+					Cardinality::One => (quote! { get }, quote! { insert }),
+					Cardinality::Optional => (quote! { get_optional }, quote! { insert_optional }),
+					Cardinality::Many => (quote! { get_all }, quote! { insert_all })
+				};
+
+				from_fields.push(quote! { #name: ::json_ld::linked_data::#get(node, #iri)? });
+				into_fields.push(quote! { ::json_ld::linked_data::#insert(&mut node, #iri, value.#name); })
+			},
+			Mapping::Skip => {
+				from_fields.push(quote! { #name: ::std::default::Default::default() })
+			}
+		}
+	}
+
+	let id_field = id_field.unwrap_or_else(|| quote! { ::std::option::Option::None });
+	let types = &attrs.types;
+
+	let ident = &input.ident;
+	let (_, ty_generics, _) = input.generics.split_for_impl();
+
+	// Generics of the implementations, with the identifier type parameter (if not given).
+	let mut generics = input.generics.clone();
+	let id_type = match attrs.id_type {
+		Some(ty) => quote! { #ty },
+		None => {
+			let param = syn::Ident::new("__T", Span::call_site());
+			generics.params.push(GenericParam::Type(TypeParam::from(param.clone())));
+			generics.make_where_clause().predicates.push(syn::parse_quote! { #param: ::json_ld::Id });
+			quote! { #param }
+		}
+	};
+	let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+	// Generics of the `TryFrom` implementation, with the node lifetime.
+	let mut node_generics = generics.clone();
+	let lifetime = Lifetime::new("'__node", Span::call_site());
+	node_generics.params.insert(0, GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())));
+	let (node_impl_generics, _, _) = node_generics.split_for_impl();
+
+	Ok(quote! {
+		impl #node_impl_generics ::std::convert::TryFrom<&#lifetime ::json_ld::Node<#id_type>> for #ident #ty_generics #where_clause {
+			type Error = ::json_ld::linked_data::Error;
+
+			fn try_from(node: &#lifetime ::json_ld::Node<#id_type>) -> ::std::result::Result<Self, ::json_ld::linked_data::Error> {
+				#(::json_ld::linked_data::require_type(node, #types)?;)*
+
+				::std::result::Result::Ok(#ident {
+					#(#from_fields),*
+				})
+			}
+		}
+
+		impl #impl_generics ::std::convert::From<#ident #ty_generics> for ::json_ld::Node<#id_type> #where_clause {
+			fn from(value: #ident #ty_generics) -> ::json_ld::Node<#id_type> {
+				#[allow(unused_mut)]
+				let mut node = ::json_ld::linked_data::new_node(#id_field);
+				#(::json_ld::linked_data::add_type(&mut node, #types);)*
+				#(#into_fields)*
+				node
+			}
+		}
+
+		impl #impl_generics ::json_ld::linked_data::FromObject<#id_type> for #ident #ty_generics #where_clause {
+			fn from_object(object: &::json_ld::Object<#id_type>) -> ::std::result::Result<Self, ::json_ld::linked_data::Error> {
+				match object {
+					::json_ld::Object::Node(node) => <Self as ::std::convert::TryFrom<&::json_ld::Node<#id_type>>>::try_from(node),
+					_ => ::std::result::Result::Err(::json_ld::linked_data::Error::Mistyped("a node object"))
+				}
+			}
+		}
+
+		impl #impl_generics ::json_ld::linked_data::IntoObject<#id_type> for #ident #ty_generics #where_clause {
+			fn into_object(self) -> ::json_ld::Object<#id_type> {
+				::json_ld::Object::Node(self.into())
+			}
+		}
+	})
+}
@@ -0,0 +1,87 @@
+//! This example shows how to use the `LinkedData` derive macro (provided by the `derive`
+//! feature) to extract Rust structs from the nodes of an expanded document, and turn them
+//! back into nodes.
+extern crate async_std;
+extern crate iref;
+extern crate json_ld;
+
+use std::convert::TryFrom;
+use iref::IriBuf;
+use json_ld::{
+	JsonContext,
+	NoLoader,
+	Document,
+	Object,
+	Node,
+	Reference,
+	LinkedData,
+	util::AsJson
+};
+
+#[derive(LinkedData)]
+#[ld(type = "http://schema.org/PostalAddress")]
+struct Address {
+	#[ld(iri = "http://schema.org/streetAddress")]
+	street: String,
+
+	#[ld(iri = "http://schema.org/postalCode")]
+	postal_code: Option<String>
+}
+
+#[derive(LinkedData)]
+#[ld(type = "http://schema.org/Person", id_type = "IriBuf")]
+struct Person {
+	#[ld(id)]
+	id: Reference,
+
+	#[ld(iri = "http://schema.org/name")]
+	name: String,
+
+	#[ld(iri = "http://schema.org/age")]
+	age: Option<u32>,
+
+	#[ld(iri = "http://schema.org/address")]
+	address: Address,
+
+	#[ld(iri = "http://schema.org/knows")]
+	knows: Vec<Reference>
+}
+
+#[async_std::main]
+async fn main() {
+	let doc = json::parse(r#"
+		{
+			"@context": { "@vocab": "http://schema.org/" },
+			"@id": "https://example.com/alice",
+			"@type": "Person",
+			"name": "Alice",
+			"age": 42,
+			"address": {
+				"@type": "PostalAddress",
+				"streetAddress": "1 Main Street"
+			},
+			"knows": [
+				{ "@id": "https://example.com/bob" },
+				{ "@id": "https://example.com/carol" }
+			]
+		}
+	"#).unwrap();
+
+	let context: JsonContext<IriBuf> = JsonContext::new(None);
+	let expanded_doc = doc.expand(&context, &mut NoLoader).await.unwrap();
+
+	for object in &expanded_doc {
+		if let Object::Node(node) = object.as_ref() {
+			match Person::try_from(node) {
+				Ok(person) => {
+					println!("{} ({}) lives at {}", person.name, person.id, person.address.street);
+					println!("{} knows {} people", person.name, person.knows.len());
+
+					let node: Node = person.into();
+					println!("{}", node.as_json().pretty(2))
+				},
+				Err(e) => eprintln!("not a person: {}", e)
+			}
+		}
+	}
+}
@@ -0,0 +1,489 @@
+//! Command-line interface to the JSON-LD algorithms.
+//!
+//! Reads a JSON-LD document (or N-Quads document for `from-rdf`) from a file or the
+//! standard input, and writes the result on the standard output.
+//! Remote documents and contexts are loaded from the file system using the mount points
+//! given with the `--mount URL=DIR` option (see [`FsLoader`]).
+//!
+//! This binary requires the `cli` feature.
+extern crate futures;
+extern crate iref;
+extern crate json;
+extern crate json_ld;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use futures::executor::block_on;
+use iref::{Iri, IriBuf};
+use json::JsonValue;
+use json_ld::{
+	Document,
+	JsonContext,
+	FsLoader,
+	BlankIdCounter,
+	ProcessingMode,
+	Object,
+	Warning,
+	context::{
+		self,
+		Local,
+		ProcessingStack,
+		ProcessingOptions
+	},
+	expansion,
+	compaction,
+	flattening,
+	framing,
+	rdf,
+	util::AsJson
+};
+
+const USAGE: &str = "Usage: jsonld <COMMAND> [OPTIONS] [FILE]
+
+Reads the input document from FILE, or from the standard input if FILE is
+omitted or `-`, and writes the result on the standard output.
+
+Commands:
+  expand      Expand the document.
+  context     Print the active context resulting from the processing of the
+              document's `@context` (or of the whole document if it has none).
+  compact     Compact the document using the context given with `--context`.
+  flatten     Flatten the document, and compact it if `--context` is given.
+  frame       Frame the document using the frame given with `--frame`.
+  to-rdf      Convert the document into an N-Quads document.
+  from-rdf    Convert an N-Quads document into an expanded JSON-LD document.
+
+Options:
+  --base <IRI>                 Base IRI of the document.
+  --expand-context <CONTEXT>   Context applied before the document's own context.
+  --context <CONTEXT>          Context used for compaction.
+  --frame <FILE>               Frame document.
+  --processing-mode <MODE>     `json-ld-1.0` or `json-ld-1.1` (default).
+  --ordered                    Process entries in lexicographical order.
+  --policy <POLICY>            Handling of the keys and values that cannot be
+                               expanded: `lenient` (default) drops the keys,
+                               `report` also warns about each unresolved value,
+                               `strict` fails on the first of them, and on
+                               malformed language tags.
+  --normalize-language-tags    Normalize language tags to lower case.
+  --mount <URL=DIR>            Load the documents under URL from the directory DIR.
+                               Can be repeated.
+  --rdf-direction <DIRECTION>  `i18n-datatype` or `compound-literal`.
+  --produce-generalized-rdf    Produce triples with blank node predicates.
+  --use-native-types           Convert native RDF literals into JSON values.
+  --use-rdf-type               Keep `rdf:type` triples as regular properties.
+  -h, --help                   Print this message.
+
+A CONTEXT is either the IRI of a remote context, loaded through the mount points,
+or the path to a JSON file. If the file contains an object with a `@context`
+entry, the value of this entry is used.";
+
+/// Command-line tool error.
+enum Error {
+	/// Invalid command-line arguments.
+	Usage(String),
+
+	/// Unable to read the input.
+	Io(io::Error),
+
+	/// Invalid JSON input.
+	Json(json::Error),
+
+	/// Invalid N-Quads input.
+	NQuads(rdf::nquads::Error),
+
+	/// JSON-LD algorithm error.
+	JsonLd(json_ld::Error),
+
+	/// JSON-LD algorithm error, located in the input file (name, line and column).
+	JsonLdAt(json_ld::Error, String, usize, usize)
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Usage(msg) => write!(f, "{}", msg),
+			Error::Io(e) => write!(f, "{}", e),
+			Error::Json(e) => write!(f, "invalid JSON: {}", e),
+			Error::NQuads(e) => write!(f, "invalid N-Quads: {}", e),
+			Error::JsonLd(e) => write_json_ld_error(f, e),
+			Error::JsonLdAt(e, file, line, column) => {
+				write!(f, "{}:{}:{}: ", file, line, column)?;
+				write_json_ld_error(f, e)
+			}
+		}
+	}
+}
+
+/// Write a JSON-LD error followed by its source, if any.
+fn write_json_ld_error(f: &mut fmt::Formatter, e: &json_ld::Error) -> fmt::Result {
+	match std::error::Error::source(e) {
+		Some(source) => write!(f, "{}: {}", e, source),
+		None => write!(f, "{}", e)
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Error {
+		Error::Io(e)
+	}
+}
+
+impl From<json::Error> for Error {
+	fn from(e: json::Error) -> Error {
+		Error::Json(e)
+	}
+}
+
+impl From<rdf::nquads::Error> for Error {
+	fn from(e: rdf::nquads::Error) -> Error {
+		Error::NQuads(e)
+	}
+}
+
+impl From<json_ld::Error> for Error {
+	fn from(e: json_ld::Error) -> Error {
+		Error::JsonLd(e)
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Command {
+	Expand,
+	Context,
+	Compact,
+	Flatten,
+	Frame,
+	ToRdf,
+	FromRdf
+}
+
+impl<'a> TryFrom<&'a str> for Command {
+	type Error = &'a str;
+
+	fn try_from(name: &'a str) -> Result<Command, &'a str> {
+		match name {
+			"expand" => Ok(Command::Expand),
+			"context" => Ok(Command::Context),
+			"compact" => Ok(Command::Compact),
+			"flatten" => Ok(Command::Flatten),
+			"frame" => Ok(Command::Frame),
+			"to-rdf" => Ok(Command::ToRdf),
+			"from-rdf" => Ok(Command::FromRdf),
+			_ => Err(name)
+		}
+	}
+}
+
+/// Parsed command-line arguments.
+struct Args {
+	command: Command,
+	input: Option<String>,
+	base: Option<IriBuf>,
+	expand_context: Option<String>,
+	context: Option<String>,
+	frame: Option<String>,
+	processing_mode: ProcessingMode,
+	ordered: bool,
+	policy: expansion::Policy,
+	normalize_language_tags: bool,
+	mounts: Vec<(IriBuf, PathBuf)>,
+	rdf_options: rdf::Options
+}
+
+impl Args {
+	fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Args>, Error> {
+		let command = match args.next() {
+			Some(arg) if arg == "-h" || arg == "--help" => return Ok(None),
+			Some(arg) => Command::try_from(arg.as_str()).map_err(|name| Error::Usage(format!("unknown command `{}`", name)))?,
+			None => return Err(Error::Usage("missing command".to_string()))
+		};
+
+		let mut result = Args {
+			command,
+			input: None,
+			base: None,
+			expand_context: None,
+			context: None,
+			frame: None,
+			processing_mode: ProcessingMode::default(),
+			ordered: false,
+			policy: expansion::Policy::default(),
+			normalize_language_tags: false,
+			mounts: Vec::new(),
+			rdf_options: rdf::Options::default()
+		};
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"-h" | "--help" => return Ok(None),
+				"--base" => {
+					let value = option_value(&mut args, &arg)?;
+					result.base = Some(parse_iri(&value)?)
+				},
+				"--expand-context" => result.expand_context = Some(option_value(&mut args, &arg)?),
+				"--context" => result.context = Some(option_value(&mut args, &arg)?),
+				"--frame" => result.frame = Some(option_value(&mut args, &arg)?),
+				"--processing-mode" => {
+					let value = option_value(&mut args, &arg)?;
+					result.processing_mode = ProcessingMode::try_from(value.as_str()).map_err(|_| Error::Usage(format!("invalid processing mode `{}`", value)))?
+				},
+				"--ordered" => result.ordered = true,
+				"--policy" => {
+					let value = option_value(&mut args, &arg)?;
+					result.policy = expansion::Policy::try_from(value.as_str()).map_err(|_| Error::Usage(format!("invalid policy `{}`", value)))?
+				},
+				"--normalize-language-tags" => result.normalize_language_tags = true,
+				"--mount" => {
+					let value = option_value(&mut args, &arg)?;
+					match value.find('=') {
+						Some(i) => result.mounts.push((parse_iri(&value[..i])?, PathBuf::from(&value[(i + 1)..]))),
+						None => return Err(Error::Usage(format!("invalid mount point `{}`, expected `URL=DIR`", value)))
+					}
+				},
+				"--rdf-direction" => {
+					let value = option_value(&mut args, &arg)?;
+					result.rdf_options.rdf_direction = Some(rdf::RdfDirection::try_from(value.as_str()).map_err(|_| Error::Usage(format!("invalid RDF direction `{}`", value)))?)
+				},
+				"--produce-generalized-rdf" => result.rdf_options.produce_generalized_rdf = true,
+				"--use-native-types" => result.rdf_options.use_native_types = true,
+				"--use-rdf-type" => result.rdf_options.use_rdf_type = true,
+				_ if arg.starts_with("--") => return Err(Error::Usage(format!("unknown option `{}`", arg))),
+				_ => {
+					if result.input.replace(arg).is_some() {
+						return Err(Error::Usage("too many input files".to_string()))
+					}
+				}
+			}
+		}
+
+		Ok(Some(result))
+	}
+
+	fn base_iri(&self) -> Option<Iri<'_>> {
+		self.base.as_ref().map(|base| base.as_iri())
+	}
+
+	fn loader(&self) -> FsLoader {
+		let mut loader = FsLoader::new();
+		for (url, path) in &self.mounts {
+			loader.mount(url.as_iri(), path)
+		}
+
+		loader
+	}
+
+	fn expansion_options<'a>(&self, expand_context: Option<&'a JsonValue>) -> expansion::Options<'a> {
+		expansion::Options {
+			processing_mode: self.processing_mode,
+			ordered: self.ordered,
+			warnings: Some(&print_warning),
+			policy: self.policy,
+			normalize_language_tags: self.normalize_language_tags,
+			expand_context: expand_context.map(|context| match context.as_str() {
+				Some(iri) if Iri::new(iri).is_ok() => expansion::ExpandContext::Iri(Iri::new(iri).unwrap()),
+				_ => expansion::ExpandContext::json(context)
+			}),
+			..expansion::Options::default()
+		}
+	}
+
+	fn compaction_options(&self) -> compaction::Options {
+		compaction::Options {
+			processing_mode: self.processing_mode,
+			ordered: self.ordered,
+			..compaction::Options::default()
+		}
+	}
+}
+
+/// Print the processing warnings on the standard error output.
+fn print_warning(warning: Warning) {
+	eprintln!("jsonld: warning: {}", warning)
+}
+
+fn option_value<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<String, Error> {
+	args.next().ok_or_else(|| Error::Usage(format!("missing value for `{}`", option)))
+}
+
+fn parse_iri(value: &str) -> Result<IriBuf, Error> {
+	IriBuf::new(value).map_err(|_| Error::Usage(format!("invalid IRI `{}`", value)))
+}
+
+/// Read the input file, or the standard input.
+fn read_input(input: Option<&str>) -> Result<String, Error> {
+	match input {
+		Some(path) if path != "-" => Ok(fs::read_to_string(path)?),
+		_ => {
+			let mut buffer = String::new();
+			io::stdin().read_to_string(&mut buffer)?;
+			Ok(buffer)
+		}
+	}
+}
+
+/// Read a context argument.
+///
+/// If it is an IRI, it is returned as a JSON string, to be loaded during context processing.
+/// Otherwise it is the path to a JSON file. If this file contains an object with a `@context`
+/// entry, the value of this entry is returned.
+fn read_context(arg: &str) -> Result<JsonValue, Error> {
+	if Iri::new(arg).is_ok() {
+		Ok(arg.into())
+	} else {
+		Ok(local_context(json::parse(&fs::read_to_string(arg)?)?))
+	}
+}
+
+/// Extract the value of the `@context` entry of an object, if any.
+fn local_context(mut value: JsonValue) -> JsonValue {
+	match &mut value {
+		JsonValue::Object(obj) if obj.get("@context").is_some() => obj.remove("@context").unwrap(),
+		_ => value
+	}
+}
+
+/// Process a local context, starting from an empty active context.
+fn process_context(args: &Args, local_context: &JsonValue, loader: &mut FsLoader) -> Result<JsonContext, Error> {
+	let options = ProcessingOptions {
+		processing_mode: args.processing_mode,
+		warnings: Some(&print_warning),
+		..ProcessingOptions::default()
+	};
+	Ok(local_context.process_with_sync(&JsonContext::new(args.base_iri()), ProcessingStack::new(), loader, args.base_iri(), options)?)
+}
+
+/// Locate an expansion error in the input file.
+///
+/// This is not done if the error occured in a remote context, or if an expansion context is
+/// given, since the error may then be located in this context.
+fn locate(e: json_ld::Error, args: &Args, input: &str) -> Error {
+	if e.remote_contexts().is_empty() && args.expand_context.is_none() {
+		if let Some((line, column)) = e.position(input) {
+			let file = match args.input.as_deref() {
+				Some(path) if path != "-" => path.to_string(),
+				_ => "<stdin>".to_string()
+			};
+
+			return Error::JsonLdAt(e, file, line, column)
+		}
+	}
+
+	Error::JsonLd(e)
+}
+
+fn run(args: Args) -> Result<JsonOrNQuads, Error> {
+	let mut loader = args.loader();
+	let input = read_input(args.input.as_deref())?;
+
+	if let Command::FromRdf = args.command {
+		let dataset: rdf::Dataset = rdf::nquads::parse(&input)?;
+		let expanded = rdf::from_rdf(&dataset, args.rdf_options)?;
+		return Ok(JsonOrNQuads::Json(if args.ordered { expanded.as_ordered_json() } else { expanded.as_json() }))
+	}
+
+	let doc = json::parse(&input)?;
+
+	if let Command::Context = args.command {
+		let active_context = process_context(&args, &local_context(doc), &mut loader)?;
+		return Ok(JsonOrNQuads::Json(active_context.as_json()))
+	}
+
+	let expand_context = match &args.expand_context {
+		Some(arg) => Some(read_context(arg)?),
+		None => None
+	};
+
+	let context: JsonContext = JsonContext::new(args.base_iri());
+	let expanded = doc.expand_ordered_with_sync(args.base_iri(), &context, &mut loader, args.expansion_options(expand_context.as_ref())).map_err(|e| locate(e, &args, &input))?;
+
+	match args.command {
+		Command::Expand => Ok(JsonOrNQuads::Json(expanded.as_json())),
+		Command::Compact => {
+			let local = read_context(args.context.as_deref().ok_or_else(|| Error::Usage("missing `--context` option".to_string()))?)?;
+			let processed = context::Processed::new(local.clone(), process_context(&args, &local, &mut loader)?);
+			Ok(JsonOrNQuads::Json(block_on(compaction::compact(&expanded, &processed, &mut loader, args.compaction_options()))?))
+		},
+		Command::Flatten => {
+			let flattened = flattening::flatten(&expanded, BlankIdCounter::default(), flattening::Options { ordered: args.ordered })?;
+			match &args.context {
+				Some(arg) => {
+					let local = read_context(arg)?;
+					let processed = context::Processed::new(local.clone(), process_context(&args, &local, &mut loader)?);
+					Ok(JsonOrNQuads::Json(block_on(flattening::compact_flattened(&flattened, &processed, &mut loader, args.compaction_options()))?))
+				},
+				None => Ok(JsonOrNQuads::Json(flattened.as_json()))
+			}
+		},
+		Command::Frame => {
+			let frame_arg = args.frame.as_deref().ok_or_else(|| Error::Usage("missing `--frame` option".to_string()))?;
+			let frame_doc = json::parse(&fs::read_to_string(frame_arg)?)?;
+
+			// The frame is expanded as a frame, and its context is used to compact the output.
+			let mut frame_options = args.expansion_options(None);
+			frame_options.frame_expansion = true;
+			let expanded_frame = frame_doc.expand_ordered_with_sync(args.base_iri(), &context, &mut loader, frame_options)?;
+			let frame = match expanded_frame.iter().find_map(|object| match object.inner() { Object::Node(node) => Some(node), _ => None }) {
+				Some(node) => framing::Frame::from_node(node)?,
+				None => framing::Frame::new()
+			};
+
+			let framed = framing::frame(&expanded, &frame, BlankIdCounter::default(), framing::Options::default())?;
+			let local = match &frame_doc {
+				JsonValue::Object(obj) => obj.get("@context").cloned().unwrap_or_else(JsonValue::new_object),
+				_ => JsonValue::new_object()
+			};
+			let processed = context::Processed::new(local.clone(), process_context(&args, &local, &mut loader)?);
+			let omit_graph = args.processing_mode == ProcessingMode::JsonLd1_1;
+			Ok(JsonOrNQuads::Json(block_on(framing::compact_framed(&framed, &processed, &mut loader, args.compaction_options(), omit_graph))?))
+		},
+		Command::ToRdf => {
+			Ok(JsonOrNQuads::NQuads(rdf::to_rdf(&expanded, BlankIdCounter::default(), args.rdf_options)?))
+		},
+		Command::Context | Command::FromRdf => unreachable!()
+	}
+}
+
+/// Command output.
+enum JsonOrNQuads {
+	Json(JsonValue),
+	NQuads(rdf::Dataset)
+}
+
+fn main() {
+	let args = match Args::parse(std::env::args().skip(1)) {
+		Ok(Some(args)) => args,
+		Ok(None) => {
+			println!("{}", USAGE);
+			return
+		},
+		Err(e) => {
+			eprintln!("jsonld: {}\n\n{}", e, USAGE);
+			std::process::exit(2)
+		}
+	};
+
+	let stdout = io::stdout();
+	let mut out = stdout.lock();
+	let result = run(args).and_then(|output| {
+		match output {
+			JsonOrNQuads::Json(json) => writeln!(out, "{}", json.pretty(2))?,
+			JsonOrNQuads::NQuads(dataset) => rdf::nquads::write(&mut out, &dataset)?
+		}
+
+		Ok(())
+	});
+
+	match result {
+		Ok(()) => (),
+		// The output has been closed early (for instance when piped into `head`).
+		Err(Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => (),
+		Err(e) => {
+			eprintln!("jsonld: {}", e);
+			std::process::exit(1)
+		}
+	}
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::convert::TryFrom;
+use std::collections::HashMap;
 use json::JsonValue;
 use crate::util;
 
@@ -69,3 +70,164 @@ impl fmt::Display for BlankId {
 		self.0.fmt(f)
 	}
 }
+
+/// Blank node identifier generator.
+///
+/// Used by the algorithms that need to create new blank nodes, such as flattening.
+pub trait BlankIdGenerator {
+	/// Generate a fresh blank node identifier.
+	fn generate(&mut self) -> BlankId;
+}
+
+impl<G: BlankIdGenerator> BlankIdGenerator for &mut G {
+	fn generate(&mut self) -> BlankId {
+		(**self).generate()
+	}
+}
+
+/// Default blank node identifier generator.
+///
+/// Generates the identifiers `_:b0`, `_:b1`, `_:b2`, etc.
+/// The prefix (`b` by default) can be changed using [`BlankIdCounter::with_prefix`].
+#[derive(Clone, Debug)]
+pub struct BlankIdCounter {
+	prefix: String,
+	count: usize
+}
+
+impl BlankIdCounter {
+	/// Create a new counter generating identifiers of the form `_:{prefix}{n}`.
+	pub fn with_prefix(prefix: &str) -> BlankIdCounter {
+		BlankIdCounter {
+			prefix: prefix.to_string(),
+			count: 0
+		}
+	}
+
+	/// Number of identifiers generated so far.
+	pub fn count(&self) -> usize {
+		self.count
+	}
+}
+
+impl Default for BlankIdCounter {
+	fn default() -> BlankIdCounter {
+		BlankIdCounter::with_prefix("b")
+	}
+}
+
+impl BlankIdGenerator for BlankIdCounter {
+	fn generate(&mut self) -> BlankId {
+		let id = BlankId::new(&format!("{}{}", self.prefix, self.count));
+		self.count += 1;
+		id
+	}
+}
+
+/// Blank node identifier relabeller.
+///
+/// Wraps a generator to replace existing blank node identifiers with fresh ones,
+/// preserving the mapping so that the same input identifier is always relabeled the same way.
+/// This is the *Generate Blank Node Identifier* algorithm of the JSON-LD API.
+/// See <https://www.w3.org/TR/json-ld11-api/#generate-blank-node-identifier>.
+pub struct BlankIdRelabeller<G: BlankIdGenerator> {
+	generator: G,
+	map: HashMap<BlankId, BlankId>
+}
+
+impl<G: BlankIdGenerator> BlankIdRelabeller<G> {
+	/// Create a new relabeller using the given generator.
+	pub fn new(generator: G) -> BlankIdRelabeller<G> {
+		BlankIdRelabeller {
+			generator,
+			map: HashMap::new()
+		}
+	}
+
+	/// Relabel the given blank node identifier.
+	///
+	/// If `id` has already been relabeled, the same new identifier is returned.
+	/// If `id` is `None`, a fresh identifier is generated.
+	pub fn relabel(&mut self, id: Option<&BlankId>) -> BlankId {
+		match id {
+			Some(id) => {
+				if let Some(new_id) = self.map.get(id) {
+					return new_id.clone()
+				}
+
+				let new_id = self.generator.generate();
+				self.map.insert(id.clone(), new_id.clone());
+				new_id
+			},
+			None => self.generator.generate()
+		}
+	}
+
+	/// Get the new identifier given to the input blank node identifier `id`, if any.
+	pub fn get(&self, id: &BlankId) -> Option<&BlankId> {
+		self.map.get(id)
+	}
+
+	/// Consume the relabeller and return the underlying generator.
+	pub fn into_generator(self) -> G {
+		self.generator
+	}
+}
+
+impl<G: BlankIdGenerator> BlankIdGenerator for BlankIdRelabeller<G> {
+	fn generate(&mut self) -> BlankId {
+		self.relabel(None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn generate<G: BlankIdGenerator>(generator: &mut G, n: usize) -> Vec<String> {
+		(0..n).map(|_| generator.generate().as_str().to_string()).collect()
+	}
+
+	#[test]
+	fn counter() {
+		let mut counter = BlankIdCounter::default();
+		assert_eq!(generate(&mut counter, 3), ["_:b0", "_:b1", "_:b2"]);
+		assert_eq!(counter.count(), 3);
+
+		// Generating through a mutable reference advances the same counter.
+		assert_eq!(generate(&mut &mut counter, 1), ["_:b3"]);
+
+		let mut counter = BlankIdCounter::with_prefix("n");
+		assert_eq!(generate(&mut counter, 2), ["_:n0", "_:n1"])
+	}
+
+	#[test]
+	fn relabeller() {
+		let a = BlankId::new("a");
+		let b = BlankId::new("b");
+
+		let mut relabeller = BlankIdRelabeller::new(BlankIdCounter::default());
+		assert_eq!(relabeller.relabel(Some(&b)).as_str(), "_:b0");
+		assert_eq!(relabeller.relabel(None).as_str(), "_:b1");
+		assert_eq!(relabeller.relabel(Some(&a)).as_str(), "_:b2");
+
+		// Already relabeled identifiers keep their new label.
+		assert_eq!(relabeller.relabel(Some(&b)).as_str(), "_:b0");
+		assert_eq!(relabeller.get(&a).map(BlankId::as_str), Some("_:b2"));
+		assert_eq!(relabeller.get(&BlankId::new("c")), None);
+		assert_eq!(relabeller.into_generator().count(), 3)
+	}
+
+	#[test]
+	fn relabeller_determinism() {
+		let ids: Vec<_> = ["x", "y", "x", "z", "y"].iter().map(|name| BlankId::new(name)).collect();
+		let relabel = || {
+			let mut relabeller = BlankIdRelabeller::new(BlankIdCounter::default());
+			ids.iter().map(|id| relabeller.relabel(Some(id))).collect::<Vec<_>>()
+		};
+
+		let labels = relabel();
+		assert_eq!(labels.iter().map(BlankId::as_str).collect::<Vec<_>>(), ["_:b0", "_:b1", "_:b0", "_:b2", "_:b1"]);
+		assert_eq!(relabel(), labels)
+	}
+}
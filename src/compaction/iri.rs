@@ -0,0 +1,537 @@
+use iref::IriRef;
+use crate::{
+	Error,
+	ErrorCode,
+	Id,
+	Indexed,
+	ProcessingMode,
+	Context,
+	object::{
+		Any,
+		Ref,
+		Value,
+		Literal
+	},
+	context::TypeLang,
+	syntax::{
+		Term,
+		TermLike,
+		Keyword,
+		Type
+	}
+};
+use super::{
+	Options,
+	is_graph_object
+};
+
+/// Compact the given IRI or keyword.
+///
+/// This is a shortcut for [`compact_iri_with`] when no value is associated to `var`.
+pub fn compact_iri<T: Id, C: Context<T>>(active_context: &C, var: &Term<T>, vocab: bool, reverse: bool, options: Options) -> Result<Option<String>, Error> {
+	compact_iri_opt::<T, C, Value<T>>(active_context, var, None, vocab, reverse, options)
+}
+
+/// IRI compaction algorithm.
+///
+/// Compact the given IRI or keyword `var`, taking into account the value associated to it.
+/// See <https://www.w3.org/TR/json-ld11-api/#iri-compaction>.
+pub fn compact_iri_with<T: Id, C: Context<T>, O: Any<T>>(active_context: &C, var: &Term<T>, value: &Indexed<O>, vocab: bool, reverse: bool, options: Options) -> Result<Option<String>, Error> {
+	compact_iri_opt(active_context, var, Some(value), vocab, reverse, options)
+}
+
+/// Lower-cased concatenation of a language and a direction, as found in inverse contexts.
+fn lang_dir(language: Option<&str>, direction: impl std::fmt::Display) -> String {
+	match language {
+		Some(language) => format!("{}_{}", language, direction).to_lowercase(),
+		None => format!("_{}", direction)
+	}
+}
+
+/// Type or language of an item of a list object, as required by the IRI compaction algorithm.
+///
+/// Returns a pair `(item_language, item_type)`.
+fn list_item_type_lang<T: Id>(item: &Indexed<crate::Object<T>>) -> (Option<String>, Option<String>) {
+	match Any::as_ref(item.inner()) {
+		Ref::Value(value) => match value {
+			// If item contains an @direction entry, then set item language to the
+			// concatenation of the item's @language entry (if any) the item's @direction,
+			// separated by an underscore (_), normalized to lower case.
+			// Otherwise, if item contains an @language entry, then set item language to its
+			// associated value, normalized to lower case.
+			Value::LangString(lang_str) => match (lang_str.language_tag(), lang_str.direction()) {
+				(language, Some(direction)) => (Some(lang_dir(language.map(|l| l.as_str()), direction)), None),
+				(Some(language), None) => (Some(language.normalized()), None),
+				(None, None) => (Some("@null".to_string()), None)
+			},
+			// Otherwise, if item contains a @type entry, set item type to its associated value.
+			Value::Literal(Literal::Json(_), _) => (None, Some("@json".to_string())),
+			Value::Literal(_, types) => match types.iter().next() {
+				Some(ty) => (None, Some(ty.as_str().to_string())),
+				// Otherwise, set item language to @null.
+				None => (Some("@null".to_string()), None)
+			}
+		},
+		// Otherwise, set item type to @id.
+		_ => (None, Some("@id".to_string()))
+	}
+}
+
+fn compact_iri_opt<T: Id, C: Context<T>, O: Any<T>>(active_context: &C, var: &Term<T>, value: Option<&Indexed<O>>, vocab: bool, reverse: bool, options: Options) -> Result<Option<String>, Error> {
+	// If var is null, return null.
+	if *var == Term::Null {
+		return Ok(None)
+	}
+
+	// If the active context has a null inverse context, set inverse context in active context
+	// to the result of calling the Inverse Context Creation algorithm using active context.
+	let inverse_context = active_context.inverse();
+
+	// If vocab is true and var is an entry of inverse context:
+	if vocab && inverse_context.contains(var) {
+		// Initialize default language based on the active context's default language,
+		// normalized to lower case and default base direction:
+		let default_language = match active_context.default_base_direction() {
+			// If the active context has a default base direction, the default language is the
+			// concatenation of the active context's default language and default base
+			// direction, separated by an underscore (_), normalized to lower case.
+			Some(direction) => lang_dir(active_context.default_language(), direction),
+			// Otherwise, the default language is the active context's default language,
+			// normalized to lower case.
+			None => match active_context.default_language() {
+				Some(language) => language.to_lowercase(),
+				None => "@none".to_string()
+			}
+		};
+
+		// If value is a map containing an @preserve entry, use the first element from the
+		// value of @preserve as value.
+		// NOTE: @preserve only appears during framing.
+
+		// Initialize containers to an empty array.
+		// This array will be used to keep track of an ordered list of preferred container
+		// mapping for a term, based on what is compatible with value.
+		let mut containers = Vec::new();
+
+		// Initialize type/language to @language and type/language value to @null.
+		// These two variables will keep track of the preferred type mapping or language
+		// mapping for a term, based on what is compatible with value.
+		let mut type_lang = TypeLang::Language;
+		let mut type_lang_value = None;
+
+		// If value is a map containing an @index entry, and value is not a graph object then
+		// append the values @index and @index@set to containers.
+		if let Some(value) = value {
+			if value.index().is_some() && !is_graph_object(value.inner()) {
+				containers.push("@index".to_string());
+				containers.push("@index@set".to_string());
+			}
+		}
+
+		if reverse {
+			// If reverse is true, set type/language to @type, type/language value to
+			// @reverse, and append @set to containers.
+			type_lang = TypeLang::Type;
+			type_lang_value = Some("@reverse".to_string());
+			containers.push("@set".to_string());
+		} else {
+			match value.map(|value| (Any::as_ref(value.inner()), value.index())) {
+				Some((Ref::List(list), index)) => {
+					// Otherwise, if value is a list object, then set type/language and
+					// type/language value to the most specific values that work for all items
+					// in the list as follows:
+
+					// If @index is not an entry in value, then append @list to containers.
+					if index.is_none() {
+						containers.push("@list".to_string())
+					}
+
+					// Initialize common type and common language to null.
+					// If list is empty, set common language to default language.
+					let mut common_type = None;
+					let mut common_language = if list.is_empty() {
+						Some(default_language.clone())
+					} else {
+						None
+					};
+
+					// For each item in list:
+					for item in list {
+						// Initialize item language to @none and item type to @none.
+						let (item_language, item_type) = list_item_type_lang(item);
+						let item_language = item_language.unwrap_or_else(|| "@none".to_string());
+						let item_type = item_type.unwrap_or_else(|| "@none".to_string());
+
+						if item.is_value() {
+							// If common language is null, set common language to item
+							// language.
+							// Otherwise, if item language does not equal common language and
+							// item contains a @value entry, then set common language to @none
+							// because list items have conflicting languages.
+							match &common_language {
+								None => common_language = Some(item_language),
+								Some(language) if *language != item_language => common_language = Some("@none".to_string()),
+								_ => ()
+							}
+						} else if common_language.is_none() {
+							common_language = Some(item_language)
+						}
+
+						// If common type is null, set common type to item type.
+						// Otherwise, if item type does not equal common type, then set common
+						// type to @none because list items have conflicting types.
+						match &common_type {
+							None => common_type = Some(item_type),
+							Some(ty) if *ty != item_type => common_type = Some("@none".to_string()),
+							_ => ()
+						}
+
+						// If common language is @none and common type is @none, then stop
+						// processing items in the list because it has been detected that there
+						// is no common language or type amongst the items.
+						if common_language.as_deref() == Some("@none") && common_type.as_deref() == Some("@none") {
+							break
+						}
+					}
+
+					// If common language is null, set common language to @none.
+					let common_language = common_language.unwrap_or_else(|| "@none".to_string());
+
+					// If common type is null, set common type to @none.
+					let common_type = common_type.unwrap_or_else(|| "@none".to_string());
+
+					if common_type != "@none" {
+						// If common type is not @none then set type/language to @type and
+						// type/language value to common type.
+						type_lang = TypeLang::Type;
+						type_lang_value = Some(common_type);
+					} else {
+						// Otherwise, set type/language value to common language.
+						type_lang_value = Some(common_language);
+					}
+				},
+				Some((Ref::Node(node), index)) if is_graph_object(node) => {
+					// Otherwise, if value is a graph object, prefer a mapping most appropriate
+					// for the particular value.
+					if index.is_some() {
+						// If value contains an @index entry, append the values @graph@index
+						// and @graph@index@set to containers.
+						containers.push("@graph@index".to_string());
+						containers.push("@graph@index@set".to_string());
+					}
+
+					if node.id.is_some() {
+						// If value contains an @id entry, append the values @graph@id and
+						// @graph@id@set to containers.
+						containers.push("@graph@id".to_string());
+						containers.push("@graph@id@set".to_string());
+					}
+
+					// Append the values @graph, @graph@set, and @set to containers.
+					containers.push("@graph".to_string());
+					containers.push("@graph@set".to_string());
+					containers.push("@set".to_string());
+
+					if index.is_none() {
+						// If value does not contain an @index entry, append the values
+						// @graph@index and @graph@index@set to containers.
+						containers.push("@graph@index".to_string());
+						containers.push("@graph@index@set".to_string());
+					}
+
+					if node.id.is_none() {
+						// If the value does not contain an @id entry, append the values
+						// @graph@id and @graph@id@set to containers.
+						containers.push("@graph@id".to_string());
+						containers.push("@graph@id@set".to_string());
+					}
+
+					// Append the values @index and @index@set to containers.
+					containers.push("@index".to_string());
+					containers.push("@index@set".to_string());
+
+					// Set type/language to @type and set type/language value to @id.
+					type_lang = TypeLang::Type;
+					type_lang_value = Some("@id".to_string());
+				},
+				Some((Ref::Value(v), index)) => {
+					// If value is a value object:
+					match v {
+						Value::LangString(lang_str) if index.is_none() && (lang_str.language().is_some() || lang_str.direction().is_some()) => {
+							// If value contains an @direction entry and does not contain an
+							// @index entry, then set type/language value to the concatenation
+							// of the value's @language entry (if any) and the value's
+							// @direction entry, separated by an underscore (_), normalized to
+							// lower case.
+							// Otherwise, if value contains an @language entry and does not
+							// contain an @index entry, then set type/language value to the
+							// value of @language normalized to lower case.
+							// In both cases, append @language and @language@set to containers.
+							type_lang_value = Some(match lang_str.direction() {
+								Some(direction) => lang_dir(lang_str.language(), direction),
+								None => lang_str.language_tag().unwrap().normalized()
+							});
+
+							containers.push("@language".to_string());
+							containers.push("@language@set".to_string());
+						},
+						Value::Literal(Literal::Json(_), _) => {
+							// Otherwise, if value contains an @type entry, set type/language
+							// value to its associated value and set type/language to @type.
+							type_lang = TypeLang::Type;
+							type_lang_value = Some("@json".to_string())
+						},
+						Value::Literal(_, types) if !types.is_empty() => {
+							type_lang = TypeLang::Type;
+							type_lang_value = Some(types.iter().next().unwrap().as_str().to_string())
+						},
+						_ => ()
+					}
+
+					// Append @set to containers.
+					containers.push("@set".to_string());
+				},
+				_ => {
+					// Otherwise, set type/language to @type and set type/language value to
+					// @id, and append @id, @id@set, @type, and @set@type, to containers.
+					type_lang = TypeLang::Type;
+					type_lang_value = Some("@id".to_string());
+					containers.push("@id".to_string());
+					containers.push("@id@set".to_string());
+					containers.push("@type".to_string());
+					containers.push("@set@type".to_string());
+
+					// Append @set to containers.
+					containers.push("@set".to_string());
+				}
+			}
+		}
+
+		// Append @none to containers.
+		// This represents the non-existence of a container mapping, and it will be the last
+		// container mapping value to be checked as it is the most generic.
+		containers.push("@none".to_string());
+
+		// If processing mode is not json-ld-1.0 and value is not a map or does not contain an
+		// @index entry, append @index and @index@set to containers.
+		if options.processing_mode != ProcessingMode::JsonLd1_0 && value.map(|value| value.index().is_none()).unwrap_or(true) {
+			containers.push("@index".to_string());
+			containers.push("@index@set".to_string());
+		}
+
+		// If processing mode is not json-ld-1.0 and value is a map containing only an @value
+		// entry, append @language and @language@set to containers.
+		if options.processing_mode != ProcessingMode::JsonLd1_0 {
+			if let Some(value) = value {
+				if value.index().is_none() {
+					let only_value = match Any::as_ref(value.inner()) {
+						Ref::Value(Value::Literal(Literal::Json(_), _)) => false,
+						Ref::Value(Value::Literal(_, types)) => types.is_empty(),
+						Ref::Value(Value::LangString(lang_str)) => lang_str.language().is_none() && lang_str.direction().is_none(),
+						_ => false
+					};
+
+					if only_value {
+						containers.push("@language".to_string());
+						containers.push("@language@set".to_string());
+					}
+				}
+			}
+		}
+
+		// If type/language value is null, set type/language value to @null.
+		// This is the key under which null values are stored in the inverse context entry.
+		let type_lang_value = type_lang_value.unwrap_or_else(|| "@null".to_string());
+
+		// Initialize preferred values to an empty array.
+		// This array will indicate, in order, the preferred values for a term's type mapping
+		// or language mapping.
+		let mut preferred_values = Vec::new();
+
+		// If type/language value is @reverse, append @reverse to preferred values.
+		if type_lang_value == "@reverse" {
+			preferred_values.push("@reverse".to_string());
+		}
+
+		// If type/language value is @id or @reverse and value is a map containing an @id
+		// entry:
+		let value_id = value.and_then(|value| value.id());
+		match value_id {
+			Some(id) if type_lang_value == "@id" || type_lang_value == "@reverse" => {
+				// If the result of IRI compacting the value of the @id entry in value has a
+				// term definition in the active context with an IRI mapping that equals the
+				// value of the @id entry in value, then append @vocab, @id, and @none, in
+				// that order, to preferred values.
+				let compacted_iri = match id {
+					crate::Lenient::Ok(id) => compact_iri(active_context, &id.clone().into(), true, false, options)?,
+					crate::Lenient::Unknown(id) => Some(id.clone())
+				};
+
+				let has_same_mapping = match compacted_iri {
+					Some(compacted_iri) => match active_context.get(compacted_iri.as_str()) {
+						Some(def) => match &def.value {
+							Some(iri_mapping) => iri_mapping.as_str() == id.as_str(),
+							None => false
+						},
+						None => false
+					},
+					None => false
+				};
+
+				if has_same_mapping {
+					preferred_values.push("@vocab".to_string());
+					preferred_values.push("@id".to_string());
+					preferred_values.push("@none".to_string());
+				} else {
+					// Otherwise, append @id, @vocab, and @none, in that order, to preferred
+					// values.
+					preferred_values.push("@id".to_string());
+					preferred_values.push("@vocab".to_string());
+					preferred_values.push("@none".to_string());
+				}
+			},
+			_ => {
+				// Otherwise, append type/language value and @none, in that order, to
+				// preferred values.
+				preferred_values.push(type_lang_value);
+				preferred_values.push("@none".to_string());
+
+				// If value is a list object with an empty array as the value of @list, set
+				// type/language to @any.
+				if let Some(value) = value {
+					if let Ref::List(list) = Any::as_ref(value.inner()) {
+						if list.is_empty() {
+							type_lang = TypeLang::Any;
+						}
+					}
+				}
+			}
+		}
+
+		// Append @any to preferred values.
+		preferred_values.push("@any".to_string());
+
+		// If preferred values contains any entry having an underscore (_), append the
+		// substring of that entry from the underscore to the end of the string to preferred
+		// values.
+		let underscored: Vec<_> = preferred_values.iter().filter_map(|value| {
+			value.find('_').map(|i| value[i..].to_string())
+		}).collect();
+		preferred_values.extend(underscored);
+
+		// Initialize term to the result of the Term Selection algorithm, passing var,
+		// containers, type/language, and preferred values.
+		// If term is not null, return term.
+		if let Some(term) = inverse_context.select(var, &containers, type_lang, &preferred_values) {
+			return Ok(Some(term.to_string()))
+		}
+	}
+
+	// At this point, there is no simple term that var can be compacted to.
+	// If vocab is true and active context has a vocabulary mapping:
+	if vocab {
+		if let Some(vocab_mapping) = active_context.vocabulary() {
+			// If var begins with the vocabulary mapping's value and its length is greater
+			// than the length of the vocabulary mapping, set suffix to the substring of var
+			// that does not match. If suffix does not have a term definition in active
+			// context, then return suffix.
+			let vocab_mapping = vocab_mapping.as_str();
+			if let Some(suffix) = var.as_str().strip_prefix(vocab_mapping) {
+				if !suffix.is_empty() && active_context.get(suffix).is_none() {
+					return Ok(Some(suffix.to_string()))
+				}
+			}
+		}
+	}
+
+	// The var could not be compacted using the active context's vocabulary mapping.
+	// Try to create a compact IRI, starting by initializing compact IRI to null.
+	// This variable will be used to store the created compact IRI, if any.
+	let mut compact_iri_result = String::new();
+
+	// For each term definition definition in active context:
+	for (key, definition) in active_context.definitions() {
+		// If the IRI mapping of definition is null, its IRI mapping equals var, its IRI
+		// mapping is not a substring at the beginning of var, or definition does not have a
+		// true prefix flag, definition's key cannot be used as a prefix.
+		// Continue with the next definition.
+		match &definition.value {
+			Some(iri_mapping) if definition.prefix => {
+				if let Some(suffix) = var.as_str().strip_prefix(iri_mapping.as_str()) {
+					if !suffix.is_empty() {
+						// Initialize candidate by concatenating definition key, a colon (:),
+						// and the substring of var that follows after the value of the
+						// definition's IRI mapping.
+						let candidate = key.clone() + ":" + suffix;
+
+						// If either compact IRI is null, candidate is shorter or the same
+						// length but lexicographically less than compact IRI and candidate
+						// does not have a term definition in active context, or if that term
+						// definition has an IRI mapping that equals var and value is null,
+						// set compact IRI to candidate.
+						let candidate_def = active_context.get(&candidate);
+						let is_usable = match candidate_def {
+							Some(def) => value.is_none() && def.value.as_ref() == Some(var),
+							None => true
+						};
+
+						if is_usable && (compact_iri_result.is_empty() || candidate.len() < compact_iri_result.len() || (candidate.len() == compact_iri_result.len() && candidate < compact_iri_result)) {
+							compact_iri_result = candidate
+						}
+					}
+				}
+			},
+			_ => ()
+		}
+	}
+
+	// If compact IRI is not null, return compact IRI.
+	if !compact_iri_result.is_empty() {
+		return Ok(Some(compact_iri_result))
+	}
+
+	// To ensure that the IRI var is not confused with a compact IRI, if the IRI scheme of
+	// var matches any term in active context with prefix flag set to true, and var has no
+	// IRI authority (preceded by double-forward-slash (//), an IRI confused with prefix
+	// error has been detected, and processing is aborted.
+	if let Some(iri) = var.as_iri() {
+		if let Some(definition) = active_context.get(iri.scheme().as_str()) {
+			if definition.prefix && iri.authority().is_none() {
+				return Err(ErrorCode::IriConfusedWithPrefix.into())
+			}
+		}
+	}
+
+	// If vocab is false, transform var to a relative IRI reference using the base IRI from
+	// active context, if it exists.
+	if !vocab && options.compact_to_relative {
+		if let Some(base_iri) = active_context.base_iri() {
+			if let Some(iri) = var.as_iri() {
+				return Ok(Some(IriRef::from(iri).relative_to(base_iri).as_str().to_string()))
+			}
+		}
+	}
+
+	// Finally, return var as is.
+	Ok(Some(var.as_str().to_string()))
+}
+
+/// Compact a keyword.
+///
+/// Keywords are always compacted with `vocab` set to `true`, and never fail to compact.
+pub(crate) fn compact_key<T: Id, C: Context<T>>(active_context: &C, keyword: Keyword, options: Options) -> Result<String, Error> {
+	Ok(compact_iri(active_context, &Term::Keyword(keyword), true, false, options)?.unwrap())
+}
+
+/// Compact a value type.
+pub(crate) fn compact_type<T: Id, C: Context<T>>(active_context: &C, ty: &Type<T>, options: Options) -> Result<String, Error> {
+	let term = match ty {
+		Type::Id => Term::Keyword(Keyword::Id),
+		Type::Json => Term::Keyword(Keyword::Json),
+		Type::None => Term::Keyword(Keyword::None),
+		Type::Vocab => Term::Keyword(Keyword::Vocab),
+		Type::Ref(id) => Term::from(id.clone())
+	};
+
+	Ok(compact_iri(active_context, &term, true, false, options)?.unwrap())
+}
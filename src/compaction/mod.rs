@@ -0,0 +1,583 @@
+//! Compaction algorithm and types.
+
+mod iri;
+mod value;
+mod node;
+
+use futures::future::{BoxFuture, FutureExt};
+use mown::Mown;
+use json::JsonValue;
+use crate::{
+	ProcessingMode,
+	Error,
+	Id,
+	Indexed,
+	Object,
+	Node,
+	ContextMut,
+	object::{
+		Any,
+		Ref,
+		Value,
+		Literal
+	},
+	context::{
+		Local,
+		Loader,
+		Processed,
+		ProcessingStack,
+		ProcessingOptions
+	},
+	syntax::{
+		Keyword,
+		ContainerType
+	},
+	generic_json::Json,
+	util::AsJson
+};
+
+pub use iri::*;
+pub use value::*;
+pub use node::*;
+
+#[derive(Clone, Copy)]
+pub struct Options {
+	/// Sets the processing mode.
+	pub processing_mode: ProcessingMode,
+
+	/// Determines if IRIs are compacted relative to the base IRI of the active context.
+	pub compact_to_relative: bool,
+
+	/// If set to true, arrays with just one element are replaced with that element during
+	/// compaction.
+	/// If false, all arrays will remain arrays even if they have just one element.
+	pub compact_arrays: bool,
+
+	/// If set to true, properties are processed lexicographically.
+	/// If false, order is not considered in processing.
+	pub ordered: bool
+}
+
+impl<'a> From<Options> for ProcessingOptions<'a> {
+	fn from(options: Options) -> ProcessingOptions<'a> {
+		ProcessingOptions {
+			processing_mode: options.processing_mode,
+			..ProcessingOptions::default()
+		}
+	}
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {
+			processing_mode: ProcessingMode::default(),
+			compact_to_relative: true,
+			compact_arrays: true,
+			ordered: false
+		}
+	}
+}
+
+/// Checks if the given object is a graph object.
+///
+/// A graph object is a node object with a `@graph` entry, and no other entries than `@id` and
+/// `@index`.
+pub(crate) fn is_graph_object<T: Id, O: Any<T>>(object: &O) -> bool {
+	match object.as_ref() {
+		Ref::Node(node) => {
+			node.graph.is_some()
+			&& node.types.is_empty()
+			&& node.included.is_none()
+			&& node.properties.is_empty()
+			&& node.reverse_properties.is_empty()
+		},
+		_ => false
+	}
+}
+
+/// Checks if the given object is a simple graph object, a graph object without `@id` entry.
+pub(crate) fn is_simple_graph_object<T: Id, O: Any<T>>(object: &O) -> bool {
+	is_graph_object(object) && object.id().is_none()
+}
+
+/// Checks if the given node is a node reference, a node object with an `@id` entry and no
+/// other entries (except for `@index`, not stored in the node itself).
+pub(crate) fn is_node_reference<T: Id>(node: &Node<T>) -> bool {
+	node.id.is_some()
+	&& node.types.is_empty()
+	&& node.graph.is_none()
+	&& node.included.is_none()
+	&& node.properties.is_empty()
+	&& node.reverse_properties.is_empty()
+}
+
+/// Add a value to the given entry of a JSON object.
+///
+/// If `as_array` is true, the entry is always turned into an array.
+/// If `value` is an array, each of its items is added to the entry.
+/// See <https://www.w3.org/TR/json-ld11-api/#dfn-add-value>.
+pub fn add_value(map: &mut json::object::Object, key: &str, value: JsonValue, as_array: bool) {
+	// If as array is true and the value of key in object does not exist or is not an array,
+	// set it to a new array containing any original value.
+	match map.get_mut(key) {
+		Some(JsonValue::Array(_)) => (),
+		Some(original_value) => {
+			if as_array {
+				let original_value_json = original_value.take();
+				*original_value = JsonValue::Array(vec![original_value_json])
+			}
+		},
+		None => {
+			if as_array {
+				map.insert(key, JsonValue::new_array())
+			}
+		}
+	}
+
+	match value {
+		// If value is an array, then for each element v in value, use add value recursively
+		// to add v to key in entry.
+		JsonValue::Array(values) => {
+			for value in values {
+				add_value(map, key, value, false)
+			}
+		},
+		value => {
+			match map.get_mut(key) {
+				// If the value of the key entry in object is not an array, set it to a new
+				// array containing the original value.
+				// Append value to the value of the key entry in object.
+				Some(JsonValue::Array(values)) => values.push(value),
+				Some(original_value) => {
+					let original_value_json = original_value.take();
+					*original_value = JsonValue::Array(vec![original_value_json, value])
+				},
+				// If key is not an entry in object, add value as the value of key in object.
+				None => map.insert(key, value)
+			}
+		}
+	}
+}
+
+/// Get the value of the given entry of a JSON object as a map, creating it if necessary.
+pub(crate) fn get_or_insert_map<'a>(map: &'a mut json::object::Object, key: &str) -> &'a mut json::object::Object {
+	match map.get(key) {
+		Some(JsonValue::Object(_)) => (),
+		_ => map.insert(key, JsonValue::new_object())
+	}
+
+	match map.get_mut(key) {
+		Some(JsonValue::Object(map)) => map,
+		_ => unreachable!()
+	}
+}
+
+/// Apply the type-scoped contexts of the given (compacted) types to the active context.
+///
+/// The types are processed in lexicographical order. Type-scoped contexts are not
+/// propagated.
+async fn apply_type_scoped_contexts<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(mut active_context: Mown<'a, C>, type_scoped_context: &'a C, compacted_types: &[String], loader: &mut L, options: Options) -> Result<Mown<'a, C>, Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	let mut compacted_types: Vec<_> = compacted_types.iter().collect();
+	compacted_types.sort();
+
+	for term in compacted_types {
+		// If the term definition for term in type-scoped context has a local context set
+		// active context to the result of the Context Processing algorithm, passing active
+		// context, the value of term's local context in type-scoped context as local
+		// context, base URL from the term definition for term in type-scoped context, and
+		// false for propagate.
+		if let Some(term_definition) = type_scoped_context.get(term.as_str()) {
+			if let Some(local_context) = &term_definition.context {
+				let base_url = term_definition.base_url.as_ref().map(|url| url.as_iri());
+				active_context = Mown::Owned(local_context.process_with(active_context.as_ref(), ProcessingStack::new(), loader, base_url, ProcessingOptions::from(options).without_propagation()).await?)
+			}
+		}
+	}
+
+	Ok(active_context)
+}
+
+/// Compaction algorithm for a collection of objects.
+///
+/// This is the array case of the compaction algorithm: each item is compacted, and the result
+/// is reduced to a single item when possible (depending on the `compact_arrays` option and
+/// container mapping of the active property).
+/// See <https://www.w3.org/TR/json-ld11-api/#compaction-algorithm>.
+pub fn compact_collection<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader, O: 'a + Send + Sync + Any<T>, I: 'a + Send + Iterator<Item = &'a Indexed<O>>>(items: I, active_context: &'a C, active_property: Option<&'a str>, loader: &'a mut L, options: Options) -> BoxFuture<'a, Result<JsonValue, Error>> where C::LocalContext: Send + Sync, L::Output: Json {
+	async move {
+		// Initialize result to an empty array.
+		let mut result = Vec::new();
+
+		// For each item in element:
+		for item in items {
+			// Initialize compacted item to the result of using this algorithm recursively,
+			// passing active context, active property, item for element, and the
+			// compactArrays and ordered flags.
+			let compacted_item = compact_indexed(item, active_context, active_property, loader, options).await?;
+
+			// If compacted item is not null, then append it to result.
+			if !compacted_item.is_null() {
+				result.push(compacted_item)
+			}
+		}
+
+		// If result is empty or contains more than one value, or compactArrays is false, or
+		// active property is either @graph or @set, or container mapping for active property
+		// in active context includes either @list or @set, return result.
+		let container_mapping = active_context.get_opt(active_property).map(|def| &def.container);
+		let keep_array = result.is_empty()
+			|| result.len() > 1
+			|| !options.compact_arrays
+			|| active_property == Some("@graph")
+			|| active_property == Some("@set")
+			|| container_mapping.map(|c| c.contains(ContainerType::List) || c.contains(ContainerType::Set)).unwrap_or(false);
+
+		if keep_array {
+			return Ok(JsonValue::Array(result))
+		}
+
+		// Otherwise, return the value in result.
+		Ok(result.into_iter().next().unwrap())
+	}.boxed()
+}
+
+/// Compaction algorithm.
+///
+/// Compact the given (indexed) object using the given active context.
+/// See <https://www.w3.org/TR/json-ld11-api/#compaction-algorithm>.
+pub fn compact_indexed<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader, O: Send + Sync + Any<T>>(element: &'a Indexed<O>, active_context: &'a C, active_property: Option<&'a str>, loader: &'a mut L, options: Options) -> BoxFuture<'a, Result<JsonValue, Error>> where C::LocalContext: Send + Sync, L::Output: Json {
+	async move {
+		// Initialize type-scoped context to active context.
+		// This is used for compacting values that may be relevant to any previous type-scoped
+		// context.
+		let type_scoped_context = active_context;
+		let mut active_context = Mown::Borrowed(active_context);
+
+		// If active context has a previous context, the active context is not propagated.
+		// If element does not contain an @value entry, and element does not consist of a
+		// single @id entry, set active context to previous context from active context, as the
+		// scope of a term-scoped context does not apply when processing new node objects.
+		if let Some(previous_context) = active_context.previous_context() {
+			let keep_context = match Any::as_ref(element.inner()) {
+				Ref::Value(_) => true,
+				Ref::Node(node) => element.index().is_none() && is_node_reference(node),
+				Ref::List(_) => false
+			};
+
+			if !keep_context {
+				active_context = Mown::Owned(previous_context.clone())
+			}
+		}
+
+		// If the term definition for active property in active context has a local context:
+		if let Some(active_property_definition) = type_scoped_context.get_opt(active_property) {
+			if let Some(local_context) = &active_property_definition.context {
+				// Set active context to the result of the Context Processing algorithm,
+				// passing active context, the value of the active property's local context
+				// as local context, base URL from the term definition for active property in
+				// active context, and true for override protected.
+				let base_url = active_property_definition.base_url.as_ref().map(|url| url.as_iri());
+				active_context = Mown::Owned(local_context.process_with(active_context.as_ref(), ProcessingStack::new(), loader, base_url, ProcessingOptions::from(options).with_override()).await?)
+			}
+		}
+
+		let is_json_type_mapping = match active_context.get_opt(active_property) {
+			Some(def) => def.typ == Some(crate::syntax::Type::Json),
+			None => false
+		};
+
+		match Any::as_ref(element.inner()) {
+			Ref::Value(value) => {
+				// If element has an @value or @id entry and the result of using the Value
+				// Compaction algorithm, passing active context, active property, and element
+				// as value is a scalar, or the term definition for active property has a type
+				// mapping of @json, return that result.
+				let compacted_value = compact_indexed_value(value, element.index(), active_context.as_ref(), active_property, options)?;
+				if !compacted_value.is_object() || is_json_type_mapping {
+					return Ok(compacted_value)
+				}
+
+				compact_value_object(value, element.index(), active_context.as_ref(), type_scoped_context, active_property, loader, options).await
+			},
+			Ref::Node(node) => {
+				if is_node_reference(node) {
+					let compacted_value = compact_indexed_node_ref(node, element.index(), active_context.as_ref(), active_property, options)?;
+					if !compacted_value.is_object() || is_json_type_mapping {
+						return Ok(compacted_value)
+					}
+				}
+
+				compact_indexed_node(node, element.index(), active_context.as_ref(), type_scoped_context, active_property, loader, options).await
+			},
+			Ref::List(list) => {
+				// If element is a list object, and the container mapping for active property
+				// in active context includes @list, return the result of using this algorithm
+				// recursively, passing active context, active property, value of @list in
+				// element for element, and the compactArrays and ordered flags.
+				let is_list_container = match active_context.get_opt(active_property) {
+					Some(def) => def.container.contains(ContainerType::List),
+					None => false
+				};
+
+				if is_list_container {
+					return compact_collection(list.iter(), active_context.as_ref(), active_property, loader, options).await
+				}
+
+				// Otherwise the list object is kept as a map.
+				let mut result = json::object::Object::new();
+
+				let alias = compact_key(active_context.as_ref(), Keyword::List, options)?;
+				let compacted_list = compact_collection(list.iter(), active_context.as_ref(), Some(alias.as_str()), loader, options).await?;
+				add_value(&mut result, &alias, compacted_list, true);
+
+				if let Some(index) = element.index() {
+					let alias = compact_key(active_context.as_ref(), Keyword::Index, options)?;
+					result.insert(&alias, index.as_json());
+				}
+
+				Ok(JsonValue::Object(result))
+			}
+		}
+	}.boxed()
+}
+
+/// Compact a value object that cannot be compacted into a scalar value.
+///
+/// Each entry of the value object is compacted, following the order of their expanded keys.
+async fn compact_value_object<T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(value: &Value<T>, index: Option<&str>, active_context: &C, type_scoped_context: &C, active_property: Option<&str>, loader: &mut L, options: Options) -> Result<JsonValue, Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	// If element has an @type entry, create a new array compacted types initialized by
+	// transforming each expanded type of that entry into its compacted form by IRI compacting
+	// expanded type.
+	let mut compacted_types = Vec::new();
+	if let Value::Literal(lit, types) = value {
+		for ty in types {
+			compacted_types.push(compact_type(active_context, &crate::syntax::Type::Ref(ty.clone()), options)?)
+		}
+
+		if let Literal::Json(_) = lit {
+			compacted_types.push(compact_type(active_context, &crate::syntax::Type::Json, options)?)
+		}
+	}
+
+	// Then, for each term in compacted types ordered lexicographically, apply its type-scoped
+	// context.
+	let active_context = apply_type_scoped_contexts(Mown::Borrowed(active_context), type_scoped_context, &compacted_types, loader, options).await?;
+	let active_context = active_context.as_ref();
+
+	// Initialize result to a new empty map.
+	let mut result = json::object::Object::new();
+
+	// For each key expanded property and value expanded value in element, ordered
+	// lexicographically by expanded property if ordered is true:
+	if let Value::LangString(lang_str) = value {
+		if let Some(direction) = lang_str.direction() {
+			result.insert(&compact_key(active_context, Keyword::Direction, options)?, direction.as_json())
+		}
+	}
+
+	if let Some(index) = index {
+		// If expanded property is @index and active property has a container mapping in
+		// active context that includes @index, then the compacted result will be inside of
+		// an @index container, drop the @index entry by continuing to the next expanded
+		// property.
+		let is_index_container = match active_context.get_opt(active_property) {
+			Some(def) => def.container.contains(ContainerType::Index),
+			None => false
+		};
+
+		if !is_index_container {
+			result.insert(&compact_key(active_context, Keyword::Index, options)?, index.as_json())
+		}
+	}
+
+	if let Value::LangString(lang_str) = value {
+		if let Some(language) = lang_str.language() {
+			result.insert(&compact_key(active_context, Keyword::Language, options)?, language.as_json())
+		}
+	}
+
+	if !compacted_types.is_empty() {
+		// If expanded property is @type, compact each type using the type-scoped context.
+		let mut compacted_value = Vec::new();
+		if let Value::Literal(lit, types) = value {
+			for ty in types {
+				compacted_value.push(compact_type(type_scoped_context, &crate::syntax::Type::Ref(ty.clone()), options)?.as_json())
+			}
+
+			if let Literal::Json(_) = lit {
+				compacted_value.push(compact_type(type_scoped_context, &crate::syntax::Type::Json, options)?.as_json())
+			}
+		}
+
+		// Initialize alias by IRI compacting expanded property.
+		let alias = compact_key(active_context, Keyword::Type, options)?;
+
+		// Initialize as array to true if processing mode is json-ld-1.1 and the container
+		// mapping for alias in the active context includes @set, otherwise to the negation
+		// of compactArrays.
+		let as_array = if options.processing_mode == ProcessingMode::JsonLd1_1 && active_context.get(alias.as_str()).map(|def| def.container.contains(ContainerType::Set)).unwrap_or(false) {
+			true
+		} else {
+			!options.compact_arrays
+		};
+
+		// Use add value to add compacted value to the alias entry in result using as array.
+		add_value(&mut result, &alias, JsonValue::Array(compacted_value), as_array)
+	}
+
+	let value_json = match value {
+		Value::Literal(lit, _) => literal_as_json(lit),
+		Value::LangString(lang_str) => lang_str.as_str().as_json()
+	};
+
+	result.insert(&compact_key(active_context, Keyword::Value, options)?, value_json);
+
+	Ok(JsonValue::Object(result))
+}
+
+/// Add the given local context to a compacted document, in a leading `@context` entry.
+///
+/// Nothing is added if the local context is empty.
+pub(crate) fn with_context<L: AsJson>(local_context: &L, mut compacted_output: json::object::Object) -> json::object::Object {
+	let local_context = local_context.as_json();
+	let is_empty_context = match &local_context {
+		JsonValue::Null => true,
+		JsonValue::Object(map) => map.is_empty(),
+		JsonValue::Array(items) => items.is_empty(),
+		_ => false
+	};
+
+	if is_empty_context {
+		compacted_output
+	} else {
+		let mut map = json::object::Object::with_capacity(compacted_output.len() + 1);
+		map.insert(Keyword::Context.into_str(), local_context);
+		for (key, value) in compacted_output.iter_mut() {
+			map.insert(key, value.take())
+		}
+
+		map
+	}
+}
+
+/// Document compaction.
+///
+/// Compact the given expanded document using the given processed context.
+/// The local context of `context` is reattached to the result in the `@context` entry,
+/// unless it is empty.
+///
+/// The top-level items of the document are compacted in the iteration order of `input`,
+/// which may be an [`ExpandedDocument`](crate::ExpandedDocument) or an [`OrderedExpandedDocument`](crate::OrderedExpandedDocument).
+/// Since an `ExpandedDocument` has no specific order, the latter must be used to get a
+/// deterministic `@graph` when the [`ordered`](Options::ordered) option is set.
+/// See <https://www.w3.org/TR/json-ld11-api/#dom-jsonldprocessor-compact>.
+pub async fn compact<'a, T: 'a + Send + Sync + Id, I: IntoIterator<Item = &'a Indexed<Object<T>>>, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(input: I, context: &'a Processed<C::LocalContext, C>, loader: &'a mut L, options: Options) -> Result<JsonValue, Error> where I::IntoIter: 'a + Send, C::LocalContext: Send + Sync, L::Output: Json {
+	let active_context = context.processed();
+
+	// Set compacted output to the result of using the Compaction algorithm, passing active
+	// context, null for active property, expanded input as element, and if passed, the
+	// compactArrays and ordered flags in options.
+	let compacted_output = compact_collection(input.into_iter(), active_context, None, loader, options).await?;
+
+	// If compacted output is an empty array, replace it with a new map.
+	// Otherwise, if compacted output is an array, replace it with a new map with a single
+	// entry whose key is the result of IRI compacting @graph and value is compacted output.
+	let compacted_output = match compacted_output {
+		JsonValue::Array(items) => {
+			let mut map = json::object::Object::new();
+			if !items.is_empty() {
+				map.insert(&compact_key(active_context, Keyword::Graph, options)?, JsonValue::Array(items))
+			}
+
+			map
+		},
+		JsonValue::Object(map) => map,
+		_ => json::object::Object::new()
+	};
+
+	// If context was not null, add an @context entry to compacted output and set its value
+	// to the provided context.
+	let compacted_output = with_context(context.local(), compacted_output);
+
+	Ok(JsonValue::Object(compacted_output))
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use crate::{
+		Document,
+		NoLoader,
+		expansion,
+		context::{
+			JsonContext,
+			Local
+		}
+	};
+	use super::*;
+
+	fn compact_with(doc: &str, context: &str, options: Options) -> JsonValue {
+		let doc = json::parse(doc).unwrap();
+		let local = json::parse(context).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = block_on(doc.expand_ordered_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+		let processed = Processed::new(local.clone(), block_on(local.process(&context, &mut NoLoader, None)).unwrap());
+		block_on(compact(&expanded, &processed, &mut NoLoader, options)).unwrap()
+	}
+
+	#[test]
+	fn terms_and_compact_iris() {
+		let doc = r#"{
+			"@id": "http://example.org/a",
+			"http://example.org/name": "Alice",
+			"http://example.org/knows": {"@id": "http://example.org/b"}
+		}"#;
+		let context = r#"{
+			"ex": "http://example.org/",
+			"name": "http://example.org/name",
+			"knows": {"@id": "http://example.org/knows", "@type": "@id"}
+		}"#;
+
+		let expected = json::parse(&format!(r#"{{
+			"@context": {},
+			"@id": "ex:a",
+			"name": "Alice",
+			"knows": "ex:b"
+		}}"#, context)).unwrap();
+
+		assert_eq!(compact_with(doc, context, Options::default()), expected)
+	}
+
+	#[test]
+	fn compact_arrays() {
+		let doc = r#"{"http://example.org/name": "Alice"}"#;
+		let context = r#"{"name": "http://example.org/name"}"#;
+
+		let options = Options {
+			compact_arrays: false,
+			..Options::default()
+		};
+		// Without array compaction, the top-level node is itself wrapped in a `@graph` array.
+		assert_eq!(compact_with(doc, context, options)["@graph"][0]["name"], json::array!["Alice"]);
+		assert_eq!(compact_with(doc, context, Options::default())["name"], "Alice")
+	}
+
+	#[test]
+	fn top_level_graph_order() {
+		let doc = r#"[
+			{"@id": "http://example.org/c", "http://example.org/name": "c"},
+			{"@id": "http://example.org/a", "http://example.org/name": "a"},
+			{"@id": "http://example.org/b", "http://example.org/name": "b"}
+		]"#;
+		let context = r#"{"@vocab": "http://example.org/"}"#;
+
+		let options = Options {
+			ordered: true,
+			..Options::default()
+		};
+		for _ in 0..8 {
+			let ids: Vec<_> = compact_with(doc, context, options)["@graph"].members().map(|node| node["@id"].to_string()).collect();
+			assert_eq!(ids, vec!["http://example.org/c", "http://example.org/a", "http://example.org/b"])
+		}
+	}
+}
@@ -0,0 +1,642 @@
+use indexmap::IndexSet;
+use mown::Mown;
+use json::JsonValue;
+use crate::{
+	ProcessingMode,
+	Error,
+	ErrorCode,
+	Id,
+	Indexed,
+	Object,
+	Node,
+	Reference,
+	Lenient,
+	ContextMut,
+	object::{
+		Any,
+		Ref,
+		Value
+	},
+	context::Loader,
+	syntax::{
+		Keyword,
+		Term,
+		ContainerType
+	},
+	expansion,
+	generic_json::Json,
+	util::AsJson
+};
+use super::{
+	Options,
+	compact_iri,
+	compact_iri_with,
+	compact_key,
+	compact_indexed,
+	compact_collection,
+	apply_type_scoped_contexts,
+	add_value,
+	get_or_insert_map,
+	is_graph_object,
+	is_simple_graph_object,
+	literal_as_json
+};
+
+/// Node object entry.
+enum Entry<'a, T: Id> {
+	Id(&'a Lenient<Reference<T>>),
+	Type,
+	Graph(&'a IndexSet<Indexed<Object<T>>>),
+	Included(&'a IndexSet<Indexed<Node<T>>>),
+	Index(&'a str),
+	Reverse,
+	Property(&'a Reference<T>, &'a [Indexed<Object<T>>])
+}
+
+impl<'a, T: Id> Entry<'a, T> {
+	/// Expanded key of the entry.
+	fn key(&self) -> &str {
+		match self {
+			Entry::Id(_) => "@id",
+			Entry::Type => "@type",
+			Entry::Graph(_) => "@graph",
+			Entry::Included(_) => "@included",
+			Entry::Index(_) => "@index",
+			Entry::Reverse => "@reverse",
+			Entry::Property(prop, _) => prop.as_str()
+		}
+	}
+}
+
+/// Compact a node identifier or type.
+fn compact_reference<T: Id, C: ContextMut<T>>(active_context: &C, reference: &Lenient<Reference<T>>, vocab: bool, options: Options) -> Result<JsonValue, Error> {
+	match reference {
+		Lenient::Ok(reference) => Ok(compact_iri(active_context, &reference.clone().into(), vocab, false, options)?.unwrap().as_json()),
+		Lenient::Unknown(reference) => Ok(reference.as_json())
+	}
+}
+
+/// Compact a node object.
+///
+/// This is the part of the compaction algorithm dedicated to node objects that cannot be
+/// compacted into a simple node reference.
+/// See <https://www.w3.org/TR/json-ld11-api/#compaction-algorithm>.
+pub async fn compact_indexed_node<T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(node: &Node<T>, index: Option<&str>, active_context: &C, type_scoped_context: &C, active_property: Option<&str>, loader: &mut L, options: Options) -> Result<JsonValue, Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	// If the term definition for active property in active context has a local context, it
+	// has already been applied at this point.
+
+	// Initialize inside reverse to true if active property equals @reverse, otherwise to
+	// false.
+	let inside_reverse = active_property == Some("@reverse");
+
+	// Initialize result to a new empty map.
+	let mut result = json::object::Object::new();
+
+	// If element has an @type entry, create a new array compacted types initialized by
+	// transforming each expanded type of that entry into its compacted form by IRI compacting
+	// expanded type. Then, for each term in compacted types ordered lexicographically, apply
+	// its type-scoped context.
+	let mut compacted_types = Vec::new();
+	for ty in &node.types {
+		if let Some(ty) = compact_reference(active_context, ty, true, options)?.as_str() {
+			compacted_types.push(ty.to_string())
+		}
+	}
+
+	let active_context = apply_type_scoped_contexts(Mown::Borrowed(active_context), type_scoped_context, &compacted_types, loader, options).await?;
+	let active_context = active_context.as_ref();
+
+	// For each key expanded property and value expanded value in element, ordered
+	// lexicographically by expanded property if ordered is true:
+	let mut entries = Vec::new();
+
+	if let Some(id) = &node.id {
+		entries.push(Entry::Id(id))
+	}
+
+	if !node.types.is_empty() {
+		entries.push(Entry::Type)
+	}
+
+	if let Some(graph) = &node.graph {
+		entries.push(Entry::Graph(graph))
+	}
+
+	if let Some(included) = &node.included {
+		entries.push(Entry::Included(included))
+	}
+
+	if let Some(index) = index {
+		entries.push(Entry::Index(index))
+	}
+
+	if !node.reverse_properties.is_empty() {
+		entries.push(Entry::Reverse)
+	}
+
+	for (prop, values) in &node.properties {
+		entries.push(Entry::Property(prop, values))
+	}
+
+	if options.ordered {
+		entries.sort_by(|a, b| a.key().cmp(b.key()))
+	}
+
+	for entry in entries {
+		match entry {
+			Entry::Id(id) => {
+				// If expanded property is @id:
+				// If expanded value is a string, then initialize compacted value by IRI
+				// compacting expanded value with vocab set to false.
+				let compacted_value = compact_reference(active_context, id, false, options)?;
+
+				// Initialize alias by IRI compacting expanded property.
+				let alias = compact_key(active_context, Keyword::Id, options)?;
+
+				// Add an entry alias to result whose value is set to compacted value and
+				// continue to the next expanded property.
+				result.insert(&alias, compacted_value)
+			},
+			Entry::Type => {
+				// If expanded property is @type:
+				// Initialize compacted value to an empty array.
+				// For each item expanded type in expanded value, set term by IRI compacting
+				// expanded type using type-scoped context for active context and append term
+				// to compacted value.
+				let mut compacted_value = Vec::new();
+				for ty in &node.types {
+					compacted_value.push(compact_reference(type_scoped_context, ty, true, options)?)
+				}
+
+				// Initialize alias by IRI compacting expanded property.
+				let alias = compact_key(active_context, Keyword::Type, options)?;
+
+				// Initialize as array to true if processing mode is json-ld-1.1 and the
+				// container mapping for alias in the active context includes @set, otherwise
+				// to the negation of compactArrays.
+				let container_mapping = active_context.get(alias.as_str()).map(|def| &def.container);
+				let as_array = if options.processing_mode == ProcessingMode::JsonLd1_1 && container_mapping.map(|c| c.contains(ContainerType::Set)).unwrap_or(false) {
+					true
+				} else {
+					!options.compact_arrays
+				};
+
+				// Use add value to add compacted value to the alias entry in result using as
+				// array.
+				add_value(&mut result, &alias, JsonValue::Array(compacted_value), as_array)
+			},
+			Entry::Reverse => {
+				// If expanded property is @reverse:
+				// Initialize compacted value to the result of using this algorithm
+				// recursively, passing active context, @reverse for active property, and
+				// expanded value for element.
+				// Since the reverse properties map is not a value object nor a node
+				// reference, the previous context applies, if any.
+				let reverse_context = match active_context.previous_context() {
+					Some(previous_context) => previous_context,
+					None => active_context
+				};
+
+				let mut compacted_value = json::object::Object::new();
+
+				let mut reverse_properties: Vec<_> = node.reverse_properties.iter().collect();
+				if options.ordered {
+					reverse_properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()))
+				}
+
+				for (expanded_property, expanded_value) in reverse_properties {
+					let expanded_value: Vec<_> = expanded_value.iter().collect();
+					compact_property(&mut compacted_value, Term::Ref(expanded_property.clone()), &expanded_value, reverse_context, loader, true, options).await?;
+				}
+
+				// For each property and value in compacted value:
+				let properties: Vec<String> = compacted_value.iter().map(|(property, _)| property.to_string()).collect();
+				for property in properties {
+					// If the term definition for property in the active context indicates that
+					// property is a reverse property
+					if let Some(term_definition) = active_context.get(property.as_str()) {
+						if term_definition.reverse_property {
+							// Initialize as array to true if the container mapping for property
+							// in the active context includes @set, otherwise the negation of
+							// compactArrays.
+							let as_array = term_definition.container.contains(ContainerType::Set) || !options.compact_arrays;
+
+							// Use add value to add value to the property entry in result using
+							// as array.
+							// Remove the property entry from compacted value.
+							let value = compacted_value.remove(property.as_str()).unwrap();
+							add_value(&mut result, &property, value, as_array)
+						}
+					}
+				}
+
+				// If compacted value has some remaining map entries, i.e., it is not an empty
+				// map:
+				if !compacted_value.is_empty() {
+					// Initialize alias by IRI compacting @reverse.
+					// Set the value of the alias entry of result to compacted value.
+					let alias = compact_key(active_context, Keyword::Reverse, options)?;
+					result.insert(&alias, JsonValue::Object(compacted_value))
+				}
+			},
+			Entry::Index(index) => {
+				// If expanded property is @index and active property has a container mapping
+				// in active context that includes @index, then the compacted result will be
+				// inside of an @index container, drop the @index entry by continuing to the
+				// next expanded property.
+				let is_index_container = match active_context.get_opt(active_property) {
+					Some(def) => def.container.contains(ContainerType::Index),
+					None => false
+				};
+
+				// Otherwise, if expanded property is @index:
+				// Initialize alias by IRI compacting expanded property.
+				// Add an entry alias to result whose value is set to expanded value and
+				// continue with the next expanded property.
+				if !is_index_container {
+					let alias = compact_key(active_context, Keyword::Index, options)?;
+					result.insert(&alias, index.as_json())
+				}
+			},
+			Entry::Graph(graph) => {
+				let expanded_value: Vec<_> = graph.iter().collect();
+				compact_property(&mut result, Term::Keyword(Keyword::Graph), &expanded_value, active_context, loader, inside_reverse, options).await?
+			},
+			Entry::Included(included) => {
+				let expanded_value: Vec<_> = included.iter().collect();
+				compact_property(&mut result, Term::Keyword(Keyword::Included), &expanded_value, active_context, loader, inside_reverse, options).await?
+			},
+			Entry::Property(prop, values) => {
+				let expanded_value: Vec<_> = values.iter().collect();
+				compact_property(&mut result, Term::Ref(prop.clone()), &expanded_value, active_context, loader, inside_reverse, options).await?
+			}
+		}
+	}
+
+	Ok(JsonValue::Object(result))
+}
+
+/// Compact the given property and values into `result`.
+///
+/// This covers the processing of any entry of a node object that is not `@id`, `@type`,
+/// `@reverse` or `@index`.
+async fn compact_property<T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader, O: Send + Sync + Any<T>>(result: &mut json::object::Object, expanded_property: Term<T>, expanded_value: &[&Indexed<O>], active_context: &C, loader: &mut L, inside_reverse: bool, options: Options) -> Result<(), Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	// If expanded value is an empty array:
+	if expanded_value.is_empty() {
+		// Initialize item active property by IRI compacting expanded property, using
+		// expanded value for value and inside reverse for reverse.
+		let item_active_property = compact_iri(active_context, &expanded_property, true, inside_reverse, options)?.unwrap();
+
+		// If the term definition for item active property in the active context has a
+		// nest value entry (nest term), set nest result to the nested map.
+		// Otherwise, set nest result to result.
+		let nest_result = select_nest_result(result, &item_active_property, active_context)?;
+
+		// Use add value to add an empty array to the item active property entry in nest
+		// result using true for as array.
+		add_value(nest_result, &item_active_property, JsonValue::new_array(), true)
+	}
+
+	// At this point, expanded value must be an array due to the Expansion algorithm.
+	// For each item expanded item in expanded value:
+	for expanded_item in expanded_value {
+		// Initialize item active property by IRI compacting expanded property, using
+		// expanded item for value and inside reverse for reverse.
+		let item_active_property = compact_iri_with(active_context, &expanded_property, *expanded_item, true, inside_reverse, options)?.unwrap();
+
+		// If the term definition for item active property in the active context has a nest
+		// value entry (nest term), set nest result to the nested map.
+		// Otherwise, set nest result to result.
+		let nest_result = select_nest_result(result, &item_active_property, active_context)?;
+
+		// Initialize container to container mapping for item active property in active
+		// context, or to a new empty array, if there is no such container mapping.
+		let container = match active_context.get(item_active_property.as_str()) {
+			Some(def) => def.container.clone(),
+			None => crate::syntax::Container::new()
+		};
+
+		// Initialize as array to true if container includes @set, or if item active
+		// property is @graph or @list, otherwise the negation of compactArrays.
+		let as_array = container.contains(ContainerType::Set)
+			|| item_active_property == "@graph"
+			|| item_active_property == "@list"
+			|| !options.compact_arrays;
+
+		// Initialize compacted item to the result of using this algorithm recursively,
+		// passing active context, item active property for active property, expanded item
+		// for element, along with the compactArrays and ordered flags.
+		// If expanded item is a list object or a graph object, use the value of the @list or
+		// @graph entries, respectively, for element instead of expanded item.
+		let compacted_item = match Any::as_ref(expanded_item.inner()) {
+			Ref::List(list) => compact_collection(list.iter(), active_context, Some(item_active_property.as_str()), loader, options).await?,
+			Ref::Node(node) if is_graph_object(node) => compact_collection(node.graph.as_ref().unwrap().iter(), active_context, Some(item_active_property.as_str()), loader, options).await?,
+			_ => compact_indexed(*expanded_item, active_context, Some(item_active_property.as_str()), loader, options).await?
+		};
+
+		match Any::as_ref(expanded_item.inner()) {
+			Ref::List(_) => {
+				// If expanded item is a list object:
+				// If compacted item is not an array, then set it to an array containing
+				// only compacted item.
+				let compacted_item = match compacted_item {
+					JsonValue::Array(items) => JsonValue::Array(items),
+					item => JsonValue::Array(vec![item])
+				};
+
+				if !container.contains(ContainerType::List) {
+					// If container does not include @list:
+					// Convert compacted item to a list object by setting it to a map
+					// containing an entry where the key is the result of IRI compacting @list
+					// and the value is the original compacted item.
+					let mut map = json::object::Object::new();
+					map.insert(&compact_key(active_context, Keyword::List, options)?, compacted_item);
+
+					// If expanded item contains the entry @index-value, then add an entry to
+					// compacted item where the key is the result of IRI compacting @index and
+					// value is value.
+					if let Some(index) = expanded_item.index() {
+						map.insert(&compact_key(active_context, Keyword::Index, options)?, index.as_json())
+					}
+
+					// Use add value to add compacted item to the item active property entry
+					// in nest result using as array.
+					add_value(nest_result, &item_active_property, JsonValue::Object(map), as_array)
+				} else {
+					// Otherwise, set the value of the item active property entry in nest
+					// result to compacted item.
+					nest_result.insert(&item_active_property, compacted_item)
+				}
+			},
+			Ref::Node(node) if is_graph_object(node) => {
+				// If expanded item is a graph object:
+				if container.contains(ContainerType::Graph) && container.contains(ContainerType::Id) {
+					// If container includes @graph and @id:
+					// Initialize map object to the value of item active property in nest
+					// result, initializing it to a new empty map, if necessary.
+					let map_object = get_or_insert_map(nest_result, &item_active_property);
+
+					// Initialize map key by IRI compacting the value of @id in expanded item
+					// or @none if no such value exists with vocab set to false if there is an
+					// @id entry in expanded item.
+					let map_key = match &node.id {
+						Some(id) => compact_reference(active_context, id, false, options)?.as_str().unwrap().to_string(),
+						None => compact_key(active_context, Keyword::None, options)?
+					};
+
+					// Use add value to add compacted item to the map key entry in map object
+					// using as array.
+					add_value(map_object, &map_key, compacted_item, as_array)
+				} else if container.contains(ContainerType::Graph) && container.contains(ContainerType::Index) && is_simple_graph_object(node) {
+					// Otherwise, if container includes @graph and @index and expanded item is
+					// a simple graph object:
+					// Initialize map object to the value of item active property in nest
+					// result, initializing it to a new empty map, if necessary.
+					let map_object = get_or_insert_map(nest_result, &item_active_property);
+
+					// Initialize map key the value of @index in expanded item or @none, if no
+					// such value exists.
+					let map_key = match expanded_item.index() {
+						Some(index) => index.to_string(),
+						None => "@none".to_string()
+					};
+
+					// Use add value to add compacted item to the map key entry in map object
+					// using as array.
+					add_value(map_object, &map_key, compacted_item, as_array)
+				} else if container.contains(ContainerType::Graph) && is_simple_graph_object(node) {
+					// Otherwise, if container includes @graph and expanded item is a simple
+					// graph object the value cannot be represented as a map object.
+					// If compacted item is an array with more than one value, it cannot be
+					// directly represented, as multiple objects would be interpreted as
+					// different named graphs. Set compacted item to a new map, containing the
+					// key from IRI compacting @included and the original compacted item as a
+					// value.
+					let compacted_item = match compacted_item {
+						JsonValue::Array(items) if items.len() > 1 => {
+							let mut map = json::object::Object::new();
+							map.insert(&compact_key(active_context, Keyword::Included, options)?, JsonValue::Array(items));
+							JsonValue::Object(map)
+						},
+						item => item
+					};
+
+					// Use add value to add compacted item to the item active property entry
+					// in nest result using as array.
+					add_value(nest_result, &item_active_property, compacted_item, as_array)
+				} else {
+					// Otherwise, container does not include @graph or otherwise does not match
+					// one of the previous cases.
+					// Set compacted item to a new map containing the key from IRI compacting
+					// @graph using the original compacted item as a value.
+					let mut map = json::object::Object::new();
+					map.insert(&compact_key(active_context, Keyword::Graph, options)?, compacted_item);
+
+					// If expanded item contains an @id entry, add an entry in compacted item
+					// using the key from IRI compacting @id using the value of IRI compacting
+					// the value of @id in expanded item using false for vocab.
+					if let Some(id) = &node.id {
+						map.insert(&compact_key(active_context, Keyword::Id, options)?, compact_reference(active_context, id, false, options)?)
+					}
+
+					// If expanded item contains an @index entry, add an entry in compacted
+					// item using the key from IRI compacting @index and the value of @index in
+					// expanded item.
+					if let Some(index) = expanded_item.index() {
+						map.insert(&compact_key(active_context, Keyword::Index, options)?, index.as_json())
+					}
+
+					// Use add value to add compacted item to the item active property entry
+					// in nest result using as array.
+					add_value(nest_result, &item_active_property, JsonValue::Object(map), as_array)
+				}
+			},
+			_ => {
+				if !container.contains(ContainerType::Graph) && (container.contains(ContainerType::Language) || container.contains(ContainerType::Index) || container.contains(ContainerType::Id) || container.contains(ContainerType::Type)) {
+					// Otherwise, if container includes @language, @index, @id, or @type and
+					// container does not include @graph:
+					let mut compacted_item = compacted_item;
+
+					// Initialize container key by IRI compacting either @language, @index,
+					// @id, or @type based on the contents of container.
+					let container_keyword = if container.contains(ContainerType::Language) {
+						Keyword::Language
+					} else if container.contains(ContainerType::Index) {
+						Keyword::Index
+					} else if container.contains(ContainerType::Id) {
+						Keyword::Id
+					} else {
+						Keyword::Type
+					};
+
+					let mut container_key = compact_key(active_context, container_keyword, options)?;
+
+					// Initialize index key to the value of index mapping in the term
+					// definition associated with item active property in active context, or
+					// @index, if no such value exists.
+					let index_key = match active_context.get(item_active_property.as_str()) {
+						Some(def) => match &def.index {
+							Some(index) => index.clone(),
+							None => "@index".to_string()
+						},
+						None => "@index".to_string()
+					};
+
+					let mut map_key = None;
+
+					if container.contains(ContainerType::Language) {
+						// If container includes @language and expanded item contains a
+						// @value entry, then set compacted item to the value associated with
+						// its @value entry. Set map key to the value of @language in expanded
+						// item, if any.
+						if let Ref::Value(value) = Any::as_ref(expanded_item.inner()) {
+							match value {
+								Value::Literal(lit, _) => {
+									compacted_item = literal_as_json(lit)
+								},
+								Value::LangString(lang_str) => {
+									compacted_item = lang_str.as_str().as_json();
+									map_key = lang_str.language().map(|lang| lang.to_string())
+								}
+							}
+						}
+					} else if container.contains(ContainerType::Index) && index_key == "@index" {
+						// Otherwise, if container includes @index and index key is @index, set
+						// map key to the value of @index in expanded item, if any.
+						map_key = expanded_item.index().map(|index| index.to_string())
+					} else if container.contains(ContainerType::Index) {
+						// Otherwise, if container includes @index and index key is not @index:
+						// Reinitialize container key by IRI compacting index key after first
+						// IRI expanding it.
+						let expanded_index_key: Term<T> = match expansion::expand_iri(active_context, &index_key, false, true) {
+							Lenient::Ok(term) => term,
+							Lenient::Unknown(_) => Term::Null
+						};
+
+						if let Some(key) = compact_iri(active_context, &expanded_index_key, true, false, options)? {
+							container_key = key
+						}
+
+						// Set map key to the first value of container key in compacted item,
+						// if any.
+						// If there are remaining values in compacted item for container key,
+						// use add value to add those remaining values to the container key in
+						// compacted item. Otherwise, remove that entry from compacted item.
+						map_key = take_first_string(&mut compacted_item, &container_key)
+					} else if container.contains(ContainerType::Id) {
+						// Otherwise, if container includes @id, set map key to the value of
+						// container key in compacted item and remove container key from
+						// compacted item.
+						if let JsonValue::Object(map) = &mut compacted_item {
+							map_key = map.remove(container_key.as_str()).and_then(|key| key.as_str().map(|key| key.to_string()))
+						}
+					} else {
+						// Otherwise, if container includes @type:
+						// Set map key to the first value of container key in compacted item,
+						// if any.
+						// If there are remaining values in compacted item for container key,
+						// use add value to add those remaining values to the container key in
+						// compacted item. Otherwise, remove that entry from compacted item.
+						map_key = take_first_string(&mut compacted_item, &container_key);
+
+						// If compacted item contains a single entry with a key expanding to
+						// @id, set compacted item to the result of using this algorithm
+						// recursively, passing active context, item active property for
+						// active property, and a map composed of the single entry for @id
+						// from expanded item for element.
+						let is_node_ref = match &compacted_item {
+							JsonValue::Object(map) if map.len() == 1 => {
+								let (key, _) = map.iter().next().unwrap();
+								expansion::expand_iri(active_context, key, false, true) == Term::Keyword(Keyword::Id)
+							},
+							_ => false
+						};
+
+						if is_node_ref {
+							if let Some(id) = expanded_item.id() {
+								let mut node_ref: Node<T> = Node::new();
+								node_ref.id = Some(id.clone());
+								let node_ref = Indexed::new(node_ref, None);
+								compacted_item = compact_indexed(&node_ref, active_context, Some(item_active_property.as_str()), loader, options).await?
+							}
+						}
+					}
+
+					// Initialize map object to the value of item active property in nest
+					// result, initializing it to a new empty map, if necessary.
+					// If map key is null, set it to the result of IRI compacting @none.
+					let map_object = get_or_insert_map(nest_result, &item_active_property);
+					let map_key = match map_key {
+						Some(key) => key,
+						None => compact_key(active_context, Keyword::None, options)?
+					};
+
+					// Use add value to add compacted item to the map key entry in map object
+					// using as array.
+					add_value(map_object, &map_key, compacted_item, as_array)
+				} else {
+					// Otherwise, use add value to add compacted item to the item active
+					// property entry in nest result using as array.
+					add_value(nest_result, &item_active_property, compacted_item, as_array)
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Find the map in which the values of the given compacted property must be added.
+///
+/// This is the map designated by the nest value of the property term definition, if any,
+/// or `result` itself.
+fn select_nest_result<'r, T: Id, C: ContextMut<T>>(result: &'r mut json::object::Object, item_active_property: &str, active_context: &C) -> Result<&'r mut json::object::Object, Error> {
+	match active_context.get(item_active_property).and_then(|def| def.nest.as_ref()) {
+		Some(nest_term) => {
+			// If nest term is not @nest, or a term in the active context that expands to
+			// @nest, an invalid @nest value error has been detected, and processing is
+			// aborted.
+			if nest_term != "@nest" {
+				match expansion::expand_iri(active_context, nest_term, false, true) {
+					Lenient::Ok(Term::Keyword(Keyword::Nest)) => (),
+					_ => return Err(ErrorCode::InvalidNestValue.into())
+				}
+			}
+
+			// If result does not have a nest term entry, initialize it to an empty map.
+			// Initialize nest result to the value of nest term in result.
+			Ok(get_or_insert_map(result, nest_term))
+		},
+		None => Ok(result)
+	}
+}
+
+/// Remove the first value of the given entry in a compacted node object, if it is a string.
+///
+/// If there are remaining values, they are kept in the entry.
+/// Otherwise the entry is removed.
+fn take_first_string(compacted_item: &mut JsonValue, key: &str) -> Option<String> {
+	if let JsonValue::Object(map) = compacted_item {
+		let mut values = match map.remove(key) {
+			Some(JsonValue::Array(values)) => values,
+			Some(value) => vec![value],
+			None => Vec::new()
+		};
+
+		let first = if values.first().map(|value| value.is_string()).unwrap_or(false) {
+			values.remove(0).as_str().map(|value| value.to_string())
+		} else {
+			None
+		};
+
+		match values.len() {
+			0 => (),
+			1 => map.insert(key, values.pop().unwrap()),
+			_ => map.insert(key, JsonValue::Array(values))
+		}
+
+		first
+	} else {
+		None
+	}
+}
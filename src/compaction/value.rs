@@ -0,0 +1,225 @@
+use json::JsonValue;
+use crate::{
+	Error,
+	Id,
+	Lenient,
+	Node,
+	Context,
+	object::{
+		Value,
+		Literal
+	},
+	syntax::{
+		ContainerType,
+		Keyword,
+		Type
+	},
+	util::AsJson
+};
+use super::{
+	Options,
+	compact_iri,
+	compact_key,
+	compact_type
+};
+
+/// Convert a literal to its JSON representation (the value of its `@value` entry).
+pub(crate) fn literal_as_json(lit: &Literal) -> JsonValue {
+	match lit {
+		Literal::Null => JsonValue::Null,
+		Literal::Boolean(b) => JsonValue::Boolean(*b),
+		Literal::Number(n) => JsonValue::Number(*n),
+		Literal::String(s) => s.as_json(),
+		Literal::Json(json) => json.clone()
+	}
+}
+
+/// Value compaction algorithm for value objects.
+///
+/// Compact the given (indexed) value object into a scalar if possible.
+/// Otherwise the value object is returned as a map with compacted keys.
+/// See <https://www.w3.org/TR/json-ld11-api/#value-compaction>.
+pub fn compact_indexed_value<T: Id, C: Context<T>>(value: &Value<T>, index: Option<&str>, active_context: &C, active_property: Option<&str>, options: Options) -> Result<JsonValue, Error> {
+	// Initialize language to the language mapping for active property in active context,
+	// if any, otherwise to the default language of active context.
+	// Initialize direction to the direction mapping for active property in active context,
+	// if any, otherwise to the default base direction of active context.
+	let active_property_definition = active_context.get_opt(active_property);
+
+	let language = match active_property_definition {
+		Some(def) => match &def.language {
+			Some(language) => language.as_ref().map(String::as_str),
+			None => active_context.default_language()
+		},
+		None => active_context.default_language()
+	};
+
+	let direction = match active_property_definition {
+		Some(def) => match def.direction {
+			Some(direction) => direction,
+			None => active_context.default_base_direction()
+		},
+		None => active_context.default_base_direction()
+	};
+
+	let type_mapping = active_property_definition.and_then(|def| def.typ.as_ref());
+	let is_index_container = active_property_definition.map(|def| def.container.contains(ContainerType::Index)).unwrap_or(false);
+
+	// If value has an @index entry which is not also an @index container of active property,
+	// the value object cannot be compacted to a scalar.
+	let remove_index = index.is_none() || is_index_container;
+
+	match value {
+		Value::Literal(Literal::Json(json), _) => {
+			// If value has an @type entry whose value matches the type mapping of active
+			// property, set result to the value associated with the @value entry of value.
+			if type_mapping == Some(&Type::Json) {
+				return Ok(json.clone())
+			}
+		},
+		Value::Literal(lit, types) if !types.is_empty() => {
+			if types.len() == 1 && remove_index {
+				if let Some(Type::Ref(ty)) = type_mapping {
+					if types.contains(ty) {
+						return Ok(literal_as_json(lit))
+					}
+				}
+			}
+		},
+		Value::Literal(lit, _) => {
+			// Otherwise, if the type mapping of active property is set to @none, leave value
+			// as is.
+			if type_mapping != Some(&Type::None) && remove_index {
+				match lit {
+					Literal::String(_) => {
+						// Otherwise, if value has no @language and no @direction entries, and
+						// language and direction are both null, set result to the value
+						// associated with the @value entry.
+						if language.is_none() && direction.is_none() {
+							return Ok(literal_as_json(lit))
+						}
+					},
+					// Otherwise, if value has an @value entry whose value is not a string,
+					// set result to the value associated with the @value entry.
+					_ => return Ok(literal_as_json(lit))
+				}
+			}
+		},
+		Value::LangString(lang_str) => {
+			if type_mapping != Some(&Type::None) && remove_index {
+				// Otherwise, if value has an @language entry whose value exactly matches
+				// language, using a case-insensitive comparison if it is not null, or is not
+				// present, if language is null, and the value has a @direction entry whose
+				// value exactly matches direction, if it is not null, or is not present, if
+				// direction is null, set result to the value associated with the @value
+				// entry.
+				let language_matches = match (lang_str.language_tag(), language) {
+					(Some(a), Some(b)) => a.normalized() == b.to_lowercase(),
+					(None, None) => true,
+					_ => false
+				};
+
+				if language_matches && lang_str.direction() == direction {
+					return Ok(lang_str.as_str().as_json())
+				}
+			}
+		}
+	}
+
+	// Otherwise, the value cannot be compacted: it is kept as a map, with each key compacted
+	// using the IRI compaction algorithm.
+	// The value of @type, if any, is also compacted.
+	let mut result = json::object::Object::new();
+
+	match value {
+		Value::Literal(lit, types) => {
+			result.insert(&compact_key(active_context, Keyword::Value, options)?, literal_as_json(lit));
+
+			let mut compacted_types = Vec::new();
+			for ty in types {
+				compacted_types.push(compact_type(active_context, &Type::Ref(ty.clone()), options)?.as_json());
+			}
+
+			if let Literal::Json(_) = lit {
+				compacted_types.push(compact_type(active_context, &Type::Json, options)?.as_json());
+			}
+
+			if !compacted_types.is_empty() {
+				let compacted_types = if compacted_types.len() == 1 {
+					compacted_types.pop().unwrap()
+				} else {
+					JsonValue::Array(compacted_types)
+				};
+
+				result.insert(&compact_key(active_context, Keyword::Type, options)?, compacted_types);
+			}
+		},
+		Value::LangString(lang_str) => {
+			result.insert(&compact_key(active_context, Keyword::Value, options)?, lang_str.as_str().as_json());
+
+			if let Some(language) = lang_str.language() {
+				result.insert(&compact_key(active_context, Keyword::Language, options)?, language.as_json());
+			}
+
+			if let Some(direction) = lang_str.direction() {
+				result.insert(&compact_key(active_context, Keyword::Direction, options)?, direction.as_json());
+			}
+		}
+	}
+
+	if let Some(index) = index {
+		result.insert(&compact_key(active_context, Keyword::Index, options)?, index.as_json());
+	}
+
+	Ok(JsonValue::Object(result))
+}
+
+/// Value compaction algorithm for node references.
+///
+/// Compact the given (indexed) node reference, a node object with no other entry than
+/// `@id` (and possibly `@index`), into a string if the type mapping of the active property
+/// allows it.
+/// Otherwise the node reference is returned as a map with compacted keys.
+/// See <https://www.w3.org/TR/json-ld11-api/#value-compaction>.
+pub fn compact_indexed_node_ref<T: Id, C: Context<T>>(node: &Node<T>, index: Option<&str>, active_context: &C, active_property: Option<&str>, options: Options) -> Result<JsonValue, Error> {
+	let active_property_definition = active_context.get_opt(active_property);
+	let type_mapping = active_property_definition.and_then(|def| def.typ.as_ref());
+	let is_index_container = active_property_definition.map(|def| def.container.contains(ContainerType::Index)).unwrap_or(false);
+	let remove_index = index.is_none() || is_index_container;
+
+	if let Some(id) = node.id() {
+		if remove_index {
+			// If value has an @id entry and has no other entries other than @index:
+			let vocab = match type_mapping {
+				// If the type mapping of active property is set to @id, set result to the
+				// result of IRI compacting the value associated with the @id entry using
+				// false for vocab.
+				Some(Type::Id) => Some(false),
+				// Otherwise, if the type mapping of active property is set to @vocab, set
+				// result to the result of IRI compacting the value associated with the @id
+				// entry.
+				Some(Type::Vocab) => Some(true),
+				_ => None
+			};
+
+			if let Some(vocab) = vocab {
+				return match id {
+					Lenient::Ok(id) => Ok(compact_iri(active_context, &id.clone().into(), vocab, false, options)?.unwrap().as_json()),
+					Lenient::Unknown(id) => Ok(id.as_json())
+				}
+			}
+		}
+	}
+
+	let mut result = json::object::Object::new();
+
+	if let Some(id) = node.id() {
+		result.insert(&compact_key(active_context, Keyword::Id, options)?, id.as_str().as_json());
+	}
+
+	if let Some(index) = index {
+		result.insert(&compact_key(active_context, Keyword::Index, options)?, index.as_json());
+	}
+
+	Ok(JsonValue::Object(result))
+}
@@ -1,7 +1,9 @@
 use iref::IriBuf;
+use json::JsonValue;
 use crate::{
 	Id,
 	Direction,
+	util,
 	syntax::{
 		Term,
 		Type,
@@ -87,3 +89,62 @@ impl<T: Id, C: Context<T>> PartialEq for TermDefinition<T, C> {
 }
 
 impl<T: Id, C: Context<T>> Eq for TermDefinition<T, C> {}
+
+/// Expanded term definition, as it would appear in a context.
+///
+/// The IRI mapping is given by the `@reverse` entry for reverse properties, `@id` otherwise.
+/// The base URL of the definition is not part of the output.
+impl<T: Id, C: Context<T>> util::AsJson for TermDefinition<T, C> {
+	fn as_json(&self) -> JsonValue {
+		let mut obj = json::object::Object::new();
+
+		let value = match &self.value {
+			Some(value) => value.as_json(),
+			None => JsonValue::Null
+		};
+
+		if self.reverse_property {
+			obj.insert("@reverse", value)
+		} else {
+			obj.insert("@id", value)
+		}
+
+		if let Some(typ) = &self.typ {
+			obj.insert("@type", typ.as_json())
+		}
+
+		if !self.container.is_empty() {
+			obj.insert("@container", self.container.as_json())
+		}
+
+		if let Some(language) = &self.language {
+			obj.insert("@language", language.as_ref().map(|l| l.as_json()).unwrap_or(JsonValue::Null))
+		}
+
+		if let Some(direction) = &self.direction {
+			obj.insert("@direction", direction.as_ref().map(|d| d.as_json()).unwrap_or(JsonValue::Null))
+		}
+
+		if let Some(index) = &self.index {
+			obj.insert("@index", index.as_json())
+		}
+
+		if let Some(nest) = &self.nest {
+			obj.insert("@nest", nest.as_json())
+		}
+
+		if let Some(context) = &self.context {
+			obj.insert("@context", context.as_json())
+		}
+
+		if self.prefix {
+			obj.insert("@prefix", true.into())
+		}
+
+		if self.protected {
+			obj.insert("@protected", true.into())
+		}
+
+		JsonValue::Object(obj)
+	}
+}
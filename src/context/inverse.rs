@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use crate::{
+	Id,
+	syntax::{
+		Term,
+		ContainerType
+	}
+};
+use super::Context;
+
+/// Selection map.
+///
+/// Maps each type or language to the term selected for it.
+type Selection = HashMap<String, String>;
+
+/// Type/language map of an inverse context entry.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TypeLang {
+	/// `@type` map.
+	Type,
+
+	/// `@language` map.
+	Language,
+
+	/// `@any` map.
+	Any
+}
+
+#[derive(Default)]
+struct TypeLangSelection {
+	language: Selection,
+	typ: Selection,
+	any: Selection
+}
+
+impl TypeLangSelection {
+	fn get(&self, type_lang: TypeLang) -> &Selection {
+		match type_lang {
+			TypeLang::Type => &self.typ,
+			TypeLang::Language => &self.language,
+			TypeLang::Any => &self.any
+		}
+	}
+}
+
+/// Inverse context.
+///
+/// Maps each IRI (or keyword) to the terms of an active context that can be used to compact it,
+/// indexed by container and type/language mapping.
+/// See <https://www.w3.org/TR/json-ld11-api/#inverse-context-creation>.
+pub struct InverseContext<T: Id> {
+	map: HashMap<Term<T>, HashMap<String, TypeLangSelection>>
+}
+
+/// Insert the given term in the selection map if no other term is already selected.
+fn select(selection: &mut Selection, key: &str, term: &str) {
+	if !selection.contains_key(key) {
+		selection.insert(key.to_string(), term.to_string());
+	}
+}
+
+impl<T: Id> InverseContext<T> {
+	/// Create the inverse context of the given active context.
+	pub fn new<C: Context<T>>(active_context: &C) -> InverseContext<T> {
+		let mut map: HashMap<Term<T>, HashMap<String, TypeLangSelection>> = HashMap::new();
+
+		// Initialize `default_language` to @none. If the active context has a default language,
+		// set default language to the default language from the active context normalized to
+		// lower case.
+		let default_language = match active_context.default_language() {
+			Some(lang) => lang.to_lowercase(),
+			None => "@none".to_string()
+		};
+
+		// For each key `term` and value `term_definition` in the active context, ordered by
+		// shortest term first (breaking ties by choosing the lexicographically least term):
+		let mut definitions: Vec<_> = active_context.definitions().collect();
+		definitions.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+		for (term, term_definition) in definitions {
+			// If the term definition is null, term cannot be selected during compaction, so
+			// continue to the next term.
+			let var = match &term_definition.value {
+				Some(var) => var,
+				None => continue
+			};
+
+			// Initialize `container` to @none. If the container mapping is not empty, set
+			// container to the concatenation of all values of the container mapping in
+			// lexicographical order.
+			let container = container_key(term_definition.container.as_slice());
+
+			// If `var` is not an entry of result, add an entry where the key is `var` and the
+			// value is an empty map to result.
+			let container_map = map.entry(var.clone()).or_default();
+
+			// If container map has no container entry, create one and set its value to a new
+			// map with three entries. The first entry is @language and its value is a new empty
+			// map, the second entry is @type and its value is a new empty map, and the third
+			// entry is @any and its value is a new map with the entry @none set to the term
+			// being processed.
+			let type_lang_map = container_map.entry(container).or_default();
+			select(&mut type_lang_map.any, "@none", term);
+
+			if term_definition.reverse_property {
+				// If the term definition indicates that the term represents a reverse property:
+				// If the type map does not have an @reverse entry, create one and set its
+				// value to the term being processed.
+				select(&mut type_lang_map.typ, "@reverse", term);
+			} else {
+				match &term_definition.typ {
+					Some(crate::syntax::Type::None) => {
+						// Otherwise, if term definition has a type mapping which is @none:
+						// If the language map does not have an @any entry, create one and set
+						// its value to the term being processed.
+						select(&mut type_lang_map.language, "@any", term);
+
+						// If the type map does not have an @any entry, create one and set its
+						// value to the term being processed.
+						select(&mut type_lang_map.typ, "@any", term);
+					},
+					Some(typ) => {
+						// Otherwise, if term definition has a type mapping:
+						// If the type map does not have an entry corresponding to the type
+						// mapping in term definition, create one and set its value to the term
+						// being processed.
+						select(&mut type_lang_map.typ, typ.as_str(), term);
+					},
+					None => {
+						match (&term_definition.language, &term_definition.direction) {
+							(Some(language), Some(direction)) => {
+								// Otherwise, if term definition has both a language mapping and
+								// a direction mapping:
+								let lang_dir = match (language, direction) {
+									(Some(language), Some(direction)) => format!("{}_{}", language, direction).to_lowercase(),
+									(Some(language), None) => language.to_lowercase(),
+									(None, Some(direction)) => format!("_{}", direction),
+									(None, None) => "@null".to_string()
+								};
+
+								select(&mut type_lang_map.language, &lang_dir, term);
+							},
+							(Some(language), None) => {
+								// Otherwise, if term definition has a language mapping (might be
+								// null):
+								let language = match language {
+									Some(language) => language.to_lowercase(),
+									None => "@null".to_string()
+								};
+
+								select(&mut type_lang_map.language, &language, term);
+							},
+							(None, Some(direction)) => {
+								// Otherwise, if term definition has a direction mapping (might be
+								// null):
+								let direction = match direction {
+									Some(direction) => format!("_{}", direction),
+									None => "@none".to_string()
+								};
+
+								select(&mut type_lang_map.language, &direction, term);
+							},
+							(None, None) => {
+								if let Some(default_base_direction) = active_context.default_base_direction() {
+									// Otherwise, if active context has a default base direction:
+									let lang_dir = match active_context.default_language() {
+										Some(language) => format!("{}_{}", language, default_base_direction).to_lowercase(),
+										None => format!("_{}", default_base_direction)
+									};
+
+									select(&mut type_lang_map.language, &lang_dir, term);
+								} else {
+									// Otherwise, if language map does not have a default
+									// language entry, create one and set its value to the term
+									// being processed.
+									select(&mut type_lang_map.language, &default_language, term);
+								}
+
+								// If language map does not have an @none entry, create one and
+								// set its value to the term being processed.
+								select(&mut type_lang_map.language, "@none", term);
+
+								// If type map does not have an @none entry, create one and set
+								// its value to the term being processed.
+								select(&mut type_lang_map.typ, "@none", term);
+							}
+						}
+					}
+				}
+			}
+		}
+
+		InverseContext {
+			map
+		}
+	}
+
+	/// Checks if the given IRI or keyword has an entry in the inverse context.
+	pub fn contains(&self, var: &Term<T>) -> bool {
+		self.map.contains_key(var)
+	}
+
+	/// Term selection.
+	///
+	/// Select the best term to compact `var`, given a list of acceptable container mappings,
+	/// the kind of type/language mapping to look for and the preferred type/language values,
+	/// both in order of preference.
+	/// See <https://www.w3.org/TR/json-ld11-api/#term-selection>.
+	pub fn select<S: AsRef<str>>(&self, var: &Term<T>, containers: &[S], type_lang: TypeLang, preferred_values: &[S]) -> Option<&str> {
+		// Initialize `container_map` to the value associated with `var` in the inverse
+		// context.
+		let container_map = self.map.get(var)?;
+
+		// For each item `container` in `containers`:
+		for container in containers {
+			// If container is not an entry of container map, then there is no term with a
+			// matching container mapping for it, so continue to the next container.
+			if let Some(type_lang_map) = container_map.get(container.as_ref()) {
+				// Initialize value map to the value associated with type/language in
+				// type/language map.
+				let value_map = type_lang_map.get(type_lang);
+
+				// For each item in preferred values:
+				for item in preferred_values {
+					// If item is not an entry of value map, then there is no term with a
+					// matching type mapping or language mapping, so continue to the next item.
+					// Otherwise, a matching term has been found, return the value associated
+					// with item in value map.
+					if let Some(term) = value_map.get(item.as_ref()) {
+						return Some(term.as_str())
+					}
+				}
+			}
+		}
+
+		// No matching term has been found. Return null.
+		None
+	}
+}
+
+/// Container mapping key of the given container types, as used in inverse contexts.
+pub fn container_key(containers: &[ContainerType]) -> String {
+	if containers.is_empty() {
+		"@none".to_string()
+	} else {
+		let mut container: Vec<_> = containers.iter().map(|c| c.into_str()).collect();
+		container.sort();
+		container.concat()
+	}
+}
@@ -47,3 +47,13 @@ pub trait Loader {
 
 	fn load_context<'a>(&'a mut self, url: Iri) -> BoxFuture<'a, Result<RemoteContext<Self::Output>, Error>>;
 }
+
+/// Synchronous context loader.
+///
+/// Counterpart of [`Loader`] used by the synchronous context processing and expansion
+/// algorithms.
+pub trait SyncLoader {
+	type Output;
+
+	fn load_context(&mut self, url: Iri) -> Result<RemoteContext<Self::Output>, Error>;
+}
@@ -3,8 +3,13 @@
 mod definition;
 mod loader;
 mod processing;
+mod inverse;
 
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+use mown::Mown;
 use futures::future::BoxFuture;
 use iref::{Iri, IriBuf};
 use json::JsonValue;
@@ -12,17 +17,22 @@ use crate::{
 	ProcessingMode,
 	Error,
 	Direction,
+	Warning,
+	WarningHandler,
+	warning::At,
 	Id,
 	syntax::Term,
+	generic_json::Json,
 	util
 };
 
 pub use definition::*;
 pub use loader::*;
 pub use processing::*;
+pub use inverse::*;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub struct ProcessingOptions {
+#[derive(Clone, Copy)]
+pub struct ProcessingOptions<'a> {
 	/// The processing mode
 	pub processing_mode: ProcessingMode,
 
@@ -30,38 +40,58 @@ pub struct ProcessingOptions {
 	pub override_protected: bool,
 
 	/// Propagate the processed context.
-	pub propagate: bool
+	pub propagate: bool,
+
+	/// Receives the warnings emitted during processing.
+	///
+	/// If `None`, warnings are logged using the `log` crate.
+	pub warnings: Option<&'a dyn WarningHandler>
 }
 
-impl ProcessingOptions {
+impl<'a> ProcessingOptions<'a> {
+	/// Report a warning to the warning handler, or log it if there is none.
+	pub fn warn(&self, warning: Warning) {
+		crate::warning::report(self.warnings, warning)
+	}
+
+	/// Return the same set of options, to process a child of the current value.
+	///
+	/// The warnings are reported to the given handler, locating them in the child.
+	pub(crate) fn at<'b>(&self, warnings: &'b At<'b>) -> ProcessingOptions<'b> where 'a: 'b {
+		let mut options: ProcessingOptions<'b> = *self;
+		options.warnings = Some(warnings);
+		options
+	}
+
 	/// Return the same set of options, but with `override_protected` set to `true`.
-	pub fn with_override(&self) -> ProcessingOptions {
+	pub fn with_override(&self) -> ProcessingOptions<'a> {
 		let mut opt = *self;
 		opt.override_protected = true;
 		opt
 	}
 
 	/// Return the same set of options, but with `override_protected` set to `false`.
-	pub fn with_no_override(&self) -> ProcessingOptions {
+	pub fn with_no_override(&self) -> ProcessingOptions<'a> {
 		let mut opt = *self;
 		opt.override_protected = false;
 		opt
 	}
 
 	/// Return the same set of options, but with `propagate` set to `false`.
-	pub fn without_propagation(&self) -> ProcessingOptions {
+	pub fn without_propagation(&self) -> ProcessingOptions<'a> {
 		let mut opt = *self;
 		opt.propagate = false;
 		opt
 	}
 }
 
-impl Default for ProcessingOptions {
-	fn default() -> ProcessingOptions {
+impl<'a> Default for ProcessingOptions<'a> {
+	fn default() -> ProcessingOptions<'a> {
 		ProcessingOptions {
 			processing_mode: ProcessingMode::default(),
 			override_protected: false,
-			propagate: true
+			propagate: true,
+			warnings: None
 		}
 	}
 }
@@ -109,6 +139,14 @@ pub trait Context<T: Id = IriBuf> : Clone {
 	fn previous_context(&self) -> Option<&Self>;
 
 	fn definitions<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&'a String, &'a TermDefinition<T, Self>)>>;
+
+	/// Inverse of the context, used to select terms during compaction.
+	///
+	/// The default implementation creates a new inverse context each time it is called.
+	/// Implementations are encouraged to cache it.
+	fn inverse(&self) -> Mown<'_, InverseContext<T>> {
+		Mown::Owned(InverseContext::new(self))
+	}
 }
 
 pub trait ContextMut<T: Id = IriBuf>: Context<T> {
@@ -131,28 +169,89 @@ pub trait ContextMut<T: Id = IriBuf>: Context<T> {
 /// existing active context.
 pub trait Local<T: Id = IriBuf>: Sized + PartialEq + util::AsJson {
 	/// Process the local context with specific options.
-	fn process_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, active_context: &'a C, stack: ProcessingStack, loader: &'a mut L, base_url: Option<Iri>, options: ProcessingOptions) -> BoxFuture<'a, Result<C, Error>> where C::LocalContext: Send + Sync + From<L::Output> + From<Self>, L::Output: Into<Self>, T: Send + Sync;
+	fn process_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, active_context: &'a C, stack: ProcessingStack, loader: &'a mut L, base_url: Option<Iri>, options: ProcessingOptions<'a>) -> BoxFuture<'a, Result<C, Error>> where C::LocalContext: Send + Sync + From<Self>, L::Output: Json, T: Send + Sync;
 
 	/// Process the local context with the given active context with the default options:
 	/// `is_remote` is `false`, `override_protected` is `false` and `propagate` is `true`.
-	fn process<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, active_context: &'a C, loader: &'a mut L, base_url: Option<Iri>) -> BoxFuture<'a, Result<C, Error>> where C::LocalContext: Send + Sync + From<L::Output> + From<Self>, L::Output: Into<Self>, T: Send + Sync {
+	fn process<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, active_context: &'a C, loader: &'a mut L, base_url: Option<Iri>) -> BoxFuture<'a, Result<C, Error>> where C::LocalContext: Send + Sync + From<Self>, L::Output: Json, T: Send + Sync {
 		self.process_with(active_context, ProcessingStack::new(), loader, base_url, ProcessingOptions::default())
 	}
+
+	/// Process the local context with specific options, using a synchronous loader.
+	///
+	/// This is the synchronous counterpart of [`process_with`](Local::process_with).
+	fn process_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, active_context: &C, stack: ProcessingStack, loader: &mut L, base_url: Option<Iri>, options: ProcessingOptions) -> Result<C, Error> where C::LocalContext: Send + Sync + From<Self>, L::Output: Json, T: Send + Sync;
+
+	/// Process the local context with the given active context with the default options,
+	/// using a synchronous loader.
+	///
+	/// This is the synchronous counterpart of [`process`](Local::process).
+	fn process_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, active_context: &C, loader: &mut L, base_url: Option<Iri>) -> Result<C, Error> where C::LocalContext: Send + Sync + From<Self>, L::Output: Json, T: Send + Sync {
+		self.process_with_sync(active_context, ProcessingStack::new(), loader, base_url, ProcessingOptions::default())
+	}
 }
 
-#[derive(Clone, PartialEq, Eq)]
-pub struct JsonContext<T: Id = IriBuf> {
+/// Processed context.
+///
+/// Holds a local context along with the active context resulting from its processing.
+/// The local context is kept so it can be included in the output of algorithms such as
+/// compaction, while the active context is used to perform them.
+pub struct Processed<L, C> {
+	local: L,
+	processed: C
+}
+
+impl<L, C> Processed<L, C> {
+	/// Wrap a local context and the result of its processing.
+	pub fn new(local: L, processed: C) -> Processed<L, C> {
+		Processed {
+			local,
+			processed
+		}
+	}
+
+	/// Original local context.
+	pub fn local(&self) -> &L {
+		&self.local
+	}
+
+	/// Active context resulting from the processing of the local context.
+	pub fn processed(&self) -> &C {
+		&self.processed
+	}
+
+	/// Consume the wrapper and return the local context and its processed active context.
+	pub fn into_parts(self) -> (L, C) {
+		(self.local, self.processed)
+	}
+}
+
+impl<L, C> Deref for Processed<L, C> {
+	type Target = C;
+
+	fn deref(&self) -> &C {
+		&self.processed
+	}
+}
+
+/// Active context whose local contexts are JSON values of type `J`.
+pub struct JsonContext<T: Id = IriBuf, J: Json = JsonValue> {
 	original_base_url: Option<IriBuf>,
 	base_iri: Option<IriBuf>,
 	vocabulary: Option<Term<T>>,
 	default_language: Option<String>,
 	default_base_direction: Option<Direction>,
 	previous_context: Option<Box<Self>>,
-	definitions: HashMap<String, TermDefinition<T, Self>>
+	definitions: HashMap<String, TermDefinition<T, Self>>,
+
+	/// Cached inverse context, reset each time the context is modified.
+	inverse: OnceLock<InverseContext<T>>,
+
+	json: PhantomData<J>
 }
 
-impl<T: Id> JsonContext<T> {
-	pub fn new(base_iri: Option<Iri>) -> JsonContext<T> {
+impl<T: Id, J: Json> JsonContext<T, J> {
+	pub fn new(base_iri: Option<Iri>) -> JsonContext<T, J> {
 		JsonContext {
 			original_base_url: base_iri.map(|iri| iri.into()),
 			base_iri: base_iri.map(|iri| iri.into()),
@@ -160,15 +259,47 @@ impl<T: Id> JsonContext<T> {
 			default_language: None,
 			default_base_direction: None,
 			previous_context: None,
-			definitions: HashMap::new()
+			definitions: HashMap::new(),
+			inverse: OnceLock::new(),
+			json: PhantomData
 		}
 	}
 }
 
-impl<T: Id> Context<T> for JsonContext<T> {
-	type LocalContext = JsonValue;
+impl<T: Id, J: Json> Clone for JsonContext<T, J> {
+	fn clone(&self) -> JsonContext<T, J> {
+		JsonContext {
+			original_base_url: self.original_base_url.clone(),
+			base_iri: self.base_iri.clone(),
+			vocabulary: self.vocabulary.clone(),
+			default_language: self.default_language.clone(),
+			default_base_direction: self.default_base_direction,
+			previous_context: self.previous_context.clone(),
+			definitions: self.definitions.clone(),
+			inverse: OnceLock::new(),
+			json: PhantomData
+		}
+	}
+}
 
-	fn new(base_iri: Option<Iri>) -> JsonContext<T> {
+impl<T: Id, J: Json> PartialEq for JsonContext<T, J> {
+	fn eq(&self, other: &JsonContext<T, J>) -> bool {
+		self.original_base_url == other.original_base_url &&
+		self.base_iri == other.base_iri &&
+		self.vocabulary == other.vocabulary &&
+		self.default_language == other.default_language &&
+		self.default_base_direction == other.default_base_direction &&
+		self.previous_context == other.previous_context &&
+		self.definitions == other.definitions
+	}
+}
+
+impl<T: Id, J: Json> Eq for JsonContext<T, J> {}
+
+impl<T: Id, J: Json> Context<T> for JsonContext<T, J> {
+	type LocalContext = J;
+
+	fn new(base_iri: Option<Iri>) -> JsonContext<T, J> {
 		Self::new(base_iri)
 	}
 
@@ -218,10 +349,15 @@ impl<T: Id> Context<T> for JsonContext<T> {
 	fn definitions<'a>(&'a self) -> Box<dyn 'a + Iterator<Item = (&'a String, &'a TermDefinition<T, Self>)>> {
 		Box::new(self.definitions.iter())
 	}
+
+	fn inverse(&self) -> Mown<'_, InverseContext<T>> {
+		Mown::Borrowed(self.inverse.get_or_init(|| InverseContext::new(self)))
+	}
 }
 
-impl<T: Id> ContextMut<T> for JsonContext<T> {
+impl<T: Id, J: Json> ContextMut<T> for JsonContext<T, J> {
 	fn set(&mut self, term: &str, definition: Option<TermDefinition<T, Self>>) -> Option<TermDefinition<T, Self>> {
+		self.inverse.take();
 		match definition {
 			Some(def) => {
 				self.definitions.insert(term.to_string(), def)
@@ -248,10 +384,12 @@ impl<T: Id> ContextMut<T> for JsonContext<T> {
 	}
 
 	fn set_default_language(&mut self, lang: Option<String>) {
+		self.inverse.take();
 		self.default_language = lang;
 	}
 
 	fn set_default_base_direction(&mut self, dir: Option<Direction>) {
+		self.inverse.take();
 		self.default_base_direction = dir;
 	}
 
@@ -259,3 +397,39 @@ impl<T: Id> ContextMut<T> for JsonContext<T> {
 		self.previous_context = Some(Box::new(previous))
 	}
 }
+
+/// Active context, as it would appear in a document.
+///
+/// The base IRI, vocabulary mapping, default language and base direction are given by the
+/// `@base`, `@vocab`, `@language` and `@direction` entries, followed by each term definition
+/// in lexicographical order.
+/// The previous context is not part of the output.
+impl<T: Id, J: Json> util::AsJson for JsonContext<T, J> {
+	fn as_json(&self) -> JsonValue {
+		let mut obj = json::object::Object::new();
+
+		if let Some(base_iri) = &self.base_iri {
+			obj.insert("@base", base_iri.as_str().into())
+		}
+
+		if let Some(vocabulary) = &self.vocabulary {
+			obj.insert("@vocab", vocabulary.as_json())
+		}
+
+		if let Some(language) = &self.default_language {
+			obj.insert("@language", language.as_json())
+		}
+
+		if let Some(direction) = &self.default_base_direction {
+			obj.insert("@direction", direction.as_json())
+		}
+
+		let mut definitions: Vec<_> = self.definitions.iter().collect();
+		definitions.sort_by_key(|(term, _)| *term);
+		for (term, definition) in definitions {
+			obj.insert(term, definition.as_json())
+		}
+
+		JsonValue::Object(obj)
+	}
+}
@@ -3,7 +3,11 @@ use std::ops::{
 	Deref,
 	DerefMut
 };
-use futures::future::{BoxFuture, FutureExt};
+use futures::future::{
+	BoxFuture,
+	FutureExt
+};
+use indexmap::IndexSet;
 use iref::{
 	Iri,
 	IriBuf
@@ -15,11 +19,15 @@ use crate::{
 	Indexed,
 	Object,
 	ContextMut,
+	Warning,
+	Warnings,
 	context::{
 		self,
-		Loader
+		Loader,
+		SyncLoader
 	},
-	expansion
+	expansion,
+	generic_json::Json
 };
 
 /// Result of the document expansion algorithm.
@@ -27,10 +35,24 @@ use crate::{
 /// It is just an alias for a set of (indexed) objects.
 pub type ExpandedDocument<T> = HashSet<Indexed<Object<T>>>;
 
+/// Result of the document expansion algorithm, preserving the order of the objects.
+///
+/// Obtained with [`Document::expand_ordered_with`].
+/// Combined with the [`ordered`](`expansion::Options::ordered`) expansion option, expanding
+/// the same document twice gives the exact same result, in the same order.
+pub type OrderedExpandedDocument<T> = IndexSet<Indexed<Object<T>>>;
+
+/// Result of the document expansion algorithm, along with the warnings emitted during the
+/// expansion.
+///
+/// Obtained with [`Document::expand_with_warnings`].
+pub type ExpandedDocumentWithWarnings<T> = (ExpandedDocument<T>, Vec<Warning>);
+
 /// JSON-LD document.
 ///
 /// This trait represent a JSON-LD document that can be expanded into an [`ExpandedDocument`].
-/// It is notabily implemented for the [`JsonValue`] type.
+/// It is notabily implemented for the [`JsonValue`] type, and any other type implementing
+/// the [`Json`] trait.
 pub trait Document<T: Id> {
 	/// The type of local contexts that may appear in the document.
 	///
@@ -48,9 +70,18 @@ pub trait Document<T: Id> {
 	///
 	/// This is an asynchronous method since expanding the context may require loading remote
 	/// ressources. It returns a boxed [`Future`](`std::future::Future`) to the result.
-	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
-		C::LocalContext: Send + Sync + From<L::Output> + From<Self::LocalContext>,
-		L::Output: Into<Self::LocalContext>,
+	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: 'a + Send + Sync;
+
+	/// Expand the document with a custom base URL, initial context, document loader and
+	/// expansion options, preserving the order of the expanded objects.
+	///
+	/// See [`OrderedExpandedDocument`].
+	fn expand_ordered_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<OrderedExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
 		T: 'a + Send + Sync;
 
 	/// Expand the document.
@@ -87,31 +118,164 @@ pub trait Document<T: Id> {
 	/// let expanded_doc = task::block_on(doc.expand(&context, &mut NoLoader))?;
 	/// ```
 	fn expand<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, context: &'a C, loader: &'a mut L) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
-		C::LocalContext: Send + Sync + From<L::Output> + From<Self::LocalContext>,
-		L::Output: Into<Self::LocalContext>,
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
 		T: 'a + Send + Sync
 	{
 		self.expand_with(self.base_url(), context, loader, expansion::Options::default())
 	}
+
+	/// Expand the document with a custom base URL, initial context, document loader and
+	/// expansion options, and collect the warnings emitted by the algorithm.
+	///
+	/// Returns the expanded document along with the warnings, in the order they have been
+	/// emitted.
+	/// The warning handler of `options`, if any, is not called.
+	fn expand_with_warnings<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri<'a>>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<ExpandedDocumentWithWarnings<T>, Error>> where
+		Self: Sync,
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: 'a + Send + Sync
+	{
+		async move {
+			let warnings = Warnings::new();
+			let options = expansion::Options {
+				warnings: Some(&warnings),
+				..options
+			};
+
+			let expanded = self.expand_with(base_url, context, loader, options).await?;
+			Ok((expanded, warnings.into_vec()))
+		}.boxed()
+	}
+
+	/// Expand the document with a custom base URL, initial context, synchronous document
+	/// loader and expansion options.
+	///
+	/// This is the synchronous counterpart of [`expand_with`](`Document::expand_with`).
+	/// It runs the same algorithm, without involving any future.
+	fn expand_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<ExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync;
+
+	/// Expand the document with a custom base URL, initial context, synchronous document
+	/// loader and expansion options, preserving the order of the expanded objects.
+	///
+	/// This is the synchronous counterpart of
+	/// [`expand_ordered_with`](`Document::expand_ordered_with`).
+	fn expand_ordered_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<OrderedExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync;
+
+	/// Expand the document using a synchronous document loader.
+	///
+	/// This is the synchronous counterpart of [`expand`](`Document::expand`).
+	/// It uses the document [`base_url`](`Document::base_url`), with the default options.
+	///
+	/// # Example
+	/// ```
+	/// use json_ld::{Document, NoLoader, context::JsonContext};
+	///
+	/// # fn main() -> Result<(), json_ld::Error> {
+	/// let context: JsonContext = JsonContext::new(None);
+	/// let doc = json::parse("{
+	///     \"@id\": \"http://timothee.haudebourg.net/\",
+	///     \"http://xmlns.com/foaf/0.1/name\": \"Timothée Haudebourg\"
+	/// }").unwrap();
+	/// let expanded_doc = doc.expand_sync(&context, &mut NoLoader)?;
+	/// assert_eq!(expanded_doc.len(), 1);
+	/// # Ok(())
+	/// # }
+	/// ```
+	fn expand_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, context: &C, loader: &mut L) -> Result<ExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		self.expand_with_sync(self.base_url(), context, loader, expansion::Options::default())
+	}
+
+	/// Expand the document with a custom base URL, initial context, synchronous document
+	/// loader and expansion options, and collect the warnings emitted by the algorithm.
+	///
+	/// This is the synchronous counterpart of
+	/// [`expand_with_warnings`](`Document::expand_with_warnings`).
+	///
+	/// # Example
+	/// ```
+	/// use json_ld::{Document, NoLoader, WarningCode, context::JsonContext, expansion};
+	///
+	/// # fn main() -> Result<(), json_ld::Error> {
+	/// let context: JsonContext = JsonContext::new(None);
+	/// let doc = json::parse(r#"{"foo": "bar"}"#).unwrap();
+	/// let (expanded_doc, warnings) = doc.expand_with_warnings_sync(None, &context, &mut NoLoader, expansion::Options::default())?;
+	/// assert!(expanded_doc.is_empty());
+	/// assert_eq!(warnings[0].code(), WarningCode::DroppedKey);
+	/// # Ok(())
+	/// # }
+	/// ```
+	fn expand_with_warnings_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<ExpandedDocumentWithWarnings<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		let warnings = Warnings::new();
+		let options = expansion::Options {
+			warnings: Some(&warnings),
+			..options
+		};
+
+		let expanded = self.expand_with_sync(base_url, context, loader, options)?;
+		Ok((expanded, warnings.into_vec()))
+	}
 }
 
 /// Default JSON document implementation.
-impl<T: Id> Document<T> for JsonValue {
-	type LocalContext = JsonValue;
+///
+/// Any JSON value is a JSON-LD document, whatever its representation
+/// (see [`generic_json`](crate::generic_json)).
+impl<T: Id, J: Json> Document<T> for J {
+	type LocalContext = J;
 
 	/// Returns `None`.
 	///
-	/// Use [`RemoteDocument`] to attach a base URL to a `JsonValue` document.
+	/// Use [`RemoteDocument`] to attach a base URL to a JSON document.
 	fn base_url(&self) -> Option<Iri> {
 		None
 	}
 
-	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
-		C::LocalContext: Send + Sync + From<L::Output> + From<JsonValue>,
-		L::Output: Into<JsonValue>,
+	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<J>,
+		L::Output: Json,
 		T: 'a + Send + Sync
 	{
-		expansion::expand(context, self, base_url, loader, options).boxed()
+		expansion::expand(context, self, base_url, loader, options)
+	}
+
+	fn expand_ordered_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<OrderedExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<J>,
+		L::Output: Json,
+		T: 'a + Send + Sync
+	{
+		expansion::expand_ordered(context, self, base_url, loader, options)
+	}
+
+	fn expand_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<ExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<J>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		expansion::sync::expand(context, self, base_url, loader, options)
+	}
+
+	fn expand_ordered_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<OrderedExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<J>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		expansion::sync::expand_ordered(context, self, base_url, loader, options)
 	}
 }
 
@@ -175,13 +339,37 @@ impl<T: Id, D: Document<T>> Document<T> for RemoteDocument<D> {
 		Some(self.base_url.as_iri())
 	}
 
-	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
-		C::LocalContext: Send + Sync + From<L::Output> + From<Self::LocalContext>,
-		L::Output: Into<Self::LocalContext>,
+	fn expand_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<ExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
 		T: 'a + Send + Sync
 	{
 		self.doc.expand_with(base_url, context, loader, options)
 	}
+
+	fn expand_ordered_with<'a, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(&'a self, base_url: Option<Iri>, context: &'a C, loader: &'a mut L, options: expansion::Options<'a>) -> BoxFuture<'a, Result<OrderedExpandedDocument<T>, Error>> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: 'a + Send + Sync
+	{
+		self.doc.expand_ordered_with(base_url, context, loader, options)
+	}
+
+	fn expand_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<ExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		self.doc.expand_with_sync(base_url, context, loader, options)
+	}
+
+	fn expand_ordered_with_sync<C: Send + Sync + ContextMut<T>, L: Send + Sync + SyncLoader>(&self, base_url: Option<Iri>, context: &C, loader: &mut L, options: expansion::Options) -> Result<OrderedExpandedDocument<T>, Error> where
+		C::LocalContext: Send + Sync + From<Self::LocalContext>,
+		L::Output: Json,
+		T: Send + Sync
+	{
+		self.doc.expand_ordered_with_sync(base_url, context, loader, options)
+	}
 }
 
 impl<D> Deref for RemoteDocument<D> {
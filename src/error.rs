@@ -1,18 +1,35 @@
 use std::convert::TryFrom;
 use std::fmt;
+use iref::IriBuf;
 
 /// Error type.
 ///
 /// This is the type of all the errors that may occur during a JSON-LD document processing.
 /// Each error is described by an error code.
 /// See [`ErrorCode`] for more informations about all the different possible errors.
+///
+/// Errors detected by the expansion and context processing algorithms are also located:
+/// [`Error::pointer`] is the [JSON Pointer](https://tools.ietf.org/html/rfc6901) to the
+/// offending element, and [`Error::remote_contexts`] the chain of remote contexts that were
+/// being processed, if any.
+/// The JSON values given to the algorithms do not keep track of their position in the
+/// source text, but it can be recovered using [`Error::position`].
 #[derive(Debug)]
 pub struct Error {
 	/// Error code.
 	code: ErrorCode,
 
 	/// The lower-level source of this error, if any.
-	source: Option<Box<dyn std::error::Error + 'static>>
+	source: Option<Box<dyn std::error::Error + 'static>>,
+
+	/// Segments of the JSON Pointer to the offending element, in reverse order.
+	///
+	/// Segments are added while the error is propagated up to the root of the document.
+	path: Vec<String>,
+
+	/// Remote contexts being processed when the error occured, from the outermost to the
+	/// innermost.
+	remote_contexts: Vec<IriBuf>
 }
 
 impl Error {
@@ -20,7 +37,9 @@ impl Error {
 	pub fn new<S: std::error::Error + 'static>(code: ErrorCode, source: S) -> Error {
 		Error {
 			code,
-			source: Some(Box::new(source))
+			source: Some(Box::new(source)),
+			path: Vec::new(),
+			remote_contexts: Vec::new()
 		}
 	}
 
@@ -28,6 +47,78 @@ impl Error {
 	pub fn code(&self) -> ErrorCode {
 		self.code
 	}
+
+	/// JSON Pointer to the offending element.
+	///
+	/// If the error occured in a remote context (see [`Error::remote_contexts`]), the pointer
+	/// is relative to the innermost remote context document.
+	/// Otherwise it is relative to the processed document (or local context).
+	/// The empty pointer `""` designates the whole document.
+	pub fn pointer(&self) -> String {
+		crate::util::json_pointer(self.path.iter().rev().map(String::as_str))
+	}
+
+	/// Chain of remote contexts being processed when the error occured, from the outermost to
+	/// the innermost.
+	///
+	/// Empty if the error did not occur in a remote context.
+	pub fn remote_contexts(&self) -> &[IriBuf] {
+		&self.remote_contexts
+	}
+
+	/// Line and column (both starting at 1) of the offending element in the given JSON source
+	/// text.
+	///
+	/// The source must be the text of the document the [`Error::pointer`] is relative to,
+	/// that is the innermost remote context if any, or the processed document otherwise.
+	/// Returns `None` if the source is not valid JSON or does not contain the element.
+	pub fn position(&self, source: &str) -> Option<(usize, usize)> {
+		crate::util::json_pointer_position(source, self.path.iter().rev().map(String::as_str))
+	}
+
+	/// Add a parent segment to the pointer of the error, while propagating it.
+	///
+	/// The pointer is left untouched once the error has been located in a remote context,
+	/// since it is then relative to the remote context document.
+	pub(crate) fn at<S: ToString>(mut self, segment: S) -> Error {
+		if self.remote_contexts.is_empty() {
+			self.path.push(segment.to_string())
+		}
+
+		self
+	}
+
+	/// Add the index of the item in which the error occured to its pointer, if the item is part
+	/// of an array.
+	///
+	/// JSON-LD often allows a single value in place of an array containing only this value,
+	/// in which case no segment is added.
+	pub(crate) fn at_item(self, index: usize, in_array: bool) -> Error {
+		if in_array {
+			self.at(index)
+		} else {
+			self
+		}
+	}
+
+	/// Add a parent segment to the pointer of the error, unless it has already been located.
+	pub(crate) fn at_if_unlocated<S: ToString>(mut self, segment: S) -> Error {
+		if self.path.is_empty() && self.remote_contexts.is_empty() {
+			self.path.push(segment.to_string())
+		}
+
+		self
+	}
+
+	/// Set the chain of remote contexts in which the error occured, unless it is already set
+	/// (by a more nested remote context).
+	pub(crate) fn in_remote_contexts(mut self, remote_contexts: Vec<IriBuf>) -> Error {
+		if self.remote_contexts.is_empty() {
+			self.remote_contexts = remote_contexts
+		}
+
+		self
+	}
 }
 
 impl std::error::Error for Error {
@@ -41,7 +132,21 @@ impl std::error::Error for Error {
 
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}", self.code.as_str())
+		write!(f, "{}", self.code.as_str())?;
+
+		if !self.path.is_empty() {
+			write!(f, " at `{}`", self.pointer())?
+		}
+
+		if let Some(url) = self.remote_contexts.last() {
+			write!(f, " in <{}>", url)?;
+
+			for url in self.remote_contexts.iter().rev().skip(1) {
+				write!(f, ", loaded from <{}>", url)?
+			}
+		}
+
+		Ok(())
 	}
 }
 
@@ -49,7 +154,9 @@ impl From<ErrorCode> for Error {
 	fn from(code: ErrorCode) -> Error {
 		Error {
 			code,
-			source: None
+			source: None,
+			path: Vec::new(),
+			remote_contexts: Vec::new()
 		}
 	}
 }
@@ -70,6 +177,9 @@ pub enum ErrorCode {
 	/// A cycle in IRI mappings has been detected.
 	CyclicIriMapping,
 
+	/// An invalid value for `@embed` has been found in a frame.
+	InvalidEmbedValue,
+
 	/// An `@id` entry was encountered whose value was not a string.
 	InvalidIdValue,
 
@@ -119,6 +229,9 @@ pub enum ErrorCode {
 	/// The value of the default language is not a string or null and thus invalid.
 	InvalidDefaultLanguage,
 
+	/// The frame is invalid.
+	InvalidFrame,
+
 	/// A local context contains a term that has an invalid or missing IRI mapping.
 	InvalidIriMapping,
 
@@ -215,7 +328,20 @@ pub enum ErrorCode {
 	ProcessingModeConflict,
 
 	/// An attempt was made to redefine a protected term.
-	ProtectedTermRedefinition
+	ProtectedTermRedefinition,
+
+	/// A [limit](crate::rdf::canonicalization::Limits) of the RDF dataset canonicalization
+	/// algorithm has been exceeded.
+	///
+	/// This error is not part of the JSON-LD specification.
+	CanonicalizationLimitExceeded,
+
+	/// A key or value could not be resolved while expanding in
+	/// [strict mode](crate::expansion::Policy::Strict).
+	///
+	/// This error is not part of the JSON-LD specification.
+	/// Its source is the [`Warning`](crate::Warning) describing the unresolved key or value.
+	UnresolvedValue
 }
 
 impl ErrorCode {
@@ -228,6 +354,7 @@ impl ErrorCode {
 			ConflictingIndexes => "conflicting indexes",
 			ContextOverflow => "context overflow",
 			CyclicIriMapping => "cyclic IRI mapping",
+			InvalidEmbedValue => "invalid @embed value",
 			InvalidIdValue => "invalid @id value",
 			InvalidImportValue => "invalid @import value",
 			InvalidIncludedValue => "invalid @included value",
@@ -244,6 +371,7 @@ impl ErrorCode {
 			InvalidContextEntry => "invalid context entry",
 			InvalidContextNullification => "invalid context nullification",
 			InvalidDefaultLanguage => "invalid default language",
+			InvalidFrame => "invalid frame",
 			InvalidIriMapping => "invalid IRI mapping",
 			InvalidJsonLiteral => "invalid JSON literal",
 			InvalidKeywordAlias => "invalid keyword alias",
@@ -272,7 +400,9 @@ impl ErrorCode {
 			LoadingRemoteContextFailed => "loading remote context failed",
 			MultipleContextLinkHeaders => "multiple context link headers",
 			ProcessingModeConflict => "processing mode conflict",
-			ProtectedTermRedefinition => "protected term redefinition"
+			ProtectedTermRedefinition => "protected term redefinition",
+			CanonicalizationLimitExceeded => "canonicalization limit exceeded",
+			UnresolvedValue => "unresolved value"
 		}
 	}
 }
@@ -287,6 +417,7 @@ impl<'a> TryFrom<&'a str> for ErrorCode {
 			"conflicting indexes" => Ok(ConflictingIndexes),
 			"context overflow" => Ok(ContextOverflow),
 			"cyclic IRI mapping" => Ok(CyclicIriMapping),
+			"invalid @embed value" => Ok(InvalidEmbedValue),
 			"invalid @id value" => Ok(InvalidIdValue),
 			"invalid @import value" => Ok(InvalidImportValue),
 			"invalid @included value" => Ok(InvalidIncludedValue),
@@ -303,6 +434,7 @@ impl<'a> TryFrom<&'a str> for ErrorCode {
 			"invalid context entry" => Ok(InvalidContextEntry),
 			"invalid context nullification" => Ok(InvalidContextNullification),
 			"invalid default language" => Ok(InvalidDefaultLanguage),
+			"invalid frame" => Ok(InvalidFrame),
 			"invalid IRI mapping" => Ok(InvalidIriMapping),
 			"invalid JSON literal" => Ok(InvalidJsonLiteral),
 			"invalid keyword alias" => Ok(InvalidKeywordAlias),
@@ -332,6 +464,8 @@ impl<'a> TryFrom<&'a str> for ErrorCode {
 			"multiple context link headers" => Ok(MultipleContextLinkHeaders),
 			"processing mode conflict" => Ok(ProcessingModeConflict),
 			"protected term redefinition" => Ok(ProtectedTermRedefinition),
+			"canonicalization limit exceeded" => Ok(CanonicalizationLimitExceeded),
+			"unresolved value" => Ok(UnresolvedValue),
 			_ => Err(())
 		}
 	}
@@ -342,3 +476,68 @@ impl fmt::Display for ErrorCode {
 		write!(f, "{}", self.as_str())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		Document,
+		NoLoader,
+		expansion,
+		context::JsonContext
+	};
+	use super::*;
+
+	/// Expand the given document, expecting an error.
+	fn expand_error(doc: &str) -> Error {
+		let json = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		match json.expand_with_sync(None, &context, &mut NoLoader, expansion::Options::default()) {
+			Ok(_) => panic!("expansion should fail"),
+			Err(e) => e
+		}
+	}
+
+	#[test]
+	fn top_level_error() {
+		let doc = r#"{"@id": 1}"#;
+		let e = expand_error(doc);
+		assert_eq!(e.code(), ErrorCode::InvalidIdValue);
+		assert_eq!(e.pointer(), "/@id");
+		assert_eq!(e.position(doc), Some((1, 9)))
+	}
+
+	#[test]
+	fn nested_error() {
+		let doc = r#"{
+	"@context": {"@vocab": "http://example.org/"},
+	"knows": [
+		{"name": "a"},
+		{"name": "b", "@type": [1]}
+	]
+}"#;
+
+		let e = expand_error(doc);
+		assert_eq!(e.code(), ErrorCode::InvalidTypeValue);
+		assert_eq!(e.pointer(), "/knows/1/@type");
+		assert_eq!(e.position(doc), Some((5, 26)));
+		assert_eq!(e.to_string(), "invalid type value at `/knows/1/@type`")
+	}
+
+	#[test]
+	fn escaped_error() {
+		let doc = r#"{"http://example.org/a~b": {"@value": {}}}"#;
+		let e = expand_error(doc);
+		assert_eq!(e.code(), ErrorCode::InvalidValueObjectValue);
+		assert_eq!(e.pointer(), "/http:~1~1example.org~1a~0b");
+		assert_eq!(e.position(doc), Some((1, 28)))
+	}
+
+	#[test]
+	fn context_error() {
+		let doc = r#"{"@context": [{"@vocab": "http://example.org/"}, {"a/b": 1}], "a/b": "c"}"#;
+		let e = expand_error(doc);
+		assert_eq!(e.code(), ErrorCode::InvalidTermDefinition);
+		assert_eq!(e.pointer(), "/@context/1/a~1b");
+		assert_eq!(e.position(doc), Some((1, 58)))
+	}
+}
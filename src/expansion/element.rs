@@ -1,41 +1,52 @@
 use mown::Mown;
-use futures::future::{BoxFuture, FutureExt};
 use iref::Iri;
-use json::JsonValue;
 use crate::{
 	Error,
 	ErrorCode,
 	Id,
+	Indexed,
 	Lenient,
+	WarningCode,
+	Warning,
+	warning::At,
 	object::*,
 	context::{
 		ContextMut,
 		ProcessingOptions,
 		ProcessingStack,
-		Local,
-		Loader
+		Local
 	},
 	syntax::{
 		Keyword,
-		Term
+		Term,
+		is_keyword_like
+	},
+	generic_json::{
+		Json,
+		JsonObject,
+		JsonRef
 	}
 };
 use crate::util::as_array;
-use super::{
+use crate::expansion::{
 	Expanded,
 	Entry,
 	Options,
 	expand_literal,
-	expand_array,
 	expand_value,
-	expand_node,
-	expand_iri
+	expand_iri,
+	warning_location
+};
+use super::{
+	Loader,
+	expand_array,
+	expand_node
 };
 
 /// https://www.w3.org/TR/json-ld11-api/#expansion-algorithm
 /// The default specified value for `ordered` and `from_map` is `false`.
-pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(active_context: &'a C, active_property: Option<&'a str>, element: &'a JsonValue, base_url: Option<Iri<'a>>, loader: &'a mut L, options: Options) -> BoxFuture<'a, Result<Expanded<T>, Error>> where C::LocalContext: Send + Sync + From<L::Output> + From<JsonValue>, L::Output: Into<JsonValue> {
-	async move {
+pub fn expand_element<'a, T: Send + Sync + Id, J: Json, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(active_context: &'a C, active_property: Option<&'a str>, element: &'a J, base_url: Option<Iri<'a>>, loader: &'a mut L, options: Options<'a>) -> BoxFuture!('a, Result<Expanded<T>, Error>) where C::LocalContext: Send + Sync + From<J>, L::Output: Json {
+	boxed_async! {
 		// If `element` is null, return null.
 		if element.is_null() {
 			return Ok(Expanded::Null)
@@ -43,10 +54,11 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 
 		let active_property_definition = active_context.get_opt(active_property);
 
-		// // If `active_property` is `@default`, initialize the `frame_expansion` flag to `false`.
-		// if active_property == Some("@default") {
-		// 	frame_expansion = false;
-		// }
+		// If `active_property` is `@default`, initialize the `frame_expansion` flag to `false`.
+		let mut options = options;
+		if active_property == Some("@default") {
+			options.frame_expansion = false;
+		}
 
 		// If `active_property` has a term definition in `active_context` with a local context,
 		// initialize property-scoped context to that local context.
@@ -61,15 +73,15 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 			None
 		};
 
-		match element {
-			JsonValue::Null => unreachable!(),
-			JsonValue::Array(element) => {
-				expand_array(active_context, active_property, active_property_definition, element, base_url, loader, options).await
+		match element.as_json_ref() {
+			JsonRef::Null => unreachable!(),
+			JsonRef::Array(element) => {
+				maybe_await!(expand_array(active_context, active_property, active_property_definition, element, base_url, loader, options))
 			},
 
-			JsonValue::Object(element) => {
+			JsonRef::Object(element) => {
 				// We will need to consider expanded keys, and maybe ordered keys.
-				let mut entries: Vec<Entry<&'a str>> = Vec::with_capacity(element.len());
+				let mut entries: Vec<Entry<&'a str, J>> = Vec::with_capacity(element.len());
 				for (key, value) in element.iter() {
 					entries.push(Entry(key, value));
 				}
@@ -78,7 +90,7 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 					entries.sort()
 				}
 
-				let mut value_entry: Option<&JsonValue> = None;
+				let mut value_entry: Option<&J> = None;
 				let mut id_entry = None;
 
 				for Entry(key, value) in entries.iter() {
@@ -115,14 +127,15 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 				// `override_protected`.
 				if let Some(property_scoped_context) = property_scoped_context {
 					let options: ProcessingOptions = options.into();
-					active_context = Mown::Owned(property_scoped_context.process_with(active_context.as_ref(), ProcessingStack::new(), loader, property_scoped_base_url, options.with_override()).await?);
+					active_context = Mown::Owned(process_with!(property_scoped_context, active_context.as_ref(), ProcessingStack::new(), loader, property_scoped_base_url, options.with_override())?);
 				}
 
 				// If `element` contains the entry `@context`, set `active_context` to the result
 				// of the Context Processing algorithm, passing `active_context`, the value of the
 				// `@context` entry as `local_context` and `base_url`.
 				if let Some(local_context) = element.get("@context") {
-					active_context = Mown::Owned(local_context.process_with(active_context.as_ref(), ProcessingStack::new(), loader, base_url, options.into()).await?);
+					let warnings = At::key(options.warnings, "@context");
+					active_context = Mown::Owned(process_with!(local_context, active_context.as_ref(), ProcessingStack::new(), loader, base_url, options.at(&warnings).into()).map_err(|e| e.at("@context"))?);
 				}
 
 				let mut type_entries = Vec::new();
@@ -130,7 +143,7 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 					let expanded_key = expand_iri(active_context.as_ref(), key, false, true);
 					match &expanded_key {
 						Lenient::Ok(Term::Keyword(Keyword::Type)) => {
-							type_entries.push(Entry(key, value));
+							type_entries.push(Entry(*key, *value));
 						},
 						_ => ()
 					}
@@ -148,7 +161,7 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 				// key IRI expands to @type:
 				for Entry(_, value) in &type_entries {
 					// Convert `value` into an array, if necessary.
-					let value = as_array(value);
+					let value = as_array(*value);
 
 					// For each `term` which is a value of `value` ordered lexicographically,
 					let mut sorted_value = Vec::with_capacity(value.len());
@@ -170,7 +183,7 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 								// definition for value in `active_context`, and `false` for `propagate`.
 								let base_url = term_definition.base_url.as_ref().map(|url| url.as_iri());
 								let options: ProcessingOptions = options.into();
-								active_context = Mown::Owned(local_context.process_with(active_context.as_ref(), ProcessingStack::new(), loader, base_url, options.without_propagation()).await?);
+								active_context = Mown::Owned(process_with!(local_context, active_context.as_ref(), ProcessingStack::new(), loader, base_url, options.without_propagation())?);
 							}
 						}
 					}
@@ -181,7 +194,7 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 				// key.
 				// Both the key and value of the matched entry are IRI expanded.
 				let input_type = if let Some(Entry(_, value)) = type_entries.first() {
-					if let Some(input_type) = as_array(value).last() {
+					if let Some(input_type) = as_array(*value).last() {
 						if let Some(input_type) = input_type.as_str() {
 							Some(expand_iri(active_context.as_ref(), input_type, false, true))
 						} else {
@@ -200,34 +213,45 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 				value_entry = None;
 				for Entry(key, value) in entries.iter() {
 					match expand_iri(active_context.as_ref(), key, false, true) {
+						// Keys having the form of a keyword expand to `null` and are dropped.
+						Lenient::Ok(Term::Null) if is_keyword_like(key) => {
+							options.drop_key(Warning::new(WarningCode::KeywordLikeValue, *key, warning_location(base_url, active_property).at(key))).map_err(|e| e.at(key))?
+						},
 						Lenient::Ok(expanded_key) => {
 							match &expanded_key {
 								Term::Keyword(Keyword::Value) => {
 									value_entry = Some(value)
 								},
 								Term::Keyword(Keyword::List) if active_property.is_some() && active_property != Some("@graph") => {
-									list_entry = Some(value)
+									list_entry = Some((*key, *value))
 								},
 								Term::Keyword(Keyword::Set) => {
-									set_entry = Some(value)
+									set_entry = Some((*key, *value))
 								},
 								_ => ()
 							}
 
-							expanded_entries.push(Entry((*key, expanded_key), value))
+							expanded_entries.push(Entry((*key, expanded_key), *value))
 						},
 						Lenient::Unknown(_) => {
-							warn!("failed to expand key `{}`", key)
+							options.drop_key(Warning::new(WarningCode::DroppedKey, *key, warning_location(base_url, active_property).at(key))).map_err(|e| e.at(key))?
 						}
 					}
 				}
 
-				if let Some(list_entry) = list_entry {
+				if let Some((list_key, list_entry)) = list_entry {
 					// List objects.
-					for Entry((_, expanded_key), _) in expanded_entries {
+					let mut index = None;
+					for Entry((key, expanded_key), value) in expanded_entries {
 						match expanded_key {
+							// If expanded property is @index:
 							Term::Keyword(Keyword::Index) => {
-								panic!("TODO list index")
+								// If value is not a string, an invalid @index value error
+								// has been detected and processing is aborted.
+								match value.as_str() {
+									Some(value) => index = Some(value.to_string()),
+									None => return Err(Error::from(ErrorCode::InvalidIndexValue).at(key))
+								}
 							},
 							Term::Keyword(Keyword::List) => (),
 							_ => {
@@ -241,17 +265,26 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 					// base URL, and the frameExpansion and ordered flags, ensuring that the
 					// result is an array..
 					let mut result = Vec::new();
-					for item in as_array(list_entry) {
-						result.extend(expand_element(active_context.as_ref(), active_property, item, base_url, loader, options).await?)
+					let list_warnings = At::key(options.warnings, list_key);
+					let list_options = options.at(&list_warnings);
+					for (i, item) in as_array(list_entry).iter().enumerate() {
+						let warnings = At::item(list_options.warnings, i, list_entry.is_array());
+						result.extend(maybe_await!(expand_element(active_context.as_ref(), active_property, item, base_url, loader, list_options.at(&warnings))).map_err(|e| e.at_item(i, list_entry.is_array()).at(list_key))?)
 					}
 
-					Ok(Expanded::Object(Object::List(result).into()))
-				} else if let Some(set_entry) = set_entry {
+					Ok(Expanded::Object(Indexed::new(Object::List(result), index)))
+				} else if let Some((set_key, set_entry)) = set_entry {
 					// Set objects.
-					for Entry((_, expanded_key), _) in expanded_entries {
+					for Entry((key, expanded_key), value) in expanded_entries {
 						match expanded_key {
+							// If value is not a string, an invalid @index value error has been
+							// detected and processing is aborted.
+							// The index of a set object is otherwise dropped, since the set
+							// object is replaced by its content.
 							Term::Keyword(Keyword::Index) => {
-								panic!("TODO set index")
+								if value.as_str().is_none() {
+									return Err(Error::from(ErrorCode::InvalidIndexValue).at(key))
+								}
 							},
 							Term::Keyword(Keyword::Set) => (),
 							_ => {
@@ -263,17 +296,18 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 					// set expanded value to the result of using this algorithm recursively,
 					// passing active context, active property, value for element, base URL, and
 					// the frameExpansion and ordered flags.
-					expand_element(active_context.as_ref(), active_property, set_entry, base_url, loader, options).await
+					let warnings = At::key(options.warnings, set_key);
+					maybe_await!(expand_element(active_context.as_ref(), active_property, set_entry, base_url, loader, options.at(&warnings))).map_err(|e| e.at(set_key))
 				} else if let Some(value_entry) = value_entry {
 					// Value objects.
-					if let Some(value) = expand_value(input_type, type_scoped_context, expanded_entries, value_entry)? {
+					if let Some(value) = expand_value(input_type, type_scoped_context, expanded_entries, value_entry, base_url, options)? {
 						Ok(Expanded::Object(value.into()))
 					} else {
 						Ok(Expanded::Null)
 					}
 				} else {
 					// Node objects.
-					if let Some(result) = expand_node(active_context.as_ref(), type_scoped_context, active_property, expanded_entries, base_url, loader, options).await? {
+					if let Some(result) = maybe_await!(expand_node(active_context.as_ref(), type_scoped_context, active_property, expanded_entries, base_url, loader, options))? {
 						Ok(result.cast::<Object<T>>().into())
 					} else {
 						Ok(Expanded::Null)
@@ -298,16 +332,12 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 				let active_context = if let Some(property_scoped_context) = property_scoped_context {
 					// FIXME it is unclear what we should use as `base_url` if there is no term definition for `active_context`.
 					let base_url = if let Some(definition) = active_context.get_opt(active_property) {
-						if let Some(base_url) = &definition.base_url {
-							Some(base_url.as_iri())
-						} else {
-							None
-						}
+						definition.base_url.as_ref().map(|base_url| base_url.as_iri())
 					} else {
 						None
 					};
 
-					let result = property_scoped_context.process_with(active_context, ProcessingStack::new(), loader, base_url, options.into()).await?;
+					let result = process_with!(property_scoped_context, active_context, ProcessingStack::new(), loader, base_url, options.into())?;
 					Mown::Owned(result)
 				} else {
 					Mown::Borrowed(active_context)
@@ -315,8 +345,8 @@ pub fn expand_element<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L
 
 				// Return the result of the Value Expansion algorithm, passing the `active_context`,
 				// `active_property`, and `element` as value.
-				return Ok(Expanded::Object(expand_literal(active_context.as_ref(), active_property, element)?))
+				return Ok(Expanded::Object(expand_literal(active_context.as_ref(), active_property, element, base_url, options)?))
 			}
 		}
-	}.boxed()
+	}
 }
@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use indexmap::IndexSet;
+use iref::{Iri, IriBuf};
+use mown::Mown;
+use json::JsonValue;
+use crate::{
+	Error,
+	Id,
+	Indexed,
+	Object,
+	ContextMut,
+	context::{
+		Local,
+		ProcessingStack
+	},
+	generic_json::Json
+};
+use crate::expansion::{
+	ExpandContext,
+	Options,
+	filter_top_level_item
+};
+use super::{
+	Loader,
+	expand_element
+};
+
+/// Expansion algorithm.
+///
+/// The top-level objects of the expanded document are collected in a `HashSet`.
+/// Use [`expand_ordered`] to preserve their order.
+pub fn expand<'a, T: 'a + Send + Sync + Id, J: Json, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(active_context: &'a C, element: &'a J, base_url: Option<Iri>, loader: &'a mut L, options: Options<'a>) -> BoxFuture!('a, Result<HashSet<Indexed<Object<T>>>, Error>) where C::LocalContext: Send + Sync + From<J>, L::Output: Json {
+	let expanded = expand_ordered(active_context, element, base_url, loader, options);
+
+	boxed_async! {
+		Ok(maybe_await!(expanded)?.into_iter().collect())
+	}
+}
+
+/// Expansion algorithm, preserving the order of the top-level objects.
+///
+/// Along with the `ordered` option, this gives a reproducible expanded document:
+/// entries are processed in lexicographical order, and the top-level objects, graphs,
+/// included nodes, properties and values are kept in processing order.
+pub fn expand_ordered<'a, T: Send + Sync + Id, J: Json, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(active_context: &'a C, element: &'a J, base_url: Option<Iri>, loader: &'a mut L, options: Options<'a>) -> BoxFuture!('a, Result<IndexSet<Indexed<Object<T>>>, Error>) where C::LocalContext: Send + Sync + From<J>, L::Output: Json {
+	let base_url = base_url.map(IriBuf::from);
+
+	boxed_async! {
+		let base_url = base_url.as_ref().map(|url| url.as_iri());
+
+		// If the expandContext option is set, update the active context using the Context
+		// Processing algorithm, passing the expandContext as local context and the original
+		// base URL from active context. If expandContext is a map having an @context entry,
+		// pass that entry's value instead.
+		let active_context = match options.expand_context {
+			Some(expand_context) => {
+				let local_context = match expand_context {
+					ExpandContext::Iri(iri) => J::string(iri.as_str()),
+					ExpandContext::Json(context) => match context.as_json() {
+						JsonValue::Object(obj) if obj.get("@context").is_some() => {
+							J::from_json(obj.get("@context").unwrap())
+						},
+						json => J::from_json(&json)
+					}
+				};
+
+				let original_base_url = active_context.original_base_url();
+				Mown::Owned(process_with!(local_context, active_context, ProcessingStack::new(), loader, original_base_url, options.into())?)
+			},
+			None => Mown::Borrowed(active_context)
+		};
+
+		let expanded = maybe_await!(expand_element(active_context.as_ref(), None, element, base_url, loader, options))?;
+		if expanded.len() == 1 {
+			match expanded.into_iter().next().unwrap().into_unnamed_graph() {
+				Ok(graph) => Ok(graph),
+				Err(obj) => {
+					let mut set = IndexSet::new();
+					if filter_top_level_item(&obj) {
+						set.insert(obj);
+					}
+					Ok(set)
+				}
+			}
+		} else {
+			Ok(expanded.into_iter().filter(filter_top_level_item).collect())
+		}
+	}
+}
@@ -3,6 +3,7 @@ use iref::{Iri, IriRef};
 use crate::{
 	BlankId,
 	Id,
+	Reference,
 	Lenient,
 	Context,
 	syntax::{
@@ -140,3 +141,14 @@ pub fn expand_iri<T: Id, C: Context<T>>(active_context: &C, value: &str, documen
 		Lenient::Unknown(value.to_string())
 	}
 }
+
+/// Convert a lenient term to a node id, if possible.
+/// Return `None` if the term is `null`.
+pub fn node_id_of_term<T: Id>(term: Lenient<Term<T>>) -> Option<Lenient<Reference<T>>> {
+	match term {
+		Lenient::Ok(Term::Null) => None,
+		Lenient::Ok(Term::Ref(prop)) => Some(Lenient::Ok(prop)),
+		Lenient::Ok(Term::Keyword(kw)) => Some(Lenient::Unknown(kw.into_str().to_string())),
+		Lenient::Unknown(u) => Some(Lenient::Unknown(u))
+	}
+}
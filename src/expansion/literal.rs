@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use json::JsonValue;
+use iref::Iri;
 use crate::{
 	Error,
 	ErrorCode,
@@ -9,11 +9,17 @@ use crate::{
 	Indexed,
 	object::*,
 	Context,
-	syntax::Type
+	syntax::Type,
+	generic_json::{
+		Json,
+		JsonRef
+	}
 };
 use super::{
+	Options,
 	expand_iri,
-	node_id_of_term
+	node_id_of_term,
+	warning_location
 };
 
 fn clone_default_language<T: Id, C: Context<T>>(active_context: &C) -> Option<String> {
@@ -31,7 +37,7 @@ fn clone_default_base_direction<T: Id, C: Context<T>>(active_context: &C) -> Opt
 }
 
 /// https://www.w3.org/TR/json-ld11-api/#value-expansion
-pub fn expand_literal<T: Id, C: Context<T>>(active_context: &C, active_property: Option<&str>, value: &JsonValue) -> Result<Indexed<Object<T>>, Error> {
+pub fn expand_literal<T: Id, J: Json, C: Context<T>>(active_context: &C, active_property: Option<&str>, value: &J, base_url: Option<Iri>, options: Options) -> Result<Indexed<Object<T>>, Error> {
 	let active_property_definition = active_context.get_opt(active_property);
 
 	let active_property_type = if let Some(active_property_definition) = active_property_definition {
@@ -45,9 +51,9 @@ pub fn expand_literal<T: Id, C: Context<T>>(active_context: &C, active_property:
 		// `value` is a string, return a new map containing a single entry where the key is `@id` and
 		// the value is the result of IRI expanding `value` using `true` for `document_relative` and
 		// `false` for vocab.
-		Some(Type::Id) if value.is_string() => {
+		Some(Type::Id) if value.as_str().is_some() => {
 			let mut node = Node::new();
-			node.id = node_id_of_term(expand_iri(active_context, value.as_str().unwrap(), true, false));
+			node.id = node_id_of_term(expand_iri(active_context, value.as_str().unwrap(), true, false)).map(|id| options.resolved(id, warning_location(base_url, active_property))).transpose()?;
 			Ok(Object::Node(node).into())
 		},
 
@@ -55,21 +61,22 @@ pub fn expand_literal<T: Id, C: Context<T>>(active_context: &C, active_property:
 		// value is a string, return a new map containing a single entry where the key is
 		// `@id` and the value is the result of IRI expanding `value` using `true` for
 		// document relative.
-		Some(Type::Vocab) if value.is_string() => {
+		Some(Type::Vocab) if value.as_str().is_some() => {
 			let mut node = Node::new();
-			node.id = node_id_of_term(expand_iri(active_context, value.as_str().unwrap(), true, true));
+			node.id = node_id_of_term(expand_iri(active_context, value.as_str().unwrap(), true, true)).map(|id| options.resolved(id, warning_location(base_url, active_property))).transpose()?;
 			Ok(Object::Node(node).into())
 		},
 
 		_ => {
 			// Otherwise, initialize `result` to a map with an `@value` entry whose value is set to
 			// `value`.
-			let result = match value {
-				JsonValue::Null => Literal::Null,
-				JsonValue::Boolean(b) => Literal::Boolean(*b),
-				JsonValue::Number(n) => Literal::Number(*n),
-				JsonValue::Short(_) | JsonValue::String(_) => Literal::String(value.as_str().unwrap().to_string()),
-				_ => panic!("expand_literal must be called with a literal JSON value")
+			let result = match value.as_json_ref() {
+				JsonRef::Null => Literal::Null,
+				JsonRef::Boolean(b) => Literal::Boolean(b),
+				JsonRef::Number(n) => Literal::Number(n),
+				JsonRef::String(s) => Literal::String(s.to_string()),
+				// Arrays and maps are not literal values.
+				_ => return Err(ErrorCode::InvalidValueObjectValue.into())
 			};
 
 			// If `active_property` has a type mapping in active context, other than `@id`,
@@ -93,6 +100,13 @@ pub fn expand_literal<T: Id, C: Context<T>>(active_context: &C, active_property:
 							clone_default_language(active_context)
 						};
 
+						// The language tag has already been checked while processing the
+						// context, but malformed tags are rejected in strict mode.
+						let language = match language {
+							Some(language) => Some(options.language_tag(language, warning_location(base_url, active_property), true)?),
+							None => None
+						};
+
 						// Initialize `direction` to the direction mapping for
 						// `active_property` in `active_context`, if any, otherwise to the
 						// default base direction of `active_context`.
@@ -4,69 +4,307 @@ mod expanded;
 mod iri;
 mod literal;
 mod value;
-mod node;
-mod array;
-mod element;
 
 use std::cmp::{Ord, Ordering};
-use std::collections::HashSet;
-use futures::Future;
-use iref::{Iri, IriBuf};
+use std::convert::TryFrom;
+use iref::Iri;
 use json::JsonValue;
 use crate::{
-	ProcessingMode,
 	Error,
+	ErrorCode,
+	ProcessingMode,
+	Lenient,
+	LanguageTag,
+	Warning,
+	WarningCode,
+	WarningHandler,
+	Location,
+	warning::At,
 	Id,
 	Indexed,
 	Object,
-	ContextMut,
-	context::{
-		ProcessingOptions,
-		Loader
-	}
+	context::ProcessingOptions,
+	generic_json::Json,
+	util::AsJson
 };
 
 pub use expanded::*;
 pub use iri::*;
 pub use literal::*;
 pub use value::*;
-pub use node::*;
-pub use array::*;
-pub use element::*;
+
+/// Expansion algorithm, using an asynchronous loader.
+mod asynchronous {
+	asynchronous_mode!($);
+
+	use crate::context::Loader;
+
+	mod expand {
+		include!("expand.rs");
+	}
+
+	mod element {
+		include!("element.rs");
+	}
+
+	mod node {
+		include!("node.rs");
+	}
+
+	mod array {
+		include!("array.rs");
+	}
+
+	pub use expand::*;
+	pub use element::*;
+	pub use node::*;
+	pub use array::*;
+}
+
+/// Expansion algorithm, using a synchronous loader.
+///
+/// This is the same algorithm as the asynchronous one, compiled without futures.
+pub(crate) mod sync {
+	synchronous_mode!($);
+
+	use crate::context::SyncLoader as Loader;
+
+	mod expand {
+		include!("expand.rs");
+	}
+
+	mod element {
+		include!("element.rs");
+	}
+
+	mod node {
+		include!("node.rs");
+	}
+
+	mod array {
+		include!("array.rs");
+	}
+
+	pub use expand::*;
+	pub use element::*;
+	pub use node::*;
+	pub use array::*;
+}
+
+pub use asynchronous::*;
+
+/// Context applied before the document's own `@context`.
+#[derive(Clone, Copy)]
+pub enum ExpandContext<'a> {
+	/// IRI of a remote context, loaded using the document loader.
+	Iri(Iri<'a>),
+
+	/// Inline context, given as any [`Json`] value.
+	///
+	/// If it is a map with an `@context` entry, the value of this entry is used.
+	/// Use [`ExpandContext::json`] to build it.
+	Json(&'a (dyn AsJson + Send + Sync))
+}
+
+impl<'a> ExpandContext<'a> {
+	/// Inline context.
+	pub fn json<K: Json>(context: &'a K) -> Self {
+		ExpandContext::Json(context)
+	}
+}
+
+/// How the expansion algorithm handles keys and values that cannot be resolved.
+///
+/// Keys that do not expand to an IRI, blank node identifier or keyword (such as undefined
+/// terms when the context has no vocabulary mapping) are dropped by the expansion algorithm.
+/// Values that do not expand to an IRI or blank node identifier, such as the value of an
+/// `@id` or `@type` entry, are kept as [`Lenient::Unknown`](crate::Lenient::Unknown).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Policy {
+	/// Drop unresolved keys, keep unresolved values.
+	///
+	/// A [`DroppedKey`](crate::WarningCode::DroppedKey) warning is emitted for each dropped
+	/// key. This is the behavior described by the JSON-LD specification.
+	#[default]
+	Lenient,
+
+	/// Same as `Lenient`, but an [`UnresolvedValue`](crate::WarningCode::UnresolvedValue)
+	/// warning is also emitted for each unresolved value, so that the warning handler
+	/// receives all of them.
+	Report,
+
+	/// Fail with an [`UnresolvedValue`](crate::ErrorCode::UnresolvedValue) error on the
+	/// first unresolved key or value.
+	///
+	/// The source of the error is the warning that would have been emitted otherwise.
+	Strict
+}
+
+impl<'a> TryFrom<&'a str> for Policy {
+	type Error = &'a str;
+
+	/// Convert the strings `"lenient"`, `"report"` and `"strict"` into a `Policy`.
+	fn try_from(name: &'a str) -> Result<Policy, &'a str> {
+		match name {
+			"lenient" => Ok(Policy::Lenient),
+			"report" => Ok(Policy::Report),
+			"strict" => Ok(Policy::Strict),
+			_ => Err(name)
+		}
+	}
+}
 
 #[derive(Clone, Copy, Default)]
-pub struct Options {
+pub struct Options<'a> {
 	/// Sets the processing mode.
 	pub processing_mode: ProcessingMode,
 
 	/// If set to true, input document entries are processed lexicographically.
 	/// If false, order is not considered in processing.
-	pub ordered: bool
+	pub ordered: bool,
+
+	/// If set to true, the input is expanded as a frame.
+	///
+	/// Framing keywords and patterns (empty maps, wildcards `{}`, `@default`, etc.) are kept
+	/// in the [framing entries](crate::framing::FrameEntries) of the expanded nodes instead of
+	/// being dropped or rejected.
+	pub frame_expansion: bool,
+
+	/// Context to apply to the active context before expanding the document.
+	///
+	/// This is the `expandContext` option of the JSON-LD API.
+	pub expand_context: Option<ExpandContext<'a>>,
+
+	/// Receives the warnings emitted during expansion, including the ones emitted while
+	/// processing the contexts of the document.
+	///
+	/// If `None`, warnings are logged using the `log` crate.
+	pub warnings: Option<&'a dyn WarningHandler>,
+
+	/// How keys and values that cannot be resolved are handled.
+	///
+	/// In strict mode, malformed language tags are also rejected.
+	pub policy: Policy,
+
+	/// If set to true, the language tags of the expanded language-tagged strings are
+	/// normalized to lower case.
+	///
+	/// Language tags are case insensitive, and the JSON-LD specification allows
+	/// processors to normalize them.
+	pub normalize_language_tags: bool
+}
+
+impl<'a> Options<'a> {
+	/// Report a warning to the warning handler, or log it if there is none.
+	pub fn warn(&self, warning: Warning) {
+		crate::warning::report(self.warnings, warning)
+	}
+
+	/// Return the same set of options, to process a child of the current value.
+	///
+	/// The warnings are reported to the given handler, locating them in the child.
+	pub(crate) fn at<'b>(&self, warnings: &'b At<'b>) -> Options<'b> where 'a: 'b {
+		let mut options: Options<'b> = *self;
+		options.warnings = Some(warnings);
+		options
+	}
+
+	/// Handle a dropped key according to the expansion policy.
+	///
+	/// The given warning is reported, or turned into an error in strict mode.
+	fn drop_key(&self, warning: Warning) -> Result<(), Error> {
+		match self.policy {
+			Policy::Strict => Err(Error::new(ErrorCode::UnresolvedValue, warning)),
+			_ => {
+				self.warn(warning);
+				Ok(())
+			}
+		}
+	}
+
+	/// Check that the given expanded value has been resolved, according to the expansion
+	/// policy.
+	///
+	/// If it is unknown, an [`UnresolvedValue`](WarningCode::UnresolvedValue) warning is
+	/// reported in lenient-report mode, or an error is returned in strict mode.
+	fn resolved<T>(&self, value: Lenient<T>, location: Location) -> Result<Lenient<T>, Error> {
+		if let Lenient::Unknown(unknown) = &value {
+			let warning = Warning::new(WarningCode::UnresolvedValue, unknown.as_str(), location);
+			match self.policy {
+				Policy::Lenient => (),
+				Policy::Report => self.warn(warning),
+				Policy::Strict => return Err(Error::new(ErrorCode::UnresolvedValue, warning))
+			}
+		}
+
+		Ok(value)
+	}
+
+	/// Check and normalize the language tag of a language-tagged string.
+	///
+	/// If the tag is not well-formed, a [`MalformedLanguageTag`](WarningCode::MalformedLanguageTag)
+	/// warning is reported, or an [`InvalidLanguageTaggedString`](ErrorCode::InvalidLanguageTaggedString)
+	/// error is returned in strict mode.
+	/// If `reported` is true, the warning has already been reported (while processing the
+	/// context defining the tag) and is not reported again.
+	/// The tag is then normalized to lower case if
+	/// [`normalize_language_tags`](Options::normalize_language_tags) is set.
+	fn language_tag(&self, tag: String, location: Location, reported: bool) -> Result<String, Error> {
+		if !LanguageTag::new(&tag).is_well_formed() {
+			let warning = Warning::new(WarningCode::MalformedLanguageTag, tag.as_str(), location);
+			match self.policy {
+				Policy::Strict => return Err(Error::new(ErrorCode::InvalidLanguageTaggedString, warning)),
+				_ if !reported => self.warn(warning),
+				_ => ()
+			}
+		}
+
+		if self.normalize_language_tags {
+			Ok(tag.to_ascii_lowercase())
+		} else {
+			Ok(tag)
+		}
+	}
 }
 
-impl From<Options> for ProcessingOptions {
-	fn from(options: Options) -> ProcessingOptions {
+impl<'a> From<Options<'a>> for ProcessingOptions<'a> {
+	fn from(options: Options<'a>) -> ProcessingOptions<'a> {
 		let mut copt = ProcessingOptions::default();
 		copt.processing_mode = options.processing_mode;
+		copt.warnings = options.warnings;
 		copt
 	}
 }
 
-#[derive(PartialEq, Eq)]
-pub struct Entry<'a, T>(T, &'a JsonValue);
+/// Entry of a JSON object, ordered by key.
+pub struct Entry<'a, T, J = JsonValue>(T, &'a J);
 
-impl<'a, T: PartialOrd> PartialOrd for Entry<'a, T> {
-	fn partial_cmp(&self, other: &Entry<'a, T>) -> Option<Ordering> {
+impl<'a, T: PartialEq, J> PartialEq for Entry<'a, T, J> {
+	fn eq(&self, other: &Entry<'a, T, J>) -> bool {
+		self.0 == other.0
+	}
+}
+
+impl<'a, T: Eq, J> Eq for Entry<'a, T, J> {}
+
+impl<'a, T: PartialOrd, J> PartialOrd for Entry<'a, T, J> {
+	fn partial_cmp(&self, other: &Entry<'a, T, J>) -> Option<Ordering> {
 		self.0.partial_cmp(&other.0)
 	}
 }
 
-impl<'a, T: Ord> Ord for Entry<'a, T> {
-	fn cmp(&self, other: &Entry<'a, T>) -> Ordering {
+impl<'a, T: Ord, J> Ord for Entry<'a, T, J> {
+	fn cmp(&self, other: &Entry<'a, T, J>) -> Ordering {
 		self.0.cmp(&other.0)
 	}
 }
 
+/// Location of a warning emitted while expanding the given entry of the document located at
+/// `base_url`.
+fn warning_location(base_url: Option<Iri>, key: Option<&str>) -> Location {
+	Location::new(base_url.map(|url| url.into()), key.map(|key| key.to_string()))
+}
+
 fn filter_top_level_item<T: Id>(item: &Indexed<Object<T>>) -> bool {
 	// Remove dangling values.
 	match item.inner() {
@@ -75,25 +313,203 @@ fn filter_top_level_item<T: Id>(item: &Indexed<Object<T>>) -> bool {
 	}
 }
 
-pub fn expand<'a, T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(active_context: &'a C, element: &'a JsonValue, base_url: Option<Iri>, loader: &'a mut L, options: Options) -> impl 'a + Send + Future<Output=Result<HashSet<Indexed<Object<T>>>, Error>> where C::LocalContext: Send + Sync + From<L::Output> + From<JsonValue>, L::Output: Into<JsonValue> {
-	let base_url = base_url.map(|url| IriBuf::from(url));
-
-	async move {
-		let base_url = base_url.as_ref().map(|url| url.as_iri());
-		let expanded = expand_element(active_context, None, element, base_url, loader, options).await?;
-		if expanded.len() == 1 {
-			match expanded.into_iter().next().unwrap().into_unnamed_graph() {
-				Ok(graph) => Ok(graph),
-				Err(obj) => {
-					let mut set = HashSet::new();
-					if filter_top_level_item(&obj) {
-						set.insert(obj);
-					}
-					Ok(set)
-				}
+#[cfg(test)]
+mod tests {
+	use iref::IriBuf;
+	use crate::{
+		Document,
+		NoLoader,
+		FsLoader,
+		SyncLoader,
+		Warnings,
+		WarningCode,
+		ErrorCode,
+		context::JsonContext
+	};
+	use super::*;
+
+	/// Expand the given document and return the code and JSON Pointer of each warning.
+	fn warnings(doc: &str) -> Vec<(WarningCode, String)> {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let (_, warnings) = doc.expand_with_warnings_sync(None, &context, &mut NoLoader, Options::default()).unwrap();
+		warnings.into_iter().map(|w| (w.code(), w.location().pointer())).collect()
+	}
+
+	/// Expand the given document, expecting an error, and return its code and JSON Pointer.
+	fn error(doc: &str) -> (ErrorCode, String) {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		match doc.expand_with_sync(None, &context, &mut NoLoader, Options::default()) {
+			Ok(_) => panic!("expansion should fail"),
+			Err(e) => (e.code(), e.pointer())
+		}
+	}
+
+	/// Expand the given document with the given `expandContext` option.
+	fn expand_with<L: Send + Sync + SyncLoader>(doc: &str, expand_context: ExpandContext, loader: &mut L) -> JsonValue where L::Document: Json {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext<IriBuf> = JsonContext::new(None);
+		let options = Options {
+			expand_context: Some(expand_context),
+			..Options::default()
+		};
+		doc.expand_ordered_with_sync(None, &context, loader, options).unwrap().as_json()
+	}
+
+	#[test]
+	fn inline_expand_context() {
+		let expected = json::parse(r#"[{"http://example.org/name": [{"@value": "a"}]}]"#).unwrap();
+
+		let context = json::parse(r#"{"@vocab": "http://example.org/"}"#).unwrap();
+		assert_eq!(expand_with(r#"{"name": "a"}"#, ExpandContext::json(&context), &mut NoLoader), expected);
+
+		// A map with an `@context` entry is unwrapped.
+		let context = json::parse(r#"{"@context": {"@vocab": "http://example.org/"}}"#).unwrap();
+		assert_eq!(expand_with(r#"{"name": "a"}"#, ExpandContext::json(&context), &mut NoLoader), expected);
+
+		// The document's own context is processed after the expand context.
+		let context = json::parse(r#"{"@vocab": "http://example.org/", "name": "http://example.com/name"}"#).unwrap();
+		let doc = r#"{"@context": {"name": null}, "name": "a", "title": "b"}"#;
+		assert_eq!(expand_with(doc, ExpandContext::json(&context), &mut NoLoader), json::parse(r#"[{"http://example.org/title": [{"@value": "b"}]}]"#).unwrap())
+	}
+
+	#[cfg(feature = "serde_json")]
+	#[test]
+	fn serde_json_expand_context() {
+		let context = serde_json::json!({"@context": {"@vocab": "http://example.org/"}});
+		assert_eq!(
+			expand_with(r#"{"name": "a"}"#, ExpandContext::json(&context), &mut NoLoader),
+			json::parse(r#"[{"http://example.org/name": [{"@value": "a"}]}]"#).unwrap()
+		)
+	}
+
+	#[test]
+	fn iri_expand_context() {
+		let dir = std::env::temp_dir().join(format!("json-ld-expand-context-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("context.jsonld"), r#"{"@context": {"@vocab": "http://example.org/"}}"#).unwrap();
+
+		let mut loader = FsLoader::new();
+		loader.mount(Iri::new("http://example.org/contexts/").unwrap(), &dir);
+		let context = Iri::new("http://example.org/contexts/context.jsonld").unwrap();
+		let expanded = expand_with(r#"{"name": "a"}"#, ExpandContext::Iri(context), &mut loader);
+		std::fs::remove_dir_all(&dir).unwrap();
+
+		assert_eq!(expanded, json::parse(r#"[{"http://example.org/name": [{"@value": "a"}]}]"#).unwrap());
+
+		// Loading fails without a mount point.
+		let doc = json::parse(r#"{"name": "a"}"#).unwrap();
+		let context_iri = Iri::new("http://example.org/contexts/context.jsonld").unwrap();
+		let options = Options {
+			expand_context: Some(ExpandContext::Iri(context_iri)),
+			..Options::default()
+		};
+		let context: JsonContext<IriBuf> = JsonContext::new(None);
+		assert!(doc.expand_with_sync(None, &context, &mut FsLoader::new(), options).is_err())
+	}
+
+	#[test]
+	fn list_index() {
+		assert_eq!(error(r#"{"http://example.org/p": {"@list": [], "@index": 1}}"#), (ErrorCode::InvalidIndexValue, "/http:~1~1example.org~1p/@index".to_string()));
+
+		let doc = json::parse(r#"{"http://example.org/p": {"@list": ["a"], "@index": "i"}}"#).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = doc.expand_with_sync(None, &context, &mut NoLoader, Options::default()).unwrap();
+		assert_eq!(expanded.as_json()[0]["http://example.org/p"][0]["@index"], "i")
+	}
+
+	#[test]
+	fn set_index() {
+		assert_eq!(error(r#"{"http://example.org/p": {"@set": [], "@index": true}}"#), (ErrorCode::InvalidIndexValue, "/http:~1~1example.org~1p/@index".to_string()))
+	}
+
+	#[test]
+	fn value_direction() {
+		assert_eq!(error(r#"{"http://example.org/p": {"@value": "a", "@direction": "up"}}"#).0, ErrorCode::InvalidBaseDirection)
+	}
+
+	#[test]
+	fn non_literal_value() {
+		let context: JsonContext<IriBuf> = JsonContext::new(None);
+		for value in &[json::array![1], json::object! {"a": 1}] {
+			match expand_literal(&context, None, value, None, Options::default()) {
+				Err(e) => assert_eq!(e.code(), ErrorCode::InvalidValueObjectValue),
+				Ok(_) => panic!("`{}` is not a literal", value.dump())
 			}
-		} else {
-			Ok(expanded.into_iter().filter(filter_top_level_item).collect())
 		}
 	}
+
+	#[test]
+	fn top_level_dropped_key() {
+		assert_eq!(warnings(r#"{"foo": "bar"}"#), vec![(WarningCode::DroppedKey, "/foo".to_string())])
+	}
+
+	#[test]
+	fn returned_warnings() {
+		let doc = json::parse(r#"{"@context": {"@vocab": "http://example.org/"}, "name": "a", "@foo": 1}"#).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+
+		// The warnings are returned instead of being passed to the handler.
+		let handler = Warnings::new();
+		let options = Options {
+			warnings: Some(&handler),
+			..Options::default()
+		};
+
+		let (expanded, warnings) = futures::executor::block_on(doc.expand_with_warnings(None, &context, &mut NoLoader, options)).unwrap();
+		assert_eq!(expanded.len(), 1);
+		assert_eq!(warnings.iter().map(|w| (w.code(), w.location().pointer())).collect::<Vec<_>>(), vec![(WarningCode::KeywordLikeValue, "/@foo".to_string())]);
+		assert!(handler.is_empty());
+
+		let (sync_expanded, sync_warnings) = doc.expand_with_warnings_sync(None, &context, &mut NoLoader, options).unwrap();
+		assert!(sync_expanded == expanded);
+		assert_eq!(sync_warnings.len(), 1)
+	}
+
+	#[test]
+	fn nested_dropped_key() {
+		let doc = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@graph": [
+				{"name": "a"},
+				{"@context": {"@vocab": null}, "knows": {"@list": [{"foo": 1}]}, "@bar": 2}
+			]
+		}"#;
+
+		assert_eq!(warnings(doc), vec![
+			(WarningCode::DroppedKey, "/@graph/1/knows".to_string()),
+			(WarningCode::KeywordLikeValue, "/@graph/1/@bar".to_string())
+		])
+	}
+
+	#[test]
+	fn dropped_key_in_list() {
+		let doc = r#"{
+			"@context": {"@vocab": "http://example.org/", "foo": null},
+			"knows": {"@list": [{"name": "a"}, {"foo": 1}]}
+		}"#;
+
+		assert_eq!(warnings(doc), vec![(WarningCode::DroppedKey, "/knows/@list/1/foo".to_string())])
+	}
+
+	#[test]
+	fn local_context_warning() {
+		let doc = r#"{
+			"@context": [{"@vocab": "http://example.org/"}, {"@language": "en_US"}],
+			"name": "a"
+		}"#;
+
+		assert_eq!(warnings(doc), vec![(WarningCode::MalformedLanguageTag, "/@context/1/@language".to_string())])
+	}
+
+	#[test]
+	fn value_language_warning() {
+		let doc = r#"{
+			"@context": {"@vocab": "http://example.org/", "lang": "@language"},
+			"name": [{"@value": "a", "@language": "en"}, {"@value": "b", "lang": "en_US"}]
+		}"#;
+
+		assert_eq!(warnings(doc), vec![(WarningCode::MalformedLanguageTag, "/name/1/lang".to_string())])
+	}
 }
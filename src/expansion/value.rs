@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use json::JsonValue;
+use iref::Iri;
 use crate::{
 	Error,
 	ErrorCode,
@@ -15,12 +16,138 @@ use crate::{
 	syntax::{
 		Keyword,
 		Term
+	},
+	generic_json::{
+		Json,
+		JsonObject,
+		JsonRef
 	}
 };
 use crate::util::as_array;
-use super::{Entry, expand_iri};
+use crate::framing::{
+	Pattern,
+	ValuePattern
+};
+use super::{Entry, Options, expand_iri, warning_location};
+
+/// Checks if the given value object entry is a frame pattern (a map or an array).
+fn is_pattern<J: Json>(value: &J) -> bool {
+	matches!(value.as_json_ref(), JsonRef::Object(_) | JsonRef::Array(_))
+}
+
+/// Expand a value pattern of a frame.
+///
+/// When the frame expansion flag is set, the `@value`, `@type` and `@language` entries of a
+/// value object may be an empty map (wildcard) or an array of values.
+fn expand_value_pattern<T: Id, J: Json, C: ContextMut<T>>(type_scoped_context: &C, expanded_entries: Vec<Entry<(&str, Term<T>), J>>, value_entry: &J) -> Result<Option<Indexed<Object<T>>>, Error> {
+	let mut pattern = ValuePattern {
+		value: Pattern::None,
+		types: Pattern::None,
+		language: Pattern::None
+	};
+
+	// Expanded value may be an empty map or an array of scalar values.
+	pattern.value = match value_entry.as_json_ref() {
+		JsonRef::Object(map) if map.is_empty() => Pattern::Wildcard,
+		JsonRef::Object(_) => return Err(ErrorCode::InvalidValueObjectValue.into()),
+		JsonRef::Array([]) => Pattern::None,
+		_ => {
+			let mut values = Vec::new();
+			for item in as_array(value_entry) {
+				values.push(match item.as_json_ref() {
+					JsonRef::Null => Literal::Null,
+					JsonRef::String(s) => Literal::String(s.to_string()),
+					JsonRef::Number(n) => Literal::Number(n),
+					JsonRef::Boolean(b) => Literal::Boolean(b),
+					_ => return Err(ErrorCode::InvalidValueObjectValue.into())
+				})
+			}
+
+			Pattern::Values(values)
+		}
+	};
+
+	let mut index = None;
+	for Entry((_, expanded_key), value) in expanded_entries {
+		match expanded_key {
+			// Expanded value may be an empty map or an array of strings.
+			Term::Keyword(Keyword::Language) => {
+				pattern.language = match value.as_json_ref() {
+					JsonRef::Object(map) if map.is_empty() => Pattern::Wildcard,
+					JsonRef::Object(_) => return Err(ErrorCode::InvalidLanguageTaggedString.into()),
+					_ => {
+						let mut languages = Vec::new();
+						for language in as_array(value) {
+							match language.as_str() {
+								Some(language) => languages.push(language.to_string()),
+								None => return Err(ErrorCode::InvalidLanguageTaggedString.into())
+							}
+						}
+
+						if languages.is_empty() {
+							Pattern::None
+						} else {
+							Pattern::Values(languages)
+						}
+					}
+				}
+			},
+			// Expanded value may be an empty map or an array of IRIs.
+			Term::Keyword(Keyword::Type) => {
+				pattern.types = match value.as_json_ref() {
+					JsonRef::Object(map) if map.is_empty() => Pattern::Wildcard,
+					JsonRef::Object(_) => return Err(ErrorCode::InvalidTypeValue.into()),
+					_ => {
+						let mut types = Vec::new();
+						for ty in as_array(value) {
+							match ty.as_str().map(|ty| expand_iri(type_scoped_context, ty, true, true)) {
+								Some(Lenient::Ok(Term::Ref(Reference::Id(ty)))) => types.push(ty),
+								_ => return Err(ErrorCode::InvalidTypedValue.into())
+							}
+						}
+
+						if types.is_empty() {
+							Pattern::None
+						} else {
+							Pattern::Values(types)
+						}
+					}
+				}
+			},
+			Term::Keyword(Keyword::Index) => {
+				if let Some(value) = value.as_str() {
+					index = Some(value.to_string())
+				} else {
+					return Err(ErrorCode::InvalidIndexValue.into())
+				}
+			},
+			Term::Keyword(Keyword::Value) | Term::Keyword(Keyword::Direction) => (),
+			_ => {
+				return Err(ErrorCode::InvalidValueObject.into());
+			}
+		}
+	}
+
+	let mut node = Node::new();
+	node.frame_entries_mut().value = Some(pattern);
+	Ok(Some(Indexed::new(Object::Node(node), index)))
+}
+
+pub fn expand_value<'a, T: Id, J: Json, C: ContextMut<T>>(input_type: Option<Lenient<Term<T>>>, type_scoped_context: &C, expanded_entries: Vec<Entry<(&str, Term<T>), J>>, value_entry: &J, base_url: Option<Iri>, options: Options<'_>) -> Result<Option<Indexed<Object<T>>>, Error> {
+	// When the frame expansion flag is set, value objects entries may be patterns.
+	if options.frame_expansion {
+		let has_pattern = is_pattern(value_entry) || expanded_entries.iter().any(|Entry((_, expanded_key), value)| {
+			match expanded_key {
+				Term::Keyword(Keyword::Type) | Term::Keyword(Keyword::Language) => is_pattern(*value),
+				_ => false
+			}
+		});
+
+		if has_pattern {
+			return expand_value_pattern(type_scoped_context, expanded_entries, value_entry)
+		}
+	}
 
-pub fn expand_value<'a, T: Id, C: ContextMut<T>>(input_type: Option<Lenient<Term<T>>>, type_scoped_context: &C, expanded_entries: Vec<Entry<(&str, Term<T>)>>, value_entry: &JsonValue) -> Result<Option<Indexed<Object<T>>>, Error> {
 	// If input type is @json, set expanded value to value.
 	// If processing mode is json-ld-1.0, an invalid value object value error has
 	// been detected and processing is aborted.
@@ -28,20 +155,20 @@ pub fn expand_value<'a, T: Id, C: ContextMut<T>>(input_type: Option<Lenient<Term
 	// Otherwise, if value is not a scalar or null, an invalid value object value
 	// error has been detected and processing is aborted.
 	let mut result = if input_type == Some(Lenient::Ok(Term::Keyword(Keyword::Json))) {
-		Literal::Json(value_entry.clone())
+		Literal::Json(JsonValue::from_json(value_entry))
 	} else {
-		match value_entry {
-			JsonValue::Null => {
+		match value_entry.as_json_ref() {
+			JsonRef::Null => {
 				Literal::Null
 			},
-			JsonValue::Short(_) | JsonValue::String(_) => {
-				Literal::String(value_entry.as_str().unwrap().to_string())
+			JsonRef::String(s) => {
+				Literal::String(s.to_string())
 			},
-			JsonValue::Number(n) => {
-				Literal::Number(*n)
+			JsonRef::Number(n) => {
+				Literal::Number(n)
 			},
-			JsonValue::Boolean(b) => {
-				Literal::Boolean(*b)
+			JsonRef::Boolean(b) => {
+				Literal::Boolean(b)
 			},
 			_ => {
 				return Err(ErrorCode::InvalidValueObjectValue.into());
@@ -54,7 +181,7 @@ pub fn expand_value<'a, T: Id, C: ContextMut<T>>(input_type: Option<Lenient<Term
 	let mut language = None;
 	let mut direction = None;
 
-	for Entry((_, expanded_key), value) in expanded_entries {
+	for Entry((key, expanded_key), value) in expanded_entries {
 		match expanded_key {
 			// If expanded property is @language:
 			Term::Keyword(Keyword::Language) => {
@@ -64,10 +191,8 @@ pub fn expand_value<'a, T: Id, C: ContextMut<T>>(input_type: Option<Lenient<Term
 					// Otherwise, set expanded value to value. If value is not
 					// well-formed according to section 2.2.9 of [BCP47],
 					// processors SHOULD issue a warning.
-					// TODO warning.
-
 					if value != "@none" {
-						language = Some(value.to_string());
+						language = Some(options.language_tag(value.to_string(), warning_location(base_url, Some(key)).at(key), false).map_err(|e| e.at(key))?);
 					}
 				} else {
 					return Err(ErrorCode::InvalidLanguageTaggedString.into())
@@ -116,7 +241,7 @@ pub fn expand_value<'a, T: Id, C: ContextMut<T>>(input_type: Option<Lenient<Term
 
 						match expanded_ty {
 							Lenient::Ok(Term::Keyword(Keyword::Json)) => {
-								result = Literal::Json(value_entry.clone())
+								result = Literal::Json(JsonValue::from_json(value_entry))
 							},
 							Lenient::Ok(Term::Ref(Reference::Id(ty))) => {
 								types.insert(ty);
@@ -0,0 +1,231 @@
+//! Flattening algorithm and types.
+
+mod node_map;
+
+use json::JsonValue;
+use crate::{
+	Error,
+	Id,
+	BlankIdGenerator,
+	Indexed,
+	Object,
+	Node,
+	ContextMut,
+	context::{
+		Loader,
+		Processed
+	},
+	compaction,
+	syntax::Keyword,
+	generic_json::Json
+};
+
+pub use node_map::*;
+
+#[derive(Clone, Copy, Default)]
+pub struct Options {
+	/// If set to true, nodes and properties are processed lexicographically, ordered by
+	/// identifier.
+	/// If false, order is not considered in processing.
+	pub ordered: bool
+}
+
+/// Checks if the given node has no other entry than `@id`.
+fn is_only_id<T: Id>(node: &Indexed<Node<T>>) -> bool {
+	node.index().is_none()
+	&& node.types.is_empty()
+	&& node.graph.is_none()
+	&& node.included.is_none()
+	&& node.properties.is_empty()
+	&& node.reverse_properties.is_empty()
+}
+
+/// Collect the nodes of a node map graph, omitting node references.
+fn graph_nodes<T: Id>(graph: NodeMapGraph<T>, options: Options) -> Vec<Indexed<Node<T>>> {
+	let mut nodes: Vec<_> = graph.into_nodes().filter(|node| !is_only_id(node)).collect();
+
+	if options.ordered {
+		nodes.sort_by(|a, b| a.id().map(|id| id.as_str()).cmp(&b.id().map(|id| id.as_str())))
+	}
+
+	nodes
+}
+
+/// Flattening algorithm.
+///
+/// Returns the nodes of the default graph, where every embedded node has been lifted out
+/// and replaced by a node reference.
+/// Named graphs are attached to the node (of the default graph) sharing their name, in its
+/// `@graph` field, themselves flattened, as specified by the flattening algorithm.
+/// To access the nodes of each graph separately, use [`generate_node_map`] and
+/// [`NodeMap::named_graphs`] instead.
+///
+/// Blank node identifiers are generated in the iteration order of `input`: flatten an
+/// [`OrderedExpandedDocument`](crate::OrderedExpandedDocument) to get the same identifiers on
+/// every run.
+/// Blank node identifiers are relabeled, and unidentified nodes labeled, using the given
+/// generator (for instance [`BlankIdCounter`](crate::BlankIdCounter)).
+/// See <https://www.w3.org/TR/json-ld11-api/#flattening-algorithm>.
+pub fn flatten<'a, T: 'a + Id, I: IntoIterator<Item = &'a Indexed<Object<T>>>, G: BlankIdGenerator>(input: I, generator: G, options: Options) -> Result<Vec<Indexed<Node<T>>>, Error> {
+	// Initialize node map to a map consisting of a single entry whose key is @default and
+	// whose value is an empty map.
+	// Perform the Node Map Generation algorithm, passing element and node map.
+	let node_map = generate_node_map(input, generator)?;
+
+	// Initialize default graph to the value of the @default entry of node map, which is a
+	// map representing the default graph.
+	let (mut default_graph, graphs) = node_map.into_parts();
+
+	// For each key-value pair graph name-graph in node map where graph name is not
+	// @default, ordered lexicographically by graph name if ordered is true:
+	for (graph_name, graph) in graphs {
+		// If default graph does not have a graph name entry, create one and initialize its
+		// value to a map consisting of an @id entry whose value is set to graph name.
+		// Reference the value associated with the graph name entry in default graph using
+		// the variable entry.
+		let entry = default_graph.declare_node(graph_name);
+
+		// Add an @graph entry to entry and set it to an empty array.
+		// For each id-node pair in graph ordered by id, add node to the @graph entry of
+		// entry, unless the only entry of node is @id.
+		let nodes = graph_nodes(graph, options);
+		entry.graph = Some(Box::new(nodes.into_iter().map(|node| node.cast::<Object<T>>()).collect()));
+	}
+
+	// Initialize an empty array flattened.
+	// For each id-node pair in default graph ordered by id, add node to flattened, unless
+	// the only entry of node is @id.
+	Ok(graph_nodes(default_graph, options))
+}
+
+/// Compact the result of the flattening algorithm using the given context.
+///
+/// The output always has a top-level `@graph` entry (or its alias), even if there is only
+/// one node, so that it has a deterministic structure.
+/// See <https://www.w3.org/TR/json-ld11-api/#dom-jsonldprocessor-flatten>.
+pub async fn compact_flattened<T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(flattened: &[Indexed<Node<T>>], context: &Processed<C::LocalContext, C>, loader: &mut L, options: compaction::Options) -> Result<JsonValue, Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	let active_context = context.processed();
+
+	let compacted = compaction::compact_collection(flattened.iter(), active_context, None, loader, options).await?;
+	let compacted = match compacted {
+		JsonValue::Array(items) => items,
+		item => vec![item]
+	};
+
+	let mut result = json::object::Object::new();
+	result.insert(&compaction::compact_key(active_context, Keyword::Graph, options)?, JsonValue::Array(compacted));
+
+	Ok(JsonValue::Object(compaction::with_context(context.local(), result)))
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use crate::{
+		Document,
+		NoLoader,
+		BlankIdCounter,
+		expansion,
+		context::{
+			JsonContext,
+			Local
+		},
+		util::AsJson
+	};
+	use super::*;
+
+	fn flatten_json(doc: &str) -> JsonValue {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = block_on(doc.expand_ordered_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+		flatten(&expanded, BlankIdCounter::default(), Options { ordered: true }).unwrap().as_json()
+	}
+
+	#[test]
+	fn embedded_nodes() {
+		let doc = r#"{
+			"@id": "http://example.org/a",
+			"http://example.org/knows": {"http://example.org/name": "b"}
+		}"#;
+
+		let expected = json::parse(r#"[
+			{"@id": "_:b0", "http://example.org/name": [{"@value": "b"}]},
+			{"@id": "http://example.org/a", "http://example.org/knows": [{"@id": "_:b0"}]}
+		]"#).unwrap();
+
+		assert_eq!(flatten_json(doc), expected)
+	}
+
+	#[test]
+	fn merged_nodes() {
+		let doc = r#"[
+			{"@id": "http://example.org/b", "http://example.org/name": "b"},
+			{"@id": "http://example.org/a", "http://example.org/knows": {"@id": "http://example.org/b", "http://example.org/age": 3}}
+		]"#;
+
+		let expected = json::parse(r#"[
+			{"@id": "http://example.org/a", "http://example.org/knows": [{"@id": "http://example.org/b"}]},
+			{"@id": "http://example.org/b", "http://example.org/name": [{"@value": "b"}], "http://example.org/age": [{"@value": 3}]}
+		]"#).unwrap();
+
+		assert_eq!(flatten_json(doc), expected)
+	}
+
+	#[test]
+	fn named_graphs() {
+		let doc = r#"{
+			"@id": "http://example.org/g",
+			"@graph": [
+				{"@id": "http://example.org/a", "http://example.org/knows": {"@id": "http://example.org/b"}}
+			]
+		}"#;
+
+		let expected = json::parse(r#"[
+			{
+				"@id": "http://example.org/g",
+				"@graph": [
+					{"@id": "http://example.org/a", "http://example.org/knows": [{"@id": "http://example.org/b"}]}
+				]
+			}
+		]"#).unwrap();
+
+		assert_eq!(flatten_json(doc), expected)
+	}
+
+	#[test]
+	fn compacted_graph() {
+		let doc = json::parse(r#"{"@id": "http://example.org/a", "http://example.org/name": "a"}"#).unwrap();
+		let local = json::parse(r#"{"@vocab": "http://example.org/"}"#).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = block_on(doc.expand_ordered_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+		let flattened = flatten(&expanded, BlankIdCounter::default(), Options::default()).unwrap();
+		let processed = Processed::new(local.clone(), block_on(local.process(&context, &mut NoLoader, None)).unwrap());
+
+		// A single node is still wrapped in a `@graph` entry.
+		let expected = json::parse(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@graph": [{"@id": "http://example.org/a", "name": "a"}]
+		}"#).unwrap();
+
+		assert_eq!(block_on(compact_flattened(&flattened, &processed, &mut NoLoader, compaction::Options::default())).unwrap(), expected)
+	}
+
+	#[test]
+	fn document_order() {
+		let doc = r#"[
+			{"http://example.org/name": "z", "http://example.org/knows": {"@id": "_:x"}},
+			{"@id": "_:x", "http://example.org/name": "a"},
+			{"@id": "http://example.org/g", "@graph": [{"http://example.org/name": "c"}]}
+		]"#;
+
+		// Blank node identifiers follow the order of the document, not the order of the output.
+		let expected = json::parse(r#"[
+			{"@id": "_:b0", "http://example.org/name": [{"@value": "z"}], "http://example.org/knows": [{"@id": "_:b1"}]},
+			{"@id": "_:b1", "http://example.org/name": [{"@value": "a"}]},
+			{"@id": "http://example.org/g", "@graph": [{"@id": "_:b2", "http://example.org/name": [{"@value": "c"}]}]}
+		]"#).unwrap();
+
+		assert_eq!(flatten_json(doc), expected);
+		assert_eq!(flatten_json(doc), flatten_json(doc))
+	}
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::collections::hash_map;
+use crate::{
+	Error,
+	ErrorCode,
+	Id,
+	BlankIdGenerator,
+	BlankIdRelabeller,
+	Reference,
+	Lenient,
+	Indexed,
+	Object,
+	Node
+};
+
+/// Nodes of a graph, indexed by identifier.
+pub struct NodeMapGraph<T: Id> {
+	nodes: HashMap<Reference<T>, Indexed<Node<T>>>
+}
+
+impl<T: Id> Default for NodeMapGraph<T> {
+	fn default() -> NodeMapGraph<T> {
+		NodeMapGraph::new()
+	}
+}
+
+impl<T: Id> NodeMapGraph<T> {
+	/// Create a new empty graph.
+	pub fn new() -> NodeMapGraph<T> {
+		NodeMapGraph {
+			nodes: HashMap::new()
+		}
+	}
+
+	/// Number of nodes in the graph.
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	/// Checks if the graph is empty.
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// Checks if the graph contains a node with the given identifier.
+	pub fn contains(&self, id: &Reference<T>) -> bool {
+		self.nodes.contains_key(id)
+	}
+
+	/// Get the node with the given identifier.
+	pub fn get(&self, id: &Reference<T>) -> Option<&Indexed<Node<T>>> {
+		self.nodes.get(id)
+	}
+
+	/// Get the node with the given identifier, mutably.
+	pub fn get_mut(&mut self, id: &Reference<T>) -> Option<&mut Indexed<Node<T>>> {
+		self.nodes.get_mut(id)
+	}
+
+	/// Iterate through the nodes of the graph along with their identifier.
+	pub fn iter(&self) -> hash_map::Iter<'_, Reference<T>, Indexed<Node<T>>> {
+		self.nodes.iter()
+	}
+
+	/// Iterate through the nodes of the graph.
+	pub fn nodes(&self) -> hash_map::Values<'_, Reference<T>, Indexed<Node<T>>> {
+		self.nodes.values()
+	}
+
+	/// Consume the graph and return its nodes.
+	pub fn into_nodes(self) -> hash_map::IntoValues<Reference<T>, Indexed<Node<T>>> {
+		self.nodes.into_values()
+	}
+
+	/// Get the node with the given identifier, creating it if necessary.
+	pub(crate) fn declare_node(&mut self, id: Reference<T>) -> &mut Indexed<Node<T>> {
+		self.nodes.entry(id.clone()).or_insert_with(|| Indexed::new(Node::with_id(Lenient::Ok(id)), None))
+	}
+}
+
+impl<T: Id> IntoIterator for NodeMapGraph<T> {
+	type Item = (Reference<T>, Indexed<Node<T>>);
+	type IntoIter = hash_map::IntoIter<Reference<T>, Indexed<Node<T>>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.nodes.into_iter()
+	}
+}
+
+/// Node map.
+///
+/// Result of the node map generation algorithm: every node of a document, grouped by graph
+/// and indexed by identifier.
+/// Each node of the map is flat: its properties only refer to other nodes through
+/// node references (nodes with no other field than `@id`).
+pub struct NodeMap<T: Id> {
+	/// Default graph.
+	default_graph: NodeMapGraph<T>,
+
+	/// Named graphs.
+	graphs: HashMap<Reference<T>, NodeMapGraph<T>>
+}
+
+impl<T: Id> Default for NodeMap<T> {
+	fn default() -> NodeMap<T> {
+		NodeMap::new()
+	}
+}
+
+impl<T: Id> NodeMap<T> {
+	/// Create a new empty node map.
+	pub fn new() -> NodeMap<T> {
+		NodeMap {
+			default_graph: NodeMapGraph::new(),
+			graphs: HashMap::new()
+		}
+	}
+
+	/// Get the default graph.
+	pub fn default_graph(&self) -> &NodeMapGraph<T> {
+		&self.default_graph
+	}
+
+	/// Get the graph with the given name, or the default graph if `id` is `None`.
+	pub fn graph(&self, id: Option<&Reference<T>>) -> Option<&NodeMapGraph<T>> {
+		match id {
+			Some(id) => self.graphs.get(id),
+			None => Some(&self.default_graph)
+		}
+	}
+
+	/// Iterate through the named graphs.
+	pub fn named_graphs(&self) -> hash_map::Iter<'_, Reference<T>, NodeMapGraph<T>> {
+		self.graphs.iter()
+	}
+
+	/// Consume the node map and return the default graph and named graphs.
+	pub fn into_parts(self) -> (NodeMapGraph<T>, HashMap<Reference<T>, NodeMapGraph<T>>) {
+		(self.default_graph, self.graphs)
+	}
+
+	/// Get the graph with the given name, or the default graph if `id` is `None`, creating it
+	/// if necessary.
+	fn graph_mut(&mut self, id: Option<&Reference<T>>) -> &mut NodeMapGraph<T> {
+		match id {
+			Some(id) => self.graphs.entry(id.clone()).or_default(),
+			None => &mut self.default_graph
+		}
+	}
+}
+
+/// Relabel the given reference if it is a blank node identifier.
+fn relabel<T: Id, G: BlankIdGenerator>(generator: &mut BlankIdRelabeller<G>, reference: &Reference<T>) -> Reference<T> {
+	match reference {
+		Reference::Blank(id) => Reference::Blank(generator.relabel(Some(id))),
+		reference => reference.clone()
+	}
+}
+
+/// Node map generation algorithm.
+///
+/// Collect every node of the given expanded document, including the ones embedded in other
+/// nodes, `@graph` and `@included` entries.
+/// Blank node identifiers are relabeled using the given generator and nodes without
+/// identifier are given a fresh blank node identifier.
+/// Nodes identified by something that is neither an IRI nor a blank node identifier are also
+/// given a fresh blank node identifier.
+/// The input is traversed in iteration order, and the `@graph` and `@included` entries of
+/// nodes in document order, so that the generated identifiers only depend on the document
+/// and the generator when the input is ordered (such as an
+/// [`OrderedExpandedDocument`](crate::OrderedExpandedDocument)).
+/// The top-level objects of an unordered expanded document are iterated in an arbitrary order.
+/// See <https://www.w3.org/TR/json-ld11-api/#node-map-generation>.
+pub fn generate_node_map<'a, T: 'a + Id, I: IntoIterator<Item = &'a Indexed<Object<T>>>, G: BlankIdGenerator>(input: I, generator: G) -> Result<NodeMap<T>, Error> {
+	let mut node_map = NodeMap::new();
+	let mut generator = BlankIdRelabeller::new(generator);
+
+	for element in input {
+		extend_node_map(&mut node_map, &mut generator, element, None)?;
+	}
+
+	Ok(node_map)
+}
+
+/// Add the given element to the node map.
+///
+/// Returns the flattened version of the element: value objects are returned as they are,
+/// list objects with their items flattened and nodes objects are replaced by a reference.
+fn extend_node_map<T: Id, G: BlankIdGenerator>(node_map: &mut NodeMap<T>, generator: &mut BlankIdRelabeller<G>, element: &Indexed<Object<T>>, active_graph: Option<&Reference<T>>) -> Result<Indexed<Object<T>>, Error> {
+	match element.inner() {
+		Object::Value(_) => Ok(element.clone()),
+		Object::List(list) => {
+			// If element has an @list entry, initialize a new map result consisting of a
+			// single entry @list whose value is initialized to an empty array, and
+			// recursively call this algorithm passing the value of element's @list entry
+			// for element and result for list.
+			let mut flat_list = Vec::with_capacity(list.len());
+			for item in list {
+				flat_list.push(extend_node_map(node_map, generator, item, active_graph)?);
+			}
+
+			Ok(Indexed::new(Object::List(flat_list), None))
+		},
+		Object::Node(node) => {
+			let id = extend_node_map_with_node(node_map, generator, node, element.index(), active_graph)?;
+			Ok(Indexed::new(Object::Node(Node::with_id(Lenient::Ok(id))), None))
+		}
+	}
+}
+
+/// Add the given node object to the node map.
+///
+/// Returns the identifier of the node in the node map.
+fn extend_node_map_with_node<T: Id, G: BlankIdGenerator>(node_map: &mut NodeMap<T>, generator: &mut BlankIdRelabeller<G>, node: &Node<T>, index: Option<&str>, active_graph: Option<&Reference<T>>) -> Result<Reference<T>, Error> {
+	// If element has an @id entry, set id to its value and remove the entry from element.
+	// If id is a blank node identifier, replace it with a newly generated blank node
+	// identifier passing id for identifier.
+	// Otherwise, set id to the result of the Generate Blank Node Identifier algorithm
+	// passing null for identifier.
+	let id = match &node.id {
+		Some(Lenient::Ok(id)) => relabel(generator, id),
+		_ => Reference::Blank(generator.relabel(None))
+	};
+
+	{
+		// If graph does not contain an entry id, create one and initialize its value to a
+		// map consisting of a single entry @id whose value is id.
+		// Reference the value of the id entry of graph using the variable node.
+		let flat_node = node_map.graph_mut(active_graph).declare_node(id.clone());
+
+		// If element has an @type entry, merge each value into the @type entry of node,
+		// replacing blank node identifiers with newly generated ones.
+		for ty in &node.types {
+			let ty = match ty {
+				Lenient::Ok(ty) => Lenient::Ok(relabel(generator, ty)),
+				ty => ty.clone()
+			};
+
+			if !flat_node.types.contains(&ty) {
+				flat_node.types.push(ty)
+			}
+		}
+
+		// If element has an @index entry, set the @index entry of node to its value.
+		// If node already has an @index entry with a different value, a conflicting
+		// indexes error has been detected, and processing is aborted.
+		if let Some(index) = index {
+			match flat_node.index() {
+				Some(current_index) if current_index != index => {
+					return Err(ErrorCode::ConflictingIndexes.into())
+				},
+				_ => flat_node.set_index(Some(index.to_string()))
+			}
+		}
+	}
+
+	// If element has an @reverse entry:
+	let mut reverse_properties: Vec<_> = node.reverse_properties.iter().collect();
+	reverse_properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+	for (property, values) in reverse_properties {
+		let property = relabel(generator, property);
+
+		// For each value of the reverse property, recursively invoke this algorithm passing
+		// value for element, active graph, referenced node for active subject, and property
+		// for active property.
+		for value in values {
+			let subject_id = extend_node_map_with_node(node_map, generator, value.inner(), value.index(), active_graph)?;
+			let reference = Indexed::new(Object::Node(Node::with_id(Lenient::Ok(id.clone()))), None);
+			let subject = node_map.graph_mut(active_graph).declare_node(subject_id);
+			let subject_values = subject.properties.entry(property.clone()).or_default();
+
+			// If node does not have an active property entry, create one and initialize its
+			// value to an array containing active subject.
+			// Otherwise, compare active subject against every item in the array associated
+			// with the active property entry of node. If there is no match, append active
+			// subject to the array.
+			if !subject_values.contains(&reference) {
+				subject_values.push(reference)
+			}
+		}
+	}
+
+	// If element has an @graph entry, recursively invoke this algorithm passing the value of
+	// the @graph entry for element and id for active graph.
+	if let Some(graph) = node.graph() {
+		node_map.graph_mut(Some(&id));
+		for item in graph {
+			extend_node_map(node_map, generator, item, Some(&id))?;
+		}
+	}
+
+	// If element has an @included entry, recursively invoke this algorithm passing the value
+	// of the @included entry for element and active graph.
+	if let Some(included) = node.included() {
+		for item in included {
+			extend_node_map_with_node(node_map, generator, item.inner(), item.index(), active_graph)?;
+		}
+	}
+
+	// Finally, for each key property and value value in element ordered by property:
+	let mut properties: Vec<_> = node.properties.iter().collect();
+	properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+	for (property, values) in properties {
+		// If property is a blank node identifier, replace it with a newly generated blank
+		// node identifier passing property for identifier.
+		let property = relabel(generator, property);
+
+		// If node does not have a property entry, create one and initialize its value to an
+		// empty array.
+		node_map.graph_mut(active_graph).declare_node(id.clone()).properties.entry(property.clone()).or_default();
+
+		// Recursively invoke this algorithm passing value for element, active graph, id for
+		// active subject, and property for active property.
+		for value in values {
+			let flat_value = extend_node_map(node_map, generator, value, active_graph)?;
+			let flat_node = node_map.graph_mut(active_graph).declare_node(id.clone());
+			let flat_values = flat_node.properties.entry(property.clone()).or_default();
+
+			// List objects are always appended, other values only if they are not already
+			// present.
+			if flat_value.is_list() || !flat_values.contains(&flat_value) {
+				flat_values.push(flat_value)
+			}
+		}
+	}
+
+	Ok(id)
+}
@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use iref::IriBuf;
+use json::JsonValue;
+use crate::{
+	Error,
+	ErrorCode,
+	Id,
+	Reference,
+	Lenient,
+	Indexed,
+	Object,
+	Node,
+	ExpandedDocument,
+	object::{
+		Literal,
+		Value
+	},
+	syntax::Keyword,
+	generic_json::{
+		Json,
+		JsonRef
+	},
+	util::AsJson
+};
+
+/// Embedding policy of a frame.
+///
+/// Defines how matched nodes are embedded in the output when they are referenced more
+/// than once.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Embed {
+	/// `@always`: always embed the node, unless it would create a circular reference.
+	Always,
+
+	/// `@once`: only embed the first occurence of the node, others are node references.
+	#[default]
+	Once,
+
+	/// `@never`: never embed the node, always use a node reference.
+	Never
+}
+
+impl Embed {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Embed::Always => "@always",
+			Embed::Once => "@once",
+			Embed::Never => "@never"
+		}
+	}
+}
+
+impl<'a> TryFrom<&'a str> for Embed {
+	type Error = &'a str;
+
+	/// Convert the strings `"@always"`, `"@once"` and `"@never"` into an `Embed` value.
+	fn try_from(name: &'a str) -> Result<Embed, &'a str> {
+		match name {
+			"@always" => Ok(Embed::Always),
+			"@once" => Ok(Embed::Once),
+			"@never" => Ok(Embed::Never),
+			_ => Err(name)
+		}
+	}
+}
+
+impl<'a> TryFrom<&'a JsonValue> for Embed {
+	type Error = &'a JsonValue;
+
+	/// Convert the value of an `@embed` entry into an `Embed` value.
+	///
+	/// The boolean values `true` and `false` are respectively equivalent to `"@once"` and
+	/// `"@never"`.
+	fn try_from(value: &'a JsonValue) -> Result<Embed, &'a JsonValue> {
+		Embed::from_json(value).ok_or(value)
+	}
+}
+
+impl Embed {
+	/// Convert the value of an `@embed` entry, in any JSON representation, into an `Embed`
+	/// value.
+	///
+	/// The boolean values `true` and `false` are respectively equivalent to `"@once"` and
+	/// `"@never"`.
+	pub fn from_json<J: Json>(value: &J) -> Option<Embed> {
+		match value.as_json_ref() {
+			JsonRef::Boolean(true) => Some(Embed::Once),
+			JsonRef::Boolean(false) => Some(Embed::Never),
+			_ => value.as_str().and_then(|name| Embed::try_from(name).ok())
+		}
+	}
+}
+
+/// Matching pattern.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Pattern<V> {
+	/// Wildcard (`{}`), matching any value, but requiring at least one.
+	Wildcard,
+
+	/// Match none (`[]`), only matching the absence of value.
+	None,
+
+	/// Match any of the given values.
+	Values(Vec<V>)
+}
+
+impl<V: PartialEq> Pattern<V> {
+	/// Checks if the given (optional) value matches the pattern.
+	pub fn matches(&self, value: Option<&V>) -> bool {
+		match (self, value) {
+			(Pattern::Wildcard, value) => value.is_some(),
+			(Pattern::None, value) => value.is_none(),
+			(Pattern::Values(values), None) => values.is_empty(),
+			(Pattern::Values(values), Some(value)) => values.contains(value)
+		}
+	}
+}
+
+/// Value pattern.
+///
+/// Frame matching value objects on their `@value`, `@type` and `@language` entries.
+/// A `Pattern::None` entry is equivalent to an absent entry in the frame.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ValuePattern<T: Id = IriBuf> {
+	/// `@value` pattern.
+	pub value: Pattern<Literal>,
+
+	/// `@type` pattern.
+	pub types: Pattern<T>,
+
+	/// `@language` pattern.
+	pub language: Pattern<String>
+}
+
+impl<T: Id> ValuePattern<T> {
+	/// Value pattern matching exactly the given value object.
+	pub fn from_value(value: &Value<T>) -> ValuePattern<T> {
+		match value {
+			Value::Literal(lit, types) => ValuePattern {
+				value: Pattern::Values(vec![lit.clone()]),
+				types: if types.is_empty() { Pattern::None } else { Pattern::Values(types.iter().cloned().collect()) },
+				language: Pattern::None
+			},
+			Value::LangString(lang_str) => ValuePattern {
+				value: Pattern::Values(vec![Literal::String(lang_str.as_str().to_string())]),
+				types: Pattern::None,
+				language: match lang_str.language() {
+					Some(language) => Pattern::Values(vec![language.to_string()]),
+					None => Pattern::None
+				}
+			}
+		}
+	}
+
+	/// Checks if the given value object matches the pattern.
+	///
+	/// Languages are compared case-insensitively.
+	/// See <https://www.w3.org/TR/json-ld11-framing/#value-pattern-matching>.
+	pub fn matches(&self, value: &Value<T>) -> bool {
+		// If the value pattern has no entry, any value object matches.
+		if self.value == Pattern::None && self.types == Pattern::None && self.language == Pattern::None {
+			return true
+		}
+
+		let (literal, ty, language) = match value {
+			Value::Literal(Literal::Json(_), _) => (None, None, None),
+			Value::Literal(lit, types) => (Some(lit.clone()), types.iter().next(), None),
+			Value::LangString(lang_str) => (Some(Literal::String(lang_str.as_str().to_string())), None, lang_str.language())
+		};
+
+		let value_matches = match &self.value {
+			Pattern::Wildcard => true,
+			Pattern::None => false,
+			Pattern::Values(values) => literal.map(|lit| values.contains(&lit)).unwrap_or(false)
+		};
+
+		let language_matches = match (&self.language, language) {
+			(Pattern::Values(languages), Some(language)) => languages.iter().any(|l| l.eq_ignore_ascii_case(language)),
+			(pattern, language) => pattern.matches(language.map(str::to_string).as_ref())
+		};
+
+		value_matches && self.types.matches(ty) && language_matches
+	}
+}
+
+/// Frame of a property.
+#[derive(Clone, PartialEq, Eq)]
+pub enum PropertyFrame<T: Id = IriBuf> {
+	/// Match none (`[]`): only nodes without this property match.
+	None,
+
+	/// Node frame.
+	Node(Frame<T>),
+
+	/// Value pattern.
+	Value(ValuePattern<T>),
+
+	/// List frame (`{ "@list": [ ... ] }`), applied to the items of a list.
+	List(Box<PropertyFrame<T>>)
+}
+
+/// Frame.
+///
+/// Expanded frame used by the framing algorithm to match and shape nodes.
+/// A new frame is a wildcard frame (`{}`), matching every node.
+/// Flags left to `None` are inherited from the framing options.
+/// See <https://www.w3.org/TR/json-ld11-framing/#framing>.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Frame<T: Id = IriBuf> {
+	/// `@id` pattern.
+	pub id: Option<Pattern<Reference<T>>>,
+
+	/// `@type` pattern.
+	pub types: Option<Pattern<Reference<T>>>,
+
+	/// Default type (`"@type": { "@default": ... }`), used when the node has no type.
+	///
+	/// A frame with a default type matches any node on `@type`.
+	pub default_type: Option<Reference<T>>,
+
+	/// Default value (`@default`) of the property this frame is attached to.
+	///
+	/// The `@null` default is represented by a `null` value.
+	pub default: Option<Vec<Indexed<Object<T>>>>,
+
+	/// `@embed` flag.
+	pub embed: Option<Embed>,
+
+	/// `@explicit` flag.
+	pub explicit: Option<bool>,
+
+	/// `@omitDefault` flag.
+	pub omit_default: Option<bool>,
+
+	/// `@requireAll` flag.
+	pub require_all: Option<bool>,
+
+	/// Frame applied to the named graph of matched nodes (`@graph`).
+	pub graph: Option<Box<Frame<T>>>,
+
+	/// Frame of the included nodes (`@included`).
+	pub included: Option<Box<Frame<T>>>,
+
+	/// Frames of reverse properties (`@reverse`).
+	pub reverse: HashMap<Reference<T>, Frame<T>>,
+
+	/// Frames of properties.
+	pub properties: HashMap<Reference<T>, PropertyFrame<T>>
+}
+
+impl<T: Id> Frame<T> {
+	/// Create a new wildcard frame.
+	pub fn new() -> Frame<T> {
+		Frame {
+			id: None,
+			types: None,
+			default_type: None,
+			default: None,
+			embed: None,
+			explicit: None,
+			omit_default: None,
+			require_all: None,
+			graph: None,
+			included: None,
+			reverse: HashMap::new(),
+			properties: HashMap::new()
+		}
+	}
+
+	/// Checks if this frame is a node reference pattern: a frame with no other entry than
+	/// `@id`.
+	pub fn is_reference(&self) -> bool {
+		self.id.is_some() && *self == Frame {
+			id: self.id.clone(),
+			..Frame::new()
+		}
+	}
+}
+
+impl<T: Id> Default for Frame<T> {
+	fn default() -> Frame<T> {
+		Frame::new()
+	}
+}
+
+/// Framing entries of a node object.
+///
+/// Entries of an expanded frame that cannot be represented by a regular node object.
+/// They are kept by the expansion algorithm when the
+/// [`frame_expansion`](crate::expansion::Options::frame_expansion) option is set.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FrameEntries<T: Id = IriBuf> {
+	/// `@id` pattern, if it is a wildcard (`{}`), match none (`[]`) or an array.
+	pub id: Option<Pattern<Reference<T>>>,
+
+	/// `@type` pattern, if it is a wildcard (`{}`) or match none (`[]`).
+	pub types: Option<Pattern<Reference<T>>>,
+
+	/// Default type (`"@type": { "@default": ... }`).
+	pub default_type: Option<Reference<T>>,
+
+	/// `@default` value.
+	pub default: Option<Vec<Indexed<Object<T>>>>,
+
+	/// `@embed` flag.
+	pub embed: Option<Embed>,
+
+	/// `@explicit` flag.
+	pub explicit: Option<bool>,
+
+	/// `@omitDefault` flag.
+	pub omit_default: Option<bool>,
+
+	/// `@requireAll` flag.
+	pub require_all: Option<bool>,
+
+	/// Value pattern, if the object is a value pattern that is not a regular value object.
+	pub value: Option<ValuePattern<T>>
+}
+
+impl<T: Id> FrameEntries<T> {
+	/// Create new empty framing entries.
+	pub fn new() -> FrameEntries<T> {
+		FrameEntries {
+			id: None,
+			types: None,
+			default_type: None,
+			default: None,
+			embed: None,
+			explicit: None,
+			omit_default: None,
+			require_all: None,
+			value: None
+		}
+	}
+
+	/// Checks if the given keyword is an entry.
+	pub fn has_key(&self, key: Keyword) -> bool {
+		match key {
+			Keyword::Id => self.id.is_some(),
+			Keyword::Type => self.types.is_some() || self.default_type.is_some(),
+			Keyword::Default => self.default.is_some(),
+			Keyword::Embed => self.embed.is_some(),
+			Keyword::Explicit => self.explicit.is_some(),
+			Keyword::OmitDefault => self.omit_default.is_some(),
+			Keyword::RequireAll => self.require_all.is_some(),
+			Keyword::Value => self.value.is_some(),
+			_ => false
+		}
+	}
+}
+
+impl<T: Id> Default for FrameEntries<T> {
+	fn default() -> FrameEntries<T> {
+		FrameEntries::new()
+	}
+}
+
+impl<V: AsJson> AsJson for Pattern<V> {
+	fn as_json(&self) -> JsonValue {
+		match self {
+			Pattern::Wildcard => JsonValue::Array(vec![JsonValue::new_object()]),
+			Pattern::None => JsonValue::new_array(),
+			Pattern::Values(values) => JsonValue::Array(values.iter().map(|v| v.as_json()).collect())
+		}
+	}
+}
+
+impl<T: Id> FrameEntries<T> {
+	/// Add the entries to the JSON representation of a node object.
+	pub(crate) fn insert_json(&self, obj: &mut json::object::Object) {
+		if let Some(id) = &self.id {
+			obj.insert(Keyword::Id.into(), id.as_json())
+		}
+
+		if let Some(types) = &self.types {
+			obj.insert(Keyword::Type.into(), types.as_json())
+		}
+
+		if let Some(default_type) = &self.default_type {
+			let mut default = json::object::Object::new();
+			default.insert(Keyword::Default.into(), default_type.as_json());
+			obj.insert(Keyword::Type.into(), JsonValue::Array(vec![JsonValue::Object(default)]))
+		}
+
+		if let Some(default) = &self.default {
+			obj.insert(Keyword::Default.into(), default.as_json())
+		}
+
+		if let Some(embed) = self.embed {
+			obj.insert(Keyword::Embed.into(), embed.as_str().into())
+		}
+
+		if let Some(explicit) = self.explicit {
+			obj.insert(Keyword::Explicit.into(), explicit.into())
+		}
+
+		if let Some(omit_default) = self.omit_default {
+			obj.insert(Keyword::OmitDefault.into(), omit_default.into())
+		}
+
+		if let Some(require_all) = self.require_all {
+			obj.insert(Keyword::RequireAll.into(), require_all.into())
+		}
+
+		if let Some(pattern) = &self.value {
+			obj.insert(Keyword::Value.into(), pattern.value.as_json());
+
+			if pattern.types != Pattern::None {
+				obj.insert(Keyword::Type.into(), pattern.types.as_json())
+			}
+
+			if pattern.language != Pattern::None {
+				obj.insert(Keyword::Language.into(), pattern.language.as_json())
+			}
+		}
+	}
+}
+
+/// Get the well-formed references of the given list.
+fn references<T: Id>(items: &[Lenient<Reference<T>>]) -> Result<Vec<Reference<T>>, Error> {
+	items.iter().map(|item| match item {
+		Lenient::Ok(r) => Ok(r.clone()),
+		Lenient::Unknown(_) => Err(ErrorCode::InvalidFrame.into())
+	}).collect()
+}
+
+/// Frame of the first node of the given collection, or the wildcard frame if it is empty.
+fn first_frame<'a, T: 'a + Id, I: IntoIterator<Item=&'a Indexed<Object<T>>>>(items: I) -> Result<Frame<T>, Error> {
+	match items.into_iter().next().map(|item| item.inner()) {
+		Some(Object::Node(node)) => Frame::from_node(node),
+		Some(_) => Err(ErrorCode::InvalidFrame.into()),
+		None => Ok(Frame::new())
+	}
+}
+
+impl<T: Id> PropertyFrame<T> {
+	/// Build a property frame from the values of a property in an expanded frame.
+	///
+	/// Only the first value is considered. No value means match none (`[]`).
+	pub fn from_expanded(values: &[Indexed<Object<T>>]) -> Result<PropertyFrame<T>, Error> {
+		match values.first().map(|value| value.inner()) {
+			None => Ok(PropertyFrame::None),
+			Some(Object::List(items)) => {
+				let item_frame = match items.first() {
+					Some(_) => PropertyFrame::from_expanded(items)?,
+					None => PropertyFrame::Node(Frame::new())
+				};
+
+				Ok(PropertyFrame::List(Box::new(item_frame)))
+			},
+			Some(Object::Value(value)) => Ok(PropertyFrame::Value(ValuePattern::from_value(value))),
+			Some(Object::Node(node)) => match node.frame_entries().and_then(|entries| entries.value.as_ref()) {
+				Some(pattern) => Ok(PropertyFrame::Value(pattern.clone())),
+				None => Ok(PropertyFrame::Node(Frame::from_node(node)?))
+			}
+		}
+	}
+}
+
+impl<T: Id> Frame<T> {
+	/// Build a frame from a node of an expanded frame.
+	///
+	/// The node is expected to be the result of the expansion algorithm with the
+	/// [`frame_expansion`](crate::expansion::Options::frame_expansion) option set.
+	/// Only the first object of `@graph`, `@included`, reverse properties and properties
+	/// is considered.
+	pub fn from_node(node: &Node<T>) -> Result<Frame<T>, Error> {
+		let mut frame = Frame::new();
+		let entries = node.frame_entries();
+
+		if entries.map(|entries| entries.value.is_some()).unwrap_or(false) {
+			return Err(ErrorCode::InvalidFrame.into())
+		}
+
+		frame.id = match (entries.and_then(|entries| entries.id.clone()), &node.id) {
+			(Some(pattern), _) => Some(pattern),
+			(None, Some(Lenient::Ok(id))) => Some(Pattern::Values(vec![id.clone()])),
+			(None, Some(Lenient::Unknown(_))) => return Err(ErrorCode::InvalidFrame.into()),
+			(None, None) => None
+		};
+
+		frame.types = match entries.and_then(|entries| entries.types.clone()) {
+			Some(pattern) => Some(pattern),
+			None if !node.types.is_empty() => Some(Pattern::Values(references(&node.types)?)),
+			None => None
+		};
+
+		if let Some(entries) = entries {
+			frame.default_type = entries.default_type.clone();
+			frame.default = entries.default.as_ref().map(|default| {
+				// The `@null` default value.
+				match default.as_slice() {
+					[value] if value.as_str() == Some(Keyword::Null.into_str()) => vec![Indexed::new(Object::Value(Value::Literal(Literal::Null, Default::default())), None)],
+					_ => default.clone()
+				}
+			});
+			frame.embed = entries.embed;
+			frame.explicit = entries.explicit;
+			frame.omit_default = entries.omit_default;
+			frame.require_all = entries.require_all;
+		}
+
+		if let Some(graph) = node.graph() {
+			frame.graph = Some(Box::new(first_frame(graph)?))
+		}
+
+		if let Some(included) = node.included() {
+			frame.included = Some(Box::new(match included.iter().next() {
+				Some(included) => Frame::from_node(included)?,
+				None => Frame::new()
+			}))
+		}
+
+		for (property, nodes) in &node.reverse_properties {
+			let reverse_frame = match nodes.first() {
+				Some(reverse_node) => Frame::from_node(reverse_node)?,
+				None => Frame::new()
+			};
+
+			frame.reverse.insert(property.clone(), reverse_frame);
+		}
+
+		for (property, values) in &node.properties {
+			frame.properties.insert(property.clone(), PropertyFrame::from_expanded(values)?);
+		}
+
+		Ok(frame)
+	}
+
+	/// Build a frame from an expanded frame document.
+	///
+	/// The document must contain exactly one node object, otherwise an `InvalidFrame` error
+	/// is returned.
+	/// An empty document is the wildcard frame.
+	pub fn from_expanded(document: &ExpandedDocument<T>) -> Result<Frame<T>, Error> {
+		if document.len() > 1 {
+			return Err(ErrorCode::InvalidFrame.into())
+		}
+
+		first_frame(document)
+	}
+}
@@ -0,0 +1,928 @@
+//! Framing algorithm and types.
+//!
+//! See <https://www.w3.org/TR/json-ld11-framing/>.
+
+mod frame;
+
+use std::cell::OnceCell;
+use std::collections::{
+	HashMap,
+	HashSet
+};
+use json::JsonValue;
+use crate::{
+	Error,
+	Id,
+	BlankId,
+	BlankIdGenerator,
+	Reference,
+	Lenient,
+	Indexed,
+	Object,
+	Node,
+	ContextMut,
+	context::{
+		Loader,
+		Processed
+	},
+	object::{
+		Literal,
+		Value
+	},
+	flattening::{
+		generate_node_map,
+		NodeMap,
+		NodeMapGraph
+	},
+	compaction,
+	syntax::Keyword,
+	generic_json::Json
+};
+
+pub use frame::*;
+
+/// Framing options.
+#[derive(Clone, Copy)]
+pub struct Options {
+	/// Default value of the `@embed` flag.
+	pub embed: Embed,
+
+	/// Default value of the `@explicit` flag.
+	pub explicit: bool,
+
+	/// Default value of the `@requireAll` flag.
+	pub require_all: bool,
+
+	/// Default value of the `@omitDefault` flag.
+	pub omit_default: bool,
+
+	/// If set to true, the frame is matched against the default graph only.
+	/// Otherwise it is matched against the merge of every graph of the input.
+	pub frame_default: bool,
+
+	/// If set to true, the `@id` entry of blank nodes that are only used once are removed.
+	pub prune_blank_node_identifiers: bool
+}
+
+impl Default for Options {
+	fn default() -> Options {
+		Options {
+			embed: Embed::Once,
+			explicit: false,
+			require_all: false,
+			omit_default: false,
+			frame_default: false,
+			prune_blank_node_identifiers: true
+		}
+	}
+}
+
+/// Graph in which the framing takes place.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum GraphName<T: Id> {
+	/// Default graph.
+	Default,
+
+	/// Merge of every graph.
+	Merged,
+
+	/// Named graph.
+	Named(Reference<T>)
+}
+
+/// Value of the framing flags for a given frame.
+#[derive(Clone, Copy)]
+struct Flags {
+	embed: Embed,
+	explicit: bool,
+	require_all: bool
+}
+
+impl Flags {
+	fn new<T: Id>(frame: &Frame<T>, options: &Options) -> Flags {
+		Flags {
+			embed: frame.embed.unwrap_or(options.embed),
+			explicit: frame.explicit.unwrap_or(options.explicit),
+			require_all: frame.require_all.unwrap_or(options.require_all)
+		}
+	}
+
+	/// Frame used for properties that do not appear in the frame, carrying the current flags.
+	fn implicit_frame<T: Id>(&self) -> Frame<T> {
+		Frame {
+			embed: Some(self.embed),
+			explicit: Some(self.explicit),
+			require_all: Some(self.require_all),
+			..Frame::new()
+		}
+	}
+}
+
+/// Framing state.
+struct State<'a, T: Id> {
+	options: Options,
+
+	/// Node map of the input.
+	node_map: &'a NodeMap<T>,
+
+	/// Merge of every graph of the node map, computed the first time it is needed.
+	merged: &'a OnceCell<NodeMapGraph<T>>,
+
+	/// Nodes already embedded, per graph.
+	unique_embeds: HashMap<GraphName<T>, HashSet<Reference<T>>>,
+
+	/// Nodes being embedded, to detect circular references.
+	subject_stack: Vec<(GraphName<T>, Reference<T>)>,
+
+	/// Number of occurences of each blank node identifier in the output.
+	blank_counts: HashMap<BlankId, usize>
+}
+
+impl<'a, T: Id> State<'a, T> {
+	fn graph(&self, name: &GraphName<T>) -> &'a NodeMapGraph<T> {
+		match name {
+			GraphName::Default => self.node_map.default_graph(),
+			GraphName::Merged => self.merged.get_or_init(|| merge_node_map(self.node_map)),
+			GraphName::Named(id) => self.node_map.graph(Some(id)).unwrap()
+		}
+	}
+
+	fn count_blank(&mut self, id: &Reference<T>) {
+		if let Reference::Blank(id) = id {
+			*self.blank_counts.entry(id.clone()).or_insert(0) += 1
+		}
+	}
+}
+
+/// Merge every graph of the node map into a single graph.
+fn merge_node_map<T: Id>(node_map: &NodeMap<T>) -> NodeMapGraph<T> {
+	let mut merged = NodeMapGraph::new();
+
+	let mut graphs = vec![node_map.default_graph()];
+	let mut named_graphs: Vec<_> = node_map.named_graphs().collect();
+	named_graphs.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+	graphs.extend(named_graphs.into_iter().map(|(_, graph)| graph));
+
+	for graph in graphs {
+		for (id, node) in sorted_nodes(graph) {
+			let merged_node = merged.declare_node(id.clone());
+
+			for ty in &node.types {
+				if !merged_node.types.contains(ty) {
+					merged_node.types.push(ty.clone())
+				}
+			}
+
+			if let Some(index) = node.index() {
+				merged_node.set_index(Some(index.to_string()))
+			}
+
+			for (property, values) in &node.properties {
+				let merged_values = merged_node.properties.entry(property.clone()).or_insert_with(Vec::new);
+				for value in values {
+					if value.is_list() || !merged_values.contains(value) {
+						merged_values.push(value.clone())
+					}
+				}
+			}
+		}
+	}
+
+	merged
+}
+
+/// Nodes of the given graph, ordered by identifier.
+fn sorted_nodes<T: Id>(graph: &NodeMapGraph<T>) -> Vec<(&Reference<T>, &Indexed<Node<T>>)> {
+	let mut nodes: Vec<_> = graph.iter().collect();
+	nodes.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+	nodes
+}
+
+/// Get the identifier of the given node reference.
+fn node_reference<T: Id>(object: &Object<T>) -> Option<&Reference<T>> {
+	match object {
+		Object::Node(node) => match &node.id {
+			Some(Lenient::Ok(id)) => Some(id),
+			_ => None
+		},
+		_ => None
+	}
+}
+
+/// Checks if the given value matches the frame of a property.
+fn value_match<T: Id>(frame: &PropertyFrame<T>, value: &Value<T>) -> bool {
+	match frame {
+		PropertyFrame::None => false,
+		// A node frame only matches values when it has no `@type` constraint.
+		PropertyFrame::Node(frame) => frame.default_type.is_none() && match &frame.types {
+			None | Some(Pattern::None) => true,
+			Some(Pattern::Values(types)) => types.is_empty(),
+			Some(Pattern::Wildcard) => false
+		},
+		PropertyFrame::Value(pattern) => pattern.matches(value),
+		PropertyFrame::List(_) => true
+	}
+}
+
+/// Checks if the given object is a node matching the frame.
+fn node_match<T: Id>(graph: &NodeMapGraph<T>, frame: &Frame<T>, object: &Object<T>, flags: Flags) -> bool {
+	match node_reference(object).and_then(|id| graph.get(id)) {
+		Some(node) => filter_subject(graph, node, frame, flags),
+		None => false
+	}
+}
+
+/// Checks if the given property values matches the frame of the property.
+fn property_match<T: Id>(graph: &NodeMapGraph<T>, frame: &PropertyFrame<T>, values: &[Indexed<Object<T>>], flags: Flags) -> bool {
+	match frame {
+		PropertyFrame::None => values.is_empty(),
+		PropertyFrame::List(item_frame) => match values.first().map(|value| value.inner()) {
+			Some(Object::List(items)) => match item_frame.as_ref() {
+				PropertyFrame::Value(pattern) => items.iter().any(|item| match item.inner() {
+					Object::Value(value) => pattern.matches(value),
+					_ => false
+				}),
+				PropertyFrame::Node(frame) => items.iter().any(|item| node_match(graph, frame, item, flags)),
+				_ => false
+			},
+			_ => false
+		},
+		PropertyFrame::Value(pattern) => values.iter().any(|value| match value.inner() {
+			Object::Value(value) => pattern.matches(value),
+			_ => false
+		}),
+		PropertyFrame::Node(frame) => {
+			if frame.is_reference() {
+				values.iter().any(|value| node_match(graph, frame, value, flags))
+			} else {
+				!values.is_empty()
+			}
+		}
+	}
+}
+
+/// Frame matching.
+///
+/// Checks if the given node matches the frame.
+/// See <https://www.w3.org/TR/json-ld11-framing/#frame-matching>.
+fn filter_subject<T: Id>(graph: &NodeMapGraph<T>, node: &Node<T>, frame: &Frame<T>, flags: Flags) -> bool {
+	let mut wildcard = true;
+	let mut matches_some = false;
+
+	// Node matches if it has an @id property including any IRI or blank node in the @id
+	// property in frame.
+	if let Some(pattern) = &frame.id {
+		let id = match &node.id {
+			Some(Lenient::Ok(id)) => Some(id),
+			_ => None
+		};
+
+		let match_this = pattern.matches(id);
+
+		if !flags.require_all {
+			return match_this
+		}
+
+		if !match_this {
+			return false
+		}
+
+		matches_some = true
+	}
+
+	// Node matches if it has any @type property including any IRI in the @type property
+	// in frame, or if frame defines a default type.
+	if frame.types.is_some() || frame.default_type.is_some() {
+		wildcard = false;
+
+		let types: Vec<_> = node.types.iter().filter_map(|ty| match ty {
+			Lenient::Ok(ty) => Some(ty),
+			_ => None
+		}).collect();
+
+		let match_this = match (&frame.types, &frame.default_type) {
+			// The frame has a default type, any node matches.
+			(_, Some(_)) => true,
+			(Some(Pattern::Values(frame_types)), None) if !frame_types.is_empty() => {
+				types.iter().any(|ty| frame_types.contains(ty))
+			},
+			// Node matches if the frame is the wildcard and the node has any type.
+			(Some(Pattern::Wildcard), None) => !types.is_empty(),
+			// Node matches if the frame is the match none pattern and the node has no type.
+			_ => types.is_empty()
+		};
+
+		if !flags.require_all {
+			return match_this
+		}
+
+		if !match_this {
+			return false
+		}
+
+		matches_some = true
+	}
+
+	// Node matches if frame has no non-keyword properties, or if it matches the frame of
+	// each (with requireAll) or any property.
+	let mut properties: Vec<_> = frame.properties.iter().collect();
+	properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+	for (property, property_frame) in properties {
+		wildcard = false;
+
+		let values = node.properties.get(property).map(Vec::as_slice).unwrap_or(&[]);
+		let has_default = match property_frame {
+			PropertyFrame::Node(frame) => frame.default.is_some(),
+			_ => false
+		};
+
+		// Skip, but allow the match if the node has no value for the property, and the frame
+		// has a default value.
+		if values.is_empty() && has_default {
+			continue
+		}
+
+		// If the frame value is empty, do not match if the node has any value.
+		if !values.is_empty() && *property_frame == PropertyFrame::None {
+			return false
+		}
+
+		let match_this = property_match(graph, property_frame, values, flags);
+
+		if !match_this && flags.require_all {
+			return false
+		}
+
+		matches_some = matches_some || match_this
+	}
+
+	wildcard || matches_some
+}
+
+/// Checks if embedding the given node would create a circular reference.
+fn creates_circular_reference<T: Id>(state: &State<T>, graph: &GraphName<T>, id: &Reference<T>) -> bool {
+	state.subject_stack.iter().rev().any(|(g, s)| g == graph && s == id)
+}
+
+/// Framing algorithm.
+///
+/// Frame the nodes of `subjects` (identifiers of `graph`) matching the given frame.
+/// Returns the framed nodes in the order of `subjects`.
+/// See <https://www.w3.org/TR/json-ld11-framing/#framing-algorithm>.
+fn frame_nodes<T: Id>(state: &mut State<T>, subjects: &[&Reference<T>], frame: &Frame<T>, graph_name: &GraphName<T>, embedded: bool, top_level: bool) -> Result<Vec<Indexed<Node<T>>>, Error> {
+	// Initialize flags embed, explicit, and requireAll from object embed flag, explicit
+	// inclusion flag, and require all flag in state overriding from any property values for
+	// @embed, @explicit, and @requireAll in frame.
+	let flags = Flags::new(frame, &state.options);
+	let graph = state.graph(graph_name);
+	let mut result = Vec::new();
+
+	// Create a list of matched subjects by filtering subjects against frame using the Frame
+	// Matching algorithm with state, subjects, frame, and requireAll.
+	// For each id and associated node object node from the set of matched subjects, ordered
+	// lexicographically by id:
+	let mut matched: Vec<_> = subjects.iter().filter_map(|id| {
+		graph.get(id).filter(|node| filter_subject(graph, node, frame, flags)).map(|node| (*id, node))
+	}).collect();
+	matched.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+	for (id, node) in matched {
+		// Initialize output to a new map with @id and id.
+		let mut output = Node::with_id(Lenient::Ok(id.clone()));
+		state.count_blank(id);
+
+		// Embedded nodes are only unique per top level node.
+		if top_level {
+			state.unique_embeds.clear()
+		}
+
+		// If embed is @never or if a circular reference would be created by an embed, add
+		// output to parent and do not perform additional processing for this node.
+		if flags.embed == Embed::Never || creates_circular_reference(state, graph_name, id) {
+			result.push(Indexed::new(output, None));
+			continue
+		}
+
+		// Otherwise, if embed is @once and parent has an existing embedded node in parent
+		// associated with graph name and id in state's uniqueEmbeds, add output to parent
+		// and do not perform additional processing for this node.
+		let unique_embeds = state.unique_embeds.entry(graph_name.clone()).or_default();
+		if embedded && flags.embed == Embed::Once && unique_embeds.contains(id) {
+			result.push(Indexed::new(output, None));
+			continue
+		}
+
+		unique_embeds.insert(id.clone());
+		state.subject_stack.push((graph_name.clone(), id.clone()));
+
+		// If state's graph map has an entry for id (the node is also a graph name):
+		if let Some(named_graph) = state.node_map.graph(Some(id)) {
+			// If frame does not have the key @graph, set recurse to true, unless graph name
+			// in state is @merged and set subframe to a new empty map.
+			// Otherwise, set subframe to the first entry for @graph in frame, or a new empty
+			// map, if it does not exist, and set recurse to true, unless id is @merged or
+			// @default.
+			let wildcard = Frame::new();
+			let (recurse, subframe) = match &frame.graph {
+				Some(subframe) => (true, subframe.as_ref()),
+				None => (*graph_name != GraphName::Merged, &wildcard)
+			};
+
+			// If recurse is true, invoke the recursive algorithm using a copy of state with
+			// the value of graph set to id and the value of embedded set to false, the keys
+			// from the graph map in state associated with the graph name in state ordered
+			// lexicographically for subjects, subframe for frame, output for parent, and
+			// @graph for active property.
+			if recurse {
+				let graph_subjects: Vec<_> = sorted_nodes(named_graph).into_iter().map(|(id, _)| id).collect();
+				let graph = frame_nodes(state, &graph_subjects, subframe, &GraphName::Named(id.clone()), false, false)?;
+				output.graph = Some(Box::new(graph.into_iter().map(|node| node.cast::<Object<T>>()).collect()))
+			}
+		}
+
+		// If frame has an @included entry, invoke the recursive algorithm using a copy of
+		// state with the value of embedded set to false, subjects, the value of @included
+		// for frame, output for parent, and @included as active property.
+		if let Some(included_frame) = &frame.included {
+			let included = frame_nodes(state, subjects, included_frame, graph_name, false, false)?;
+			output.included = Some(Box::new(included.into_iter().collect()))
+		}
+
+		// For each property and objects in node, ordered by property:
+		// If property is a keyword, add property and objects to output.
+		for ty in &node.types {
+			if let Lenient::Ok(ty) = ty {
+				state.count_blank(ty)
+			}
+
+			output.types.push(ty.clone())
+		}
+
+		let mut properties: Vec<_> = node.properties.iter().collect();
+		properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+		for (property, objects) in properties {
+			// Otherwise, if property is not in frame, and explicit is true, processors must
+			// not add any values for property to output, and the following steps are
+			// skipped.
+			let property_frame = frame.properties.get(property);
+			if flags.explicit && property_frame.is_none() {
+				continue
+			}
+
+			// For each item in objects:
+			let implicit_frame = PropertyFrame::Node(flags.implicit_frame());
+			let subframe = property_frame.unwrap_or(&implicit_frame);
+
+			for item in objects {
+				match item.inner() {
+					// If item is a list object, process each list item by invoking the
+					// recursive algorithm for node references, or adding the value as is.
+					Object::List(list) => {
+						let list_frame = match subframe {
+							PropertyFrame::List(list_frame) => match list_frame.as_ref() {
+								PropertyFrame::Node(list_frame) => Some(list_frame),
+								_ => None
+							},
+							_ => None
+						};
+
+						let implicit_list_frame = flags.implicit_frame();
+						let list_frame = list_frame.unwrap_or(&implicit_list_frame);
+
+						let mut framed_list = Vec::new();
+						for list_item in list {
+							match node_reference(list_item.inner()) {
+								Some(list_item_id) => {
+									let framed = frame_nodes(state, &[list_item_id], list_frame, graph_name, true, false)?;
+									framed_list.extend(framed.into_iter().map(|node| node.cast::<Object<T>>()))
+								},
+								None => framed_list.push(list_item.clone())
+							}
+						}
+
+						output.insert(property.clone(), Indexed::new(Object::List(framed_list), item.index().map(str::to_string)))
+					},
+					Object::Node(_) => {
+						// If item is a node reference, invoke the recursive algorithm using a
+						// copy of state with the value of embedded set to true, the value of
+						// id from item as the sole item in a new subjects array, the first
+						// value from property in frame as frame, output as parent, and
+						// property as active property.
+						let item_frame = match subframe {
+							PropertyFrame::Node(frame) => Some(frame),
+							PropertyFrame::None => None,
+							_ => Some(match &implicit_frame {
+								PropertyFrame::Node(frame) => frame,
+								_ => unreachable!()
+							})
+						};
+
+						if let (Some(item_frame), Some(item_id)) = (item_frame, node_reference(item.inner())) {
+							let framed = frame_nodes(state, &[item_id], item_frame, graph_name, true, false)?;
+							output.insert_all(property.clone(), framed.into_iter().map(|node| node.cast::<Object<T>>()))
+						}
+					},
+					// Otherwise, if item is a value object matching the frame of the
+					// property, add it to output.
+					Object::Value(value) => {
+						if value_match(subframe, value) {
+							output.insert(property.clone(), item.clone())
+						}
+					}
+				}
+			}
+		}
+
+		// For each non-keyword property and objects in frame (other than @type) that is not
+		// in output:
+		let mut frame_properties: Vec<_> = frame.properties.iter().collect();
+		frame_properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+		for (property, property_frame) in frame_properties {
+			// Let item be the first element in objects, which must be a frame object.
+			// Set property frame to the first item in objects or a newly created frame object
+			// if value is objects. property frame must be a map.
+			// Skip property and property frame if property frame contains @omitDefault with a
+			// value of true, or does not contain @omitDefault and the value of the omit
+			// default flag is true.
+			let (omit_default, default) = match property_frame {
+				PropertyFrame::Node(frame) => (frame.omit_default.unwrap_or(state.options.omit_default), frame.default.as_ref()),
+				_ => (state.options.omit_default, None)
+			};
+
+			if !omit_default && !output.properties.contains_key(property) {
+				// Add property to output with a new map having a property @preserve and a
+				// value that is a copy of the value of @default in frame if it exists, or the
+				// string @null otherwise.
+				let values = match default {
+					Some(default) => default.clone(),
+					None => vec![Indexed::new(Object::Value(Value::Literal(Literal::Null, HashSet::new())), None)]
+				};
+
+				output.insert_all(property.clone(), values.into_iter())
+			}
+		}
+
+		// Default type.
+		if let Some(default_type) = &frame.default_type {
+			if !state.options.omit_default && output.types.is_empty() {
+				output.types.push(Lenient::Ok(default_type.clone()))
+			}
+		}
+
+		// If frame has @reverse, then for each reverse property and sub frame that are the
+		// values of @reverse in frame:
+		let mut reverse_properties: Vec<_> = frame.reverse.iter().collect();
+		reverse_properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+		for (reverse_property, subframe) in reverse_properties {
+			// For each reverse id and node in the map of flattened subjects that has the
+			// property reverse property containing a node reference with an @id of id:
+			let reverse_subjects: Vec<_> = sorted_nodes(graph).into_iter().filter(|(_, subject)| {
+				subject.properties.get(reverse_property).map(|values| {
+					values.iter().any(|value| node_reference(value.inner()) == Some(id))
+				}).unwrap_or(false)
+			}).map(|(id, _)| id).collect();
+
+			if !reverse_subjects.is_empty() {
+				// Invoke the recursive algorithm using a copy of state with the value of
+				// embedded set to true, the reverse id as the sole item in a new subjects
+				// array, sub frame as frame, null as active property, and the array value of
+				// reverse property in the @reverse map in output as parent.
+				let framed = frame_nodes(state, &reverse_subjects, subframe, graph_name, true, false)?;
+				output.reverse_properties.entry(reverse_property.clone()).or_insert_with(Vec::new).extend(framed)
+			}
+		}
+
+		// Once output has been set are required in the previous steps, add output to
+		// parent.
+		result.push(Indexed::new(output, node.index().map(str::to_string)));
+		state.subject_stack.pop();
+	}
+
+	Ok(result)
+}
+
+/// Remove the identifier of the blank nodes used only once in the output.
+fn prune_blank_node_identifiers<T: Id>(node: &mut Node<T>, blank_counts: &HashMap<BlankId, usize>) {
+	if let Some(Lenient::Ok(Reference::Blank(id))) = &node.id {
+		if blank_counts.get(id).cloned().unwrap_or(0) <= 1 {
+			node.id = None
+		}
+	}
+
+	fn prune_object<T: Id>(object: &mut Object<T>, blank_counts: &HashMap<BlankId, usize>) {
+		match object {
+			Object::Node(node) => prune_blank_node_identifiers(node, blank_counts),
+			Object::List(items) => {
+				for item in items {
+					prune_object(item, blank_counts)
+				}
+			},
+			Object::Value(_) => ()
+		}
+	}
+
+	if let Some(graph) = node.graph.take() {
+		node.graph = Some(Box::new(graph.into_iter().map(|mut item| {
+			prune_object(&mut item, blank_counts);
+			item
+		}).collect()))
+	}
+
+	if let Some(included) = node.included.take() {
+		node.included = Some(Box::new(included.into_iter().map(|mut item| {
+			prune_blank_node_identifiers(&mut item, blank_counts);
+			item
+		}).collect()))
+	}
+
+	for values in node.properties.values_mut() {
+		for value in values {
+			prune_object(value, blank_counts)
+		}
+	}
+
+	for nodes in node.reverse_properties.values_mut() {
+		for reverse_node in nodes {
+			prune_blank_node_identifiers(reverse_node, blank_counts)
+		}
+	}
+}
+
+/// Framing algorithm.
+///
+/// Returns the top-level nodes of the input matching the given frame, where matching
+/// nodes are embedded according to the frame.
+/// Unless the `frame_default` option is set, the frame is matched against the merge of
+/// every graph of the input.
+/// Blank nodes are labeled using the given generator
+/// (for instance [`BlankIdCounter`](crate::BlankIdCounter)).
+/// See <https://www.w3.org/TR/json-ld11-framing/#framing-algorithm>.
+pub fn frame<'a, T: 'a + Id, I: IntoIterator<Item = &'a Indexed<Object<T>>>, G: BlankIdGenerator>(input: I, frame: &Frame<T>, generator: G, options: Options) -> Result<Vec<Indexed<Node<T>>>, Error> {
+	// Initialize node map using the Node Map Generation algorithm.
+	let node_map = generate_node_map(input, generator)?;
+
+	// If the frameDefault option is present with the value true, set graph name in state
+	// to @default. Otherwise, create merged node map using the Merge Node Maps algorithm
+	// and set graph name in state to @merged.
+	// The merged node map is only created if it is used.
+	let merged = OnceCell::new();
+
+	let graph_name = if options.frame_default {
+		GraphName::Default
+	} else {
+		GraphName::Merged
+	};
+
+	let mut state = State {
+		options,
+		node_map: &node_map,
+		merged: &merged,
+		unique_embeds: HashMap::new(),
+		subject_stack: Vec::new(),
+		blank_counts: HashMap::new()
+	};
+
+	// Invoke the recursive algorithm using state, the keys from the graph map in state
+	// associated with the graph name in state ordered lexicographically for subjects,
+	// frame, result for parent, and null as active property.
+	let subjects: Vec<_> = sorted_nodes(state.graph(&graph_name)).into_iter().map(|(id, _)| id).collect();
+	let mut result = frame_nodes(&mut state, &subjects, frame, &graph_name, false, true)?;
+
+	// If the pruneBlankNodeIdentifiers flag is set, remove the @id entry of blank nodes
+	// that are only used once.
+	if options.prune_blank_node_identifiers {
+		for node in &mut result {
+			prune_blank_node_identifiers(node, &state.blank_counts)
+		}
+	}
+
+	Ok(result)
+}
+
+/// Compact the result of the framing algorithm using the given context.
+///
+/// If `omit_graph` is true, the top-level `@graph` entry is omitted when there is exactly
+/// one framed node, and the output is that node. Otherwise the output always has a
+/// top-level `@graph` entry (or its alias).
+/// See <https://www.w3.org/TR/json-ld11-framing/#dom-jsonldprocessor-frame>.
+pub async fn compact_framed<T: Send + Sync + Id, C: Send + Sync + ContextMut<T>, L: Send + Sync + Loader>(framed: &[Indexed<Node<T>>], context: &Processed<C::LocalContext, C>, loader: &mut L, options: compaction::Options, omit_graph: bool) -> Result<JsonValue, Error> where C::LocalContext: Send + Sync, L::Output: Json {
+	let active_context = context.processed();
+
+	let compacted = compaction::compact_collection(framed.iter(), active_context, None, loader, options).await?;
+	let result = match compacted {
+		JsonValue::Object(object) if omit_graph => object,
+		JsonValue::Array(items) if omit_graph && items.is_empty() => json::object::Object::new(),
+		compacted => {
+			let items = match compacted {
+				JsonValue::Array(items) => items,
+				item => vec![item]
+			};
+
+			let mut result = json::object::Object::new();
+			result.insert(&compaction::compact_key(active_context, Keyword::Graph, options)?, JsonValue::Array(items));
+			result
+		}
+	};
+
+	Ok(JsonValue::Object(compaction::with_context(context.local(), result)))
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use crate::{
+		Document,
+		NoLoader,
+		BlankIdCounter,
+		expansion,
+		context::{
+			JsonContext,
+			Local
+		}
+	};
+	use super::*;
+
+	const INPUT: &str = r#"{
+		"@context": {"@vocab": "http://example.org/"},
+		"@graph": [
+			{"@id": "http://example.org/library", "@type": "Library", "contains": {"@id": "http://example.org/book"}},
+			{"@id": "http://example.org/book", "@type": "Book", "title": "Book", "contains": {"@id": "http://example.org/chapter"}},
+			{"@id": "http://example.org/chapter", "@type": "Chapter", "title": "Chapter", "author": {"name": "Alice"}}
+		]
+	}"#;
+
+	/// Frame the input with the given frame, and compact the result with the frame context.
+	fn frame_json(input: &str, frame_doc: &str, options: Options) -> JsonValue {
+		let input = json::parse(input).unwrap();
+		let frame_doc = json::parse(frame_doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = block_on(input.expand_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+
+		let frame_options = expansion::Options {
+			frame_expansion: true,
+			..expansion::Options::default()
+		};
+		let expanded_frame = block_on(frame_doc.expand_with(None, &context, &mut NoLoader, frame_options)).unwrap();
+		let frame_value = Frame::from_expanded(&expanded_frame).unwrap();
+
+		let framed = frame(&expanded, &frame_value, BlankIdCounter::default(), options).unwrap();
+		let local = frame_doc["@context"].clone();
+		let processed = Processed::new(local.clone(), block_on(local.process(&context, &mut NoLoader, None)).unwrap());
+		block_on(compact_framed(&framed, &processed, &mut NoLoader, compaction::Options::default(), true)).unwrap()
+	}
+
+	#[test]
+	fn embedding() {
+		let frame = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@type": "Library",
+			"contains": {"@type": "Book", "contains": {"@type": "Chapter"}}
+		}"#;
+
+		let expected = json::parse(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@id": "http://example.org/library",
+			"@type": "Library",
+			"contains": {
+				"@id": "http://example.org/book",
+				"@type": "Book",
+				"title": "Book",
+				"contains": {
+					"@id": "http://example.org/chapter",
+					"@type": "Chapter",
+					"title": "Chapter",
+					"author": {"name": "Alice"}
+				}
+			}
+		}"#).unwrap();
+
+		assert_eq!(frame_json(INPUT, frame, Options::default()), expected)
+	}
+
+	#[test]
+	fn explicit() {
+		let frame = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@type": "Book",
+			"@explicit": true,
+			"title": {}
+		}"#;
+
+		let expected = json::parse(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@id": "http://example.org/book",
+			"@type": "Book",
+			"title": "Book"
+		}"#).unwrap();
+
+		assert_eq!(frame_json(INPUT, frame, Options::default()), expected)
+	}
+
+	#[test]
+	fn embed_never() {
+		let frame = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@type": "Library",
+			"contains": {"@embed": "@never"}
+		}"#;
+
+		let expected = json::parse(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@id": "http://example.org/library",
+			"@type": "Library",
+			"contains": {"@id": "http://example.org/book"}
+		}"#).unwrap();
+
+		assert_eq!(frame_json(INPUT, frame, Options::default()), expected)
+	}
+
+	#[test]
+	fn default_values() {
+		let frame = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@type": "Chapter",
+			"isbn": {"@default": "none"}
+		}"#;
+
+		let framed = frame_json(INPUT, frame, Options::default());
+		assert_eq!(framed["isbn"], "none");
+
+		let options = Options {
+			omit_default: true,
+			..Options::default()
+		};
+		assert!(!frame_json(INPUT, frame, options).has_key("isbn"))
+	}
+
+	#[test]
+	fn no_match() {
+		let frame = r#"{"@context": {"@vocab": "http://example.org/"}, "@type": "Shelf"}"#;
+		assert_eq!(frame_json(INPUT, frame, Options::default()), json::object! {"@context": {"@vocab": "http://example.org/"}})
+	}
+
+	#[test]
+	fn blank_node_identifiers() {
+		let frame = r#"{"@context": {"@vocab": "http://example.org/"}, "@type": "Chapter"}"#;
+
+		let mut options = Options::default();
+		assert!(!frame_json(INPUT, frame, options)["author"].has_key("@id"));
+
+		options.prune_blank_node_identifiers = false;
+		assert_eq!(frame_json(INPUT, frame, options)["author"]["@id"], "_:b0")
+	}
+
+	#[test]
+	fn type_patterns() {
+		let input = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@graph": [
+				{"@id": "http://example.org/a", "@type": "Book", "title": "A"},
+				{"@id": "http://example.org/b", "title": "B"}
+			]
+		}"#;
+
+		let frame_id = |frame: &str, options| frame_json(input, frame, options)["@id"].clone();
+
+		// The wildcard matches typed nodes, the match none pattern untyped nodes.
+		assert_eq!(frame_id(r#"{"@context": {"@vocab": "http://example.org/"}, "@type": {}}"#, Options::default()), "http://example.org/a");
+		assert_eq!(frame_id(r#"{"@context": {"@vocab": "http://example.org/"}, "@type": []}"#, Options::default()), "http://example.org/b");
+
+		// Without requireAll, the type pattern alone decides, whatever the other properties.
+		let frame = r#"{"@context": {"@vocab": "http://example.org/"}, "@type": {}, "title": {}}"#;
+		assert_eq!(frame_id(frame, Options::default()), "http://example.org/a");
+
+		let options = Options {
+			require_all: true,
+			..Options::default()
+		};
+		assert_eq!(frame_id(frame, options), "http://example.org/a");
+
+		// A default type matches every node.
+		let frame = r#"{"@context": {"@vocab": "http://example.org/"}, "@type": {"@default": "Book"}}"#;
+		assert_eq!(frame_json(input, frame, Options::default())["@graph"].len(), 2)
+	}
+
+	#[test]
+	fn frame_default() {
+		let input = r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@id": "http://example.org/g",
+			"@graph": {"@id": "http://example.org/a", "@type": "Book"}
+		}"#;
+
+		let frame = r#"{"@context": {"@vocab": "http://example.org/"}, "@type": "Book"}"#;
+
+		// By default, the nodes of every graph are matched.
+		assert_eq!(frame_json(input, frame, Options::default())["@id"], "http://example.org/a");
+
+		let options = Options {
+			frame_default: true,
+			..Options::default()
+		};
+		assert_eq!(frame_json(input, frame, options), json::object! {"@context": {"@vocab": "http://example.org/"}})
+	}
+}
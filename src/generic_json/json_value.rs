@@ -0,0 +1,69 @@
+use json::{
+	JsonValue,
+	number::Number,
+	object::Object
+};
+use super::{
+	Json,
+	JsonObject,
+	JsonRef
+};
+
+impl JsonObject<JsonValue> for Object {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn get(&self, key: &str) -> Option<&JsonValue> {
+		self.get(key)
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn 'a + Send + Iterator<Item = (&'a str, &'a JsonValue)>> {
+		Box::new(self.iter())
+	}
+}
+
+impl Json for JsonValue {
+	type Object = Object;
+
+	fn as_json_ref(&self) -> JsonRef<'_, JsonValue> {
+		match self {
+			JsonValue::Null => JsonRef::Null,
+			JsonValue::Boolean(b) => JsonRef::Boolean(*b),
+			JsonValue::Number(n) => JsonRef::Number(*n),
+			JsonValue::Short(s) => JsonRef::String(s.as_str()),
+			JsonValue::String(s) => JsonRef::String(s.as_str()),
+			JsonValue::Array(items) => JsonRef::Array(items),
+			JsonValue::Object(obj) => JsonRef::Object(obj)
+		}
+	}
+
+	fn null() -> JsonValue {
+		JsonValue::Null
+	}
+
+	fn boolean(b: bool) -> JsonValue {
+		JsonValue::Boolean(b)
+	}
+
+	fn number(n: Number) -> JsonValue {
+		JsonValue::Number(n)
+	}
+
+	fn string(s: &str) -> JsonValue {
+		s.into()
+	}
+
+	fn array(items: Vec<JsonValue>) -> JsonValue {
+		JsonValue::Array(items)
+	}
+
+	fn object(entries: Vec<(String, JsonValue)>) -> JsonValue {
+		let mut obj = Object::new();
+		for (key, value) in entries {
+			obj.insert(&key, value)
+		}
+
+		JsonValue::Object(obj)
+	}
+}
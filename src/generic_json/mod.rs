@@ -0,0 +1,128 @@
+//! Generic JSON representation.
+//!
+//! The expansion and context processing algorithms are not bound to a specific JSON
+//! implementation.
+//! Instead, they operate on any type implementing the [`Json`] trait.
+//! It is implemented for [`json::JsonValue`], and for `serde_json::Value` when the
+//! `serde_json` feature is enabled.
+
+use json::number::Number;
+use crate::util;
+
+mod json_value;
+
+#[cfg(feature="serde_json")]
+mod serde_json_value;
+
+/// Reference to a JSON value, used to inspect it.
+pub enum JsonRef<'a, J: Json> {
+	Null,
+	Boolean(bool),
+	Number(Number),
+	String(&'a str),
+	Array(&'a [J]),
+	Object(&'a J::Object)
+}
+
+/// JSON object.
+pub trait JsonObject<J> {
+	/// Number of entries in the object.
+	fn len(&self) -> usize;
+
+	/// Checks if the object has no entries.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Get the value associated to the given key.
+	fn get(&self, key: &str) -> Option<&J>;
+
+	/// Iterate through the entries of the object, in order.
+	fn iter<'a>(&'a self) -> Box<dyn 'a + Send + Iterator<Item = (&'a str, &'a J)>>;
+}
+
+/// JSON value.
+pub trait Json: Sized + Clone + PartialEq + Send + Sync + util::AsJson {
+	/// JSON object type.
+	type Object: JsonObject<Self> + Send + Sync;
+
+	/// Get a reference to the value to inspect it.
+	fn as_json_ref(&self) -> JsonRef<'_, Self>;
+
+	/// Create the `null` value.
+	fn null() -> Self;
+
+	/// Create a boolean value.
+	fn boolean(b: bool) -> Self;
+
+	/// Create a number value.
+	fn number(n: Number) -> Self;
+
+	/// Create a string value.
+	fn string(s: &str) -> Self;
+
+	/// Create an array value.
+	fn array(items: Vec<Self>) -> Self;
+
+	/// Create an object value from its entries.
+	fn object(entries: Vec<(String, Self)>) -> Self;
+
+	/// Convert a value from any other JSON implementation.
+	fn from_json<K: Json>(value: &K) -> Self {
+		match value.as_json_ref() {
+			JsonRef::Null => Self::null(),
+			JsonRef::Boolean(b) => Self::boolean(b),
+			JsonRef::Number(n) => Self::number(n),
+			JsonRef::String(s) => Self::string(s),
+			JsonRef::Array(items) => Self::array(items.iter().map(Self::from_json).collect()),
+			JsonRef::Object(obj) => Self::object(obj.iter().map(|(key, value)| (key.to_string(), Self::from_json(value))).collect())
+		}
+	}
+
+	/// Checks if the value is `null`.
+	fn is_null(&self) -> bool {
+		matches!(self.as_json_ref(), JsonRef::Null)
+	}
+
+	/// Checks if the value is an array.
+	fn is_array(&self) -> bool {
+		matches!(self.as_json_ref(), JsonRef::Array(_))
+	}
+
+	/// Checks if the value is an object.
+	fn is_object(&self) -> bool {
+		matches!(self.as_json_ref(), JsonRef::Object(_))
+	}
+
+	/// Get the value as a boolean, if it is one.
+	fn as_bool(&self) -> Option<bool> {
+		match self.as_json_ref() {
+			JsonRef::Boolean(b) => Some(b),
+			_ => None
+		}
+	}
+
+	/// Get the value as a 32-bit floating point number, if it is a number.
+	fn as_f32(&self) -> Option<f32> {
+		match self.as_json_ref() {
+			JsonRef::Number(n) => Some(n.into()),
+			_ => None
+		}
+	}
+
+	/// Get the value as a string, if it is one.
+	fn as_str(&self) -> Option<&str> {
+		match self.as_json_ref() {
+			JsonRef::String(s) => Some(s),
+			_ => None
+		}
+	}
+
+	/// Get the value as an object, if it is one.
+	fn as_object(&self) -> Option<&Self::Object> {
+		match self.as_json_ref() {
+			JsonRef::Object(obj) => Some(obj),
+			_ => None
+		}
+	}
+}
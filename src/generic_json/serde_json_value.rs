@@ -0,0 +1,149 @@
+use json::{
+	JsonValue,
+	number::Number
+};
+use serde_json::{
+	Value,
+	Map
+};
+use crate::util;
+use super::{
+	Json,
+	JsonObject,
+	JsonRef
+};
+
+impl JsonObject<Value> for Map<String, Value> {
+	fn len(&self) -> usize {
+		self.len()
+	}
+
+	fn get(&self, key: &str) -> Option<&Value> {
+		self.get(key)
+	}
+
+	fn iter<'a>(&'a self) -> Box<dyn 'a + Send + Iterator<Item = (&'a str, &'a Value)>> {
+		Box::new(self.iter().map(|(key, value)| (key.as_str(), value)))
+	}
+}
+
+/// Convert a `serde_json` number into a `json` number.
+///
+/// Integers are converted without loss of precision.
+pub(crate) fn to_number(n: &serde_json::Number) -> Number {
+	if let Some(n) = n.as_u64() {
+		n.into()
+	} else if let Some(n) = n.as_i64() {
+		util::integer_number(n)
+	} else {
+		n.as_f64().unwrap_or(f64::NAN).into()
+	}
+}
+
+/// Convert a `json` number into a `serde_json` value.
+///
+/// Integers fitting in a `u64` or `i64` are converted without loss of precision.
+/// `NaN` is converted into `null` since it cannot be represented in JSON.
+pub(crate) fn from_number(n: Number) -> Value {
+	if n.is_nan() {
+		return Value::Null
+	}
+
+	let (positive, mantissa, exponent) = n.as_parts();
+	if exponent == 0 {
+		if positive {
+			return Value::Number(mantissa.into())
+		} else if mantissa <= i64::MIN.unsigned_abs() {
+			// The wrapping negation maps `2^63` to `i64::MIN`.
+			return Value::Number((mantissa as i64).wrapping_neg().into())
+		}
+	}
+
+	let f: f64 = n.into();
+	match serde_json::Number::from_f64(f) {
+		Some(n) => Value::Number(n),
+		None => Value::Null
+	}
+}
+
+impl Json for Value {
+	type Object = Map<String, Value>;
+
+	fn as_json_ref(&self) -> JsonRef<'_, Value> {
+		match self {
+			Value::Null => JsonRef::Null,
+			Value::Bool(b) => JsonRef::Boolean(*b),
+			Value::Number(n) => JsonRef::Number(to_number(n)),
+			Value::String(s) => JsonRef::String(s.as_str()),
+			Value::Array(items) => JsonRef::Array(items),
+			Value::Object(obj) => JsonRef::Object(obj)
+		}
+	}
+
+	fn null() -> Value {
+		Value::Null
+	}
+
+	fn boolean(b: bool) -> Value {
+		Value::Bool(b)
+	}
+
+	fn number(n: Number) -> Value {
+		from_number(n)
+	}
+
+	fn string(s: &str) -> Value {
+		Value::String(s.to_string())
+	}
+
+	fn array(items: Vec<Value>) -> Value {
+		Value::Array(items)
+	}
+
+	fn object(entries: Vec<(String, Value)>) -> Value {
+		Value::Object(entries.into_iter().collect())
+	}
+}
+
+impl util::AsJson for Value {
+	fn as_json(&self) -> JsonValue {
+		JsonValue::from_json(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn round_trip(value: Value) -> Value {
+		match value {
+			Value::Number(n) => from_number(to_number(&n)),
+			_ => panic!("not a number")
+		}
+	}
+
+	#[test]
+	fn integer_bounds() {
+		for value in &[Value::from(u64::MAX), Value::from(i64::MIN), Value::from(i64::MAX), Value::from(0), Value::from(-1)] {
+			assert_eq!(round_trip(value.clone()), *value)
+		}
+	}
+
+	#[test]
+	fn i64_min() {
+		let n = to_number(&i64::MIN.into());
+		assert_eq!(n.as_parts(), (false, 1u64 << 63, 0))
+	}
+
+	#[test]
+	fn floats() {
+		for value in &[1.5e300, -1.5e300, 0.5, f64::MAX, f64::MIN_POSITIVE] {
+			assert_eq!(round_trip(Value::from(*value)), Value::from(*value))
+		}
+	}
+
+	#[test]
+	fn nan() {
+		assert_eq!(from_number(Number::from(f64::NAN)), Value::Null)
+	}
+}
@@ -124,10 +124,9 @@ impl<T> AsMut<T> for Indexed<T> {
 	}
 }
 
-impl<T: AsJson> AsJson for Indexed<T> {
-	fn as_json(&self) -> JsonValue {
-		let mut json = self.value.as_json();
-
+impl<T> Indexed<T> {
+	/// Add the `@index` entry to the given JSON representation of the inner value.
+	fn with_index_json(&self, mut json: JsonValue) -> JsonValue {
 		if let JsonValue::Object(ref mut obj) = &mut json {
 			if let Some(index) = &self.index {
 				obj.insert(Keyword::Index.into(), index.as_json())
@@ -137,3 +136,13 @@ impl<T: AsJson> AsJson for Indexed<T> {
 		json
 	}
 }
+
+impl<T: AsJson> AsJson for Indexed<T> {
+	fn as_json(&self) -> JsonValue {
+		self.with_index_json(self.value.as_json())
+	}
+
+	fn as_ordered_json(&self) -> JsonValue {
+		self.with_index_json(self.value.as_ordered_json())
+	}
+}
@@ -1,4 +1,10 @@
-use crate::Direction;
+use json::JsonValue;
+use crate::{
+	Direction,
+	LanguageTag,
+	syntax::Keyword,
+	util::AsJson
+};
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct LangString {
@@ -27,6 +33,12 @@ impl LangString {
 		}
 	}
 
+	/// Language tag of the string, if any, that can be checked or parsed according to
+	/// [BCP47](https://tools.ietf.org/html/bcp47).
+	pub fn language_tag(&self) -> Option<LanguageTag<'_>> {
+		self.language().map(LanguageTag::new)
+	}
+
 	pub fn set_language(&mut self, language: Option<String>) {
 		self.language = language
 	}
@@ -39,3 +51,21 @@ impl LangString {
 		self.direction = direction
 	}
 }
+
+impl AsJson for LangString {
+	/// Returns the value object representing the language string.
+	fn as_json(&self) -> JsonValue {
+		let mut obj = json::object::Object::new();
+		obj.insert(Keyword::Value.into(), self.as_str().into());
+
+		if let Some(language) = self.language() {
+			obj.insert(Keyword::Language.into(), language.as_json());
+		}
+
+		if let Some(direction) = self.direction() {
+			obj.insert(Keyword::Direction.into(), direction.as_json());
+		}
+
+		JsonValue::Object(obj)
+	}
+}
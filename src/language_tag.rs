@@ -0,0 +1,362 @@
+use std::fmt;
+use json::JsonValue;
+use crate::util::AsJson;
+
+/// Irregular and regular grandfathered tags.
+///
+/// See section 2.2.8 of [BCP47](https://tools.ietf.org/html/bcp47).
+const GRANDFATHERED: [&str; 26] = [
+	"en-GB-oed",
+	"i-ami",
+	"i-bnn",
+	"i-default",
+	"i-enochian",
+	"i-hak",
+	"i-klingon",
+	"i-lux",
+	"i-mingo",
+	"i-navajo",
+	"i-pwn",
+	"i-tao",
+	"i-tay",
+	"i-tsu",
+	"sgn-BE-FR",
+	"sgn-BE-NL",
+	"sgn-CH-DE",
+	"art-lojban",
+	"cel-gaulish",
+	"no-bok",
+	"no-nyn",
+	"zh-guoyu",
+	"zh-hakka",
+	"zh-min",
+	"zh-min-nan",
+	"zh-xiang"
+];
+
+/// Language tag.
+///
+/// Language tags are found in the `@language` entries of value objects and contexts.
+/// They should be well-formed [BCP47](https://tools.ietf.org/html/bcp47) language tags,
+/// but malformed tags are kept by the JSON-LD algorithms (with a warning), so this type
+/// accepts any string.
+/// Use [`LanguageTag::is_well_formed`] or [`LanguageTag::subtags`] to validate it.
+///
+/// Language tags are case insensitive.
+/// The comparison operators however compare the tags as they are written:
+/// use [`LanguageTag::normalized`] to compare them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct LanguageTag<'a> {
+	data: &'a str
+}
+
+impl<'a> LanguageTag<'a> {
+	/// Wrap the given language tag, without checking that it is well-formed.
+	pub fn new(data: &'a str) -> LanguageTag<'a> {
+		LanguageTag {
+			data
+		}
+	}
+
+	/// Get the language tag as a string, as it is written.
+	pub fn as_str(&self) -> &'a str {
+		self.data
+	}
+
+	/// Parse the language tag according to the syntax of section 2.1 of
+	/// [BCP47](https://tools.ietf.org/html/bcp47#section-2.1).
+	///
+	/// Returns `None` if the tag is not well-formed.
+	/// Only the syntax is checked: subtags are not looked up in the IANA Language Subtag
+	/// Registry.
+	pub fn subtags(&self) -> Option<Subtags<'a>> {
+		parse(self.data)
+	}
+
+	/// Checks if the language tag is well-formed according to section 2.2.9 of
+	/// [BCP47](https://tools.ietf.org/html/bcp47#section-2.2.9).
+	pub fn is_well_formed(&self) -> bool {
+		self.subtags().is_some()
+	}
+
+	/// Checks if the language tag is written in lower case.
+	pub fn is_normalized(&self) -> bool {
+		!self.data.chars().any(|c| c.is_ascii_uppercase())
+	}
+
+	/// Language tag normalized to lower case.
+	///
+	/// The JSON-LD specification allows processors to normalize language tags to lower case.
+	pub fn normalized(&self) -> String {
+		self.data.to_ascii_lowercase()
+	}
+}
+
+impl<'a> PartialEq<str> for LanguageTag<'a> {
+	fn eq(&self, other: &str) -> bool {
+		self.data == other
+	}
+}
+
+impl<'a> fmt::Display for LanguageTag<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.data)
+	}
+}
+
+impl<'a> AsJson for LanguageTag<'a> {
+	fn as_json(&self) -> JsonValue {
+		self.data.into()
+	}
+}
+
+/// Subtags of a well-formed language tag.
+///
+/// Subtags are given as they are written in the tag (they are not normalized).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Subtags<'a> {
+	/// Regular language tag (`langtag` production).
+	Normal {
+		/// Primary language subtag.
+		language: &'a str,
+
+		/// Extended language subtags (up to three, separated by hyphens).
+		extended_language: Option<&'a str>,
+
+		/// Script subtag.
+		script: Option<&'a str>,
+
+		/// Region subtag.
+		region: Option<&'a str>,
+
+		/// Variant subtags.
+		variants: Vec<&'a str>,
+
+		/// Extensions, each one being a singleton followed by its subtags
+		/// (such as `u-co-phonebk`).
+		extensions: Vec<&'a str>,
+
+		/// Private use subtags, without the `x-` prefix.
+		private_use: Option<&'a str>
+	},
+
+	/// Private use tag (starting with `x-`), without the `x-` prefix.
+	PrivateUse(&'a str),
+
+	/// Grandfathered tag.
+	Grandfathered(&'a str)
+}
+
+fn is_alpha(subtag: &str) -> bool {
+	subtag.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+fn is_digit(subtag: &str) -> bool {
+	subtag.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_alphanum(subtag: &str) -> bool {
+	subtag.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Language tag being parsed: the tag and the position of each of its subtags.
+struct Parser<'a> {
+	tag: &'a str,
+	subtags: Vec<(usize, &'a str)>,
+	index: usize
+}
+
+impl<'a> Parser<'a> {
+	fn peek(&self) -> Option<&'a str> {
+		self.subtags.get(self.index).map(|(_, subtag)| *subtag)
+	}
+
+	/// Consume the next subtag if it satisfies the given predicate.
+	fn next_if<F: Fn(&str) -> bool>(&mut self, f: F) -> Option<&'a str> {
+		match self.peek() {
+			Some(subtag) if f(subtag) => {
+				self.index += 1;
+				Some(subtag)
+			},
+			_ => None
+		}
+	}
+
+	/// Part of the tag going from the subtag at index `start` to the last consumed subtag.
+	fn span(&self, start: usize) -> &'a str {
+		let (begin, _) = self.subtags[start];
+		let (end, last) = self.subtags[self.index - 1];
+		&self.tag[begin..(end + last.len())]
+	}
+
+	/// Consume a sequence of subtags satisfying the given predicate, and return its span.
+	fn sequence<F: Fn(&str) -> bool>(&mut self, f: F) -> Option<&'a str> {
+		let start = self.index;
+		while self.next_if(&f).is_some() {}
+
+		if self.index > start {
+			Some(self.span(start))
+		} else {
+			None
+		}
+	}
+
+	fn is_done(&self) -> bool {
+		self.index >= self.subtags.len()
+	}
+}
+
+fn parse(tag: &str) -> Option<Subtags<'_>> {
+	if GRANDFATHERED.iter().any(|grandfathered| grandfathered.eq_ignore_ascii_case(tag)) {
+		return Some(Subtags::Grandfathered(tag))
+	}
+
+	let mut subtags = Vec::new();
+	let mut offset = 0;
+	for subtag in tag.split('-') {
+		if subtag.is_empty() || subtag.len() > 8 || !is_alphanum(subtag) {
+			return None
+		}
+
+		subtags.push((offset, subtag));
+		offset += subtag.len() + 1;
+	}
+
+	let mut parser = Parser {
+		tag,
+		subtags,
+		index: 0
+	};
+
+	// privateuse = "x" 1*("-" (1*8alphanum))
+	if parser.next_if(|s| s.eq_ignore_ascii_case("x")).is_some() {
+		return parser.sequence(|_| true).map(Subtags::PrivateUse)
+	}
+
+	// language = 2*3ALPHA ["-" extlang] / 4ALPHA / 5*8ALPHA
+	let language = parser.next_if(|s| s.len() >= 2 && is_alpha(s))?;
+
+	// extlang = 3ALPHA *2("-" 3ALPHA)
+	let extended_language = if language.len() <= 3 {
+		let start = parser.index;
+		for _ in 0..3 {
+			if parser.next_if(|s| s.len() == 3 && is_alpha(s)).is_none() {
+				break
+			}
+		}
+
+		if parser.index > start {
+			Some(parser.span(start))
+		} else {
+			None
+		}
+	} else {
+		None
+	};
+
+	// script = 4ALPHA
+	let script = parser.next_if(|s| s.len() == 4 && is_alpha(s));
+
+	// region = 2ALPHA / 3DIGIT
+	let region = parser.next_if(|s| (s.len() == 2 && is_alpha(s)) || (s.len() == 3 && is_digit(s)));
+
+	// variant = 5*8alphanum / (DIGIT 3alphanum)
+	let mut variants = Vec::new();
+	while let Some(variant) = parser.next_if(|s| s.len() >= 5 || (s.len() == 4 && s.as_bytes()[0].is_ascii_digit())) {
+		variants.push(variant)
+	}
+
+	// extension = singleton 1*("-" (2*8alphanum))
+	let mut extensions = Vec::new();
+	while let Some(singleton) = parser.peek() {
+		if singleton.len() != 1 || singleton.eq_ignore_ascii_case("x") {
+			break
+		}
+
+		let start = parser.index;
+		parser.index += 1;
+		parser.sequence(|s| s.len() >= 2)?;
+		extensions.push(parser.span(start))
+	}
+
+	// ["-" privateuse]
+	let private_use = if parser.next_if(|s| s.eq_ignore_ascii_case("x")).is_some() {
+		Some(parser.sequence(|_| true)?)
+	} else {
+		None
+	};
+
+	if parser.is_done() {
+		Some(Subtags::Normal {
+			language,
+			extended_language,
+			script,
+			region,
+			variants,
+			extensions,
+			private_use
+		})
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn normal(tag: &str) -> (&str, Option<&str>, Option<&str>, Option<&str>, Vec<&str>, Vec<&str>, Option<&str>) {
+		match parse(tag) {
+			Some(Subtags::Normal { language, extended_language, script, region, variants, extensions, private_use }) => {
+				(language, extended_language, script, region, variants, extensions, private_use)
+			},
+			other => panic!("`{}` parsed as {:?}", tag, other)
+		}
+	}
+
+	#[test]
+	fn simple() {
+		assert_eq!(normal("en"), ("en", None, None, None, vec![], vec![], None));
+		assert_eq!(normal("en-US"), ("en", None, None, Some("US"), vec![], vec![], None));
+		assert_eq!(normal("zh-Hant-TW"), ("zh", None, Some("Hant"), Some("TW"), vec![], vec![], None));
+		assert_eq!(normal("es-419"), ("es", None, None, Some("419"), vec![], vec![], None))
+	}
+
+	#[test]
+	fn grandfathered() {
+		assert_eq!(parse("i-klingon"), Some(Subtags::Grandfathered("i-klingon")));
+		assert_eq!(parse("en-GB-oed"), Some(Subtags::Grandfathered("en-GB-oed")));
+		assert_eq!(parse("ZH-min-NAN"), Some(Subtags::Grandfathered("ZH-min-NAN")))
+	}
+
+	#[test]
+	fn extended_language() {
+		assert_eq!(normal("zh-yue-HK"), ("zh", Some("yue"), None, Some("HK"), vec![], vec![], None));
+		assert_eq!(normal("zh-yue-abc-def"), ("zh", Some("yue-abc-def"), None, None, vec![], vec![], None))
+	}
+
+	#[test]
+	fn variants() {
+		assert_eq!(normal("sl-rozaj-biske"), ("sl", None, None, None, vec!["rozaj", "biske"], vec![], None));
+		assert_eq!(normal("de-CH-1901"), ("de", None, None, Some("CH"), vec!["1901"], vec![], None))
+	}
+
+	#[test]
+	fn extensions() {
+		assert_eq!(normal("de-DE-u-co-phonebk"), ("de", None, None, Some("DE"), vec![], vec!["u-co-phonebk"], None));
+		assert_eq!(normal("en-a-bbb-x-a-ccc"), ("en", None, None, None, vec![], vec!["a-bbb"], Some("a-ccc")))
+	}
+
+	#[test]
+	fn private_use() {
+		assert_eq!(parse("x-whatever"), Some(Subtags::PrivateUse("whatever")));
+		assert_eq!(normal("qaa-Qaaa-QM-x-southern"), ("qaa", None, Some("Qaaa"), Some("QM"), vec![], vec![], Some("southern")))
+	}
+
+	#[test]
+	fn malformed() {
+		for tag in &["", "en--US", "a-DE", "en-", "-en", "en_US", "i-foo", "x", "en-x", "en-a", "en-a-x-b", "ab-abc-abc-abc-abc", "de-419-DE", "toolongtag"] {
+			assert!(parse(tag).is_none(), "`{}` should be malformed", tag)
+		}
+	}
+}
@@ -6,10 +6,14 @@ extern crate log;
 extern crate json;
 extern crate iref;
 
+#[macro_use]
+mod maybe_async;
 mod mode;
 mod error;
+mod warning;
 mod direction;
 mod lang;
+mod language_tag;
 mod id;
 mod blank;
 mod reference;
@@ -22,15 +26,26 @@ pub mod syntax;
 pub mod object;
 pub mod context;
 pub mod expansion;
+pub mod compaction;
+pub mod flattening;
+pub mod framing;
+pub mod rdf;
 pub mod util;
+pub mod generic_json;
+pub mod linked_data;
 
 #[cfg(feature="reqwest-loader")]
 pub mod reqwest;
 
+#[cfg(feature="serde")]
+mod serialization;
+
 pub use mode::*;
 pub use error::*;
+pub use warning::*;
 pub use direction::*;
 pub use lang::*;
+pub use language_tag::*;
 pub use id::*;
 pub use blank::*;
 pub use reference::*;
@@ -41,6 +56,9 @@ pub use document::*;
 pub use loader::*;
 
 pub use object::{Object, Node, Value};
+
+#[cfg(feature="derive")]
+pub use json_ld_derive::LinkedData;
 pub use context::{
 	Context,
 	ContextMut,
@@ -0,0 +1,401 @@
+//! Mapping between Rust types and node objects.
+//!
+//! This module provides the runtime support of the `LinkedData` derive macro
+//! (enabled by the `derive` feature), which generates the conversion of a
+//! struct from (`TryFrom<&Node<T>>`) and into (`Into<Node<T>>`) a node object.
+//! Each field is mapped to a property, and converted from/into its values using the
+//! [`FromObject`] and [`IntoObject`] traits.
+//!
+//! Properties are given by their IRI and looked up using [`Id::from_iri`], so a
+//! [`Lexicon`](crate::Lexicon) identifier type will resolve them into its vocabulary.
+//!
+//! # Example
+//! ```ignore
+//! use std::convert::TryFrom;
+//! use json_ld::{Node, Reference, LinkedData};
+//!
+//! #[derive(LinkedData)]
+//! #[ld(type = "http://schema.org/Person", id_type = "iref::IriBuf")]
+//! struct Person {
+//!     #[ld(id)]
+//!     id: Option<Reference>,
+//!
+//!     #[ld(iri = "http://schema.org/name")]
+//!     name: String,
+//!
+//!     #[ld(iri = "http://schema.org/knows")]
+//!     knows: Vec<Reference>
+//! }
+//!
+//! fn handle_node(node: &Node) -> Result<(), json_ld::linked_data::Error> {
+//!     let person = Person::try_from(node)?;
+//!     println!("person name: {}", person.name);
+//!     let node: Node = person.into();
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The macro fails to compile on structs without named fields:
+//! ```compile_fail
+//! use json_ld::LinkedData;
+//!
+//! #[derive(LinkedData)]
+//! struct Name(#[ld(iri = "http://schema.org/name")] String);
+//! ```
+//!
+//! and on fields without mapping:
+//! ```compile_fail
+//! use json_ld::LinkedData;
+//!
+//! #[derive(LinkedData)]
+//! struct Person {
+//!     name: String
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::collections::HashSet;
+use iref::Iri;
+use crate::{
+	Id,
+	Reference,
+	Lenient,
+	Indexed,
+	LangString,
+	Object,
+	Node,
+	Value,
+	object::Literal
+};
+
+/// Mapping error.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Error {
+	/// The node has no `@id` entry.
+	MissingId,
+
+	/// The `@id` entry of the node is not a valid IRI or blank node identifier.
+	InvalidId(String),
+
+	/// The node does not have the required type.
+	MissingType(String),
+
+	/// The node has no value for a required property.
+	MissingProperty(String),
+
+	/// The node has more than one value for a property expecting a single one.
+	MultipleValues(String),
+
+	/// The value of a property has not the expected type.
+	InvalidValue {
+		/// Property IRI.
+		property: String,
+
+		/// Error found while converting the value.
+		error: Box<Error>
+	},
+
+	/// The object has not the expected form.
+	///
+	/// The payload is a description of the expected object.
+	Mistyped(&'static str)
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::MissingId => write!(f, "missing node identifier"),
+			Error::InvalidId(id) => write!(f, "invalid node identifier `{}`", id),
+			Error::MissingType(ty) => write!(f, "missing node type `{}`", ty),
+			Error::MissingProperty(prop) => write!(f, "missing property `{}`", prop),
+			Error::MultipleValues(prop) => write!(f, "multiple values for property `{}`", prop),
+			Error::InvalidValue { property, error } => write!(f, "invalid value for property `{}`: {}", property, error),
+			Error::Mistyped(expected) => write!(f, "expected {}", expected)
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+/// Type that can be extracted from an object.
+pub trait FromObject<T: Id>: Sized {
+	fn from_object(object: &Object<T>) -> Result<Self, Error>;
+}
+
+/// Type that can be turned into an object.
+pub trait IntoObject<T: Id> {
+	fn into_object(self) -> Object<T>;
+}
+
+impl<T: Id> FromObject<T> for Object<T> {
+	fn from_object(object: &Object<T>) -> Result<Object<T>, Error> {
+		Ok(object.clone())
+	}
+}
+
+impl<T: Id> IntoObject<T> for Object<T> {
+	fn into_object(self) -> Object<T> {
+		self
+	}
+}
+
+impl<T: Id> FromObject<T> for Node<T> {
+	fn from_object(object: &Object<T>) -> Result<Node<T>, Error> {
+		match object {
+			Object::Node(node) => Ok(node.clone()),
+			_ => Err(Error::Mistyped("a node object"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for Node<T> {
+	fn into_object(self) -> Object<T> {
+		Object::Node(self)
+	}
+}
+
+impl<T: Id> FromObject<T> for Reference<T> {
+	fn from_object(object: &Object<T>) -> Result<Reference<T>, Error> {
+		match object {
+			Object::Node(node) => id(node),
+			_ => Err(Error::Mistyped("a node reference"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for Reference<T> {
+	fn into_object(self) -> Object<T> {
+		Object::Node(Node::with_id(Lenient::Ok(self)))
+	}
+}
+
+impl<T: Id> FromObject<T> for Lenient<Reference<T>> {
+	fn from_object(object: &Object<T>) -> Result<Lenient<Reference<T>>, Error> {
+		match object {
+			Object::Node(node) => node.id().cloned().ok_or(Error::MissingId),
+			_ => Err(Error::Mistyped("a node reference"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for Lenient<Reference<T>> {
+	fn into_object(self) -> Object<T> {
+		Object::Node(Node::with_id(self))
+	}
+}
+
+impl<T: Id> FromObject<T> for LangString {
+	fn from_object(object: &Object<T>) -> Result<LangString, Error> {
+		match object {
+			Object::Value(Value::LangString(str)) => Ok(str.clone()),
+			_ => Err(Error::Mistyped("a language string"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for LangString {
+	fn into_object(self) -> Object<T> {
+		Object::Value(Value::LangString(self))
+	}
+}
+
+impl<T: Id> FromObject<T> for String {
+	/// Accepts any string value, with or without language and direction.
+	fn from_object(object: &Object<T>) -> Result<String, Error> {
+		match object {
+			Object::Value(Value::LangString(str)) => Ok(str.as_str().to_string()),
+			Object::Value(Value::Literal(Literal::String(str), _)) => Ok(str.clone()),
+			_ => Err(Error::Mistyped("a string value"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for String {
+	/// Strings are converted into language strings without language nor direction, as the
+	/// expansion algorithm does.
+	fn into_object(self) -> Object<T> {
+		Object::Value(Value::LangString(LangString::new(self, None, None)))
+	}
+}
+
+impl<T: Id> FromObject<T> for bool {
+	fn from_object(object: &Object<T>) -> Result<bool, Error> {
+		match object {
+			Object::Value(Value::Literal(Literal::Boolean(b), _)) => Ok(*b),
+			_ => Err(Error::Mistyped("a boolean value"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for bool {
+	fn into_object(self) -> Object<T> {
+		Object::Value(Value::Literal(Literal::Boolean(self), HashSet::new()))
+	}
+}
+
+macro_rules! integer_mapping {
+	($($ty:ident),*) => {
+		$(
+			impl<T: Id> FromObject<T> for $ty {
+				fn from_object(object: &Object<T>) -> Result<$ty, Error> {
+					if let Object::Value(Value::Literal(Literal::Number(n), _)) = object {
+						let (positive, mantissa, exponent) = n.as_parts();
+						if exponent == 0 {
+							let value = if positive {
+								$ty::try_from(mantissa).ok()
+							} else {
+								i64::try_from(mantissa).ok().and_then(|m| $ty::try_from(-m).ok())
+							};
+
+							if let Some(value) = value {
+								return Ok(value)
+							}
+						}
+					}
+
+					Err(Error::Mistyped(concat!("an integer value fitting in `", stringify!($ty), "`")))
+				}
+			}
+
+			impl<T: Id> IntoObject<T> for $ty {
+				fn into_object(self) -> Object<T> {
+					Object::Value(Value::Literal(Literal::Number(self.into()), HashSet::new()))
+				}
+			}
+		)*
+	};
+}
+
+integer_mapping!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl<T: Id> FromObject<T> for f64 {
+	fn from_object(object: &Object<T>) -> Result<f64, Error> {
+		match object {
+			Object::Value(Value::Literal(Literal::Number(n), _)) => Ok((*n).into()),
+			_ => Err(Error::Mistyped("a number value"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for f64 {
+	fn into_object(self) -> Object<T> {
+		Object::Value(Value::Literal(Literal::Number(self.into()), HashSet::new()))
+	}
+}
+
+impl<T: Id> FromObject<T> for f32 {
+	fn from_object(object: &Object<T>) -> Result<f32, Error> {
+		match object {
+			Object::Value(Value::Literal(Literal::Number(n), _)) => Ok((*n).into()),
+			_ => Err(Error::Mistyped("a number value"))
+		}
+	}
+}
+
+impl<T: Id> IntoObject<T> for f32 {
+	fn into_object(self) -> Object<T> {
+		Object::Value(Value::Literal(Literal::Number(self.into()), HashSet::new()))
+	}
+}
+
+/// Build the reference of the given property.
+fn property<T: Id>(iri: &str) -> Reference<T> {
+	Reference::Id(T::from_iri(Iri::new(iri).expect("invalid property IRI")))
+}
+
+fn from_property_value<T: Id, V: FromObject<T>>(iri: &str, object: &Object<T>) -> Result<V, Error> {
+	V::from_object(object).map_err(|e| Error::InvalidValue {
+		property: iri.to_string(),
+		error: Box::new(e)
+	})
+}
+
+/// Get the identifier of the given node.
+pub fn id<T: Id>(node: &Node<T>) -> Result<Reference<T>, Error> {
+	match optional_id(node)? {
+		Some(id) => Ok(id),
+		None => Err(Error::MissingId)
+	}
+}
+
+/// Get the identifier of the given node, if any.
+pub fn optional_id<T: Id>(node: &Node<T>) -> Result<Option<Reference<T>>, Error> {
+	match node.id() {
+		Some(Lenient::Ok(id)) => Ok(Some(id.clone())),
+		Some(Lenient::Unknown(id)) => Err(Error::InvalidId(id.clone())),
+		None => Ok(None)
+	}
+}
+
+/// Create a new node with the given (optional) identifier.
+pub fn new_node<T: Id>(id: Option<Reference<T>>) -> Node<T> {
+	match id {
+		Some(id) => Node::with_id(Lenient::Ok(id)),
+		None => Node::new()
+	}
+}
+
+/// Checks that the given node has the given type.
+pub fn require_type<T: Id>(node: &Node<T>, iri: &str) -> Result<(), Error> {
+	let ty = property::<T>(iri);
+	if node.types().iter().any(|t| *t == Lenient::Ok(ty.clone())) {
+		Ok(())
+	} else {
+		Err(Error::MissingType(iri.to_string()))
+	}
+}
+
+/// Add a type to the given node.
+pub fn add_type<T: Id>(node: &mut Node<T>, iri: &str) {
+	node.types.push(Lenient::Ok(property(iri)))
+}
+
+/// Get the unique value of a property.
+pub fn get<T: Id, V: FromObject<T>>(node: &Node<T>, iri: &str) -> Result<V, Error> {
+	match get_optional(node, iri)? {
+		Some(value) => Ok(value),
+		None => Err(Error::MissingProperty(iri.to_string()))
+	}
+}
+
+/// Get the value of a property, if any.
+pub fn get_optional<T: Id, V: FromObject<T>>(node: &Node<T>, iri: &str) -> Result<Option<V>, Error> {
+	let prop = property::<T>(iri);
+	let mut objects = node.get(&prop);
+	match objects.next() {
+		Some(object) => {
+			if objects.next().is_some() {
+				return Err(Error::MultipleValues(iri.to_string()))
+			}
+
+			Ok(Some(from_property_value(iri, object)?))
+		},
+		None => Ok(None)
+	}
+}
+
+/// Get all the values of a property.
+pub fn get_all<T: Id, V: FromObject<T>>(node: &Node<T>, iri: &str) -> Result<Vec<V>, Error> {
+	let prop = property::<T>(iri);
+	node.get(&prop).map(|object| from_property_value(iri, object)).collect()
+}
+
+/// Add a value to a property.
+pub fn insert<T: Id, V: IntoObject<T>>(node: &mut Node<T>, iri: &str, value: V) {
+	node.insert(property(iri), Indexed::new(value.into_object(), None))
+}
+
+/// Add a value to a property, if any.
+pub fn insert_optional<T: Id, V: IntoObject<T>>(node: &mut Node<T>, iri: &str, value: Option<V>) {
+	if let Some(value) = value {
+		insert(node, iri, value)
+	}
+}
+
+/// Add all the given values to a property.
+pub fn insert_all<T: Id, V: IntoObject<T>>(node: &mut Node<T>, iri: &str, values: Vec<V>) {
+	if !values.is_empty() {
+		node.insert_all(property(iri), values.into_iter().map(|value| Indexed::new(value.into_object(), None)))
+	}
+}
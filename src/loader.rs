@@ -9,6 +9,10 @@ use crate::{
 	Error,
 	ErrorCode,
 	RemoteDocument,
+	generic_json::{
+		Json,
+		JsonObject
+	},
 	context::{
 		self,
 		RemoteContext
@@ -21,33 +25,57 @@ pub trait Loader {
 	fn load<'a>(&'a mut self, url: Iri<'_>) -> BoxFuture<'a, Result<RemoteDocument<Self::Document>, Error>>;
 }
 
-impl<L: Send + Sync + Loader<Document = JsonValue>> context::Loader for L {
-	type Output = JsonValue;
+/// Synchronous document loader.
+///
+/// Counterpart of [`Loader`] used by the synchronous API
+/// (such as [`Document::expand_sync`](crate::Document::expand_sync)),
+/// for loaders that do not need to wait for any asynchronous operation.
+pub trait SyncLoader {
+	type Document;
 
-	fn load_context<'a>(&'a mut self, url: Iri) -> BoxFuture<'a, Result<RemoteContext<JsonValue>, Error>> {
-		let url = IriBuf::from(url);
-		async move {
-			match self.load(url.as_iri()).await {
-				Ok(remote_doc) => {
-					let (doc, url) = remote_doc.into_parts();
-					if let JsonValue::Object(obj) = doc {
-						if let Some(context) = obj.get("@context") {
-							Ok(RemoteContext::from_parts(url, context.clone()))
-						} else {
-							Err(ErrorCode::InvalidRemoteContext.into())
-						}
-					} else {
-						Err(ErrorCode::InvalidRemoteContext.into())
-					}
-				},
-				Err(_) => {
-					Err(ErrorCode::LoadingRemoteContextFailed.into())
+	fn load(&mut self, url: Iri<'_>) -> Result<RemoteDocument<Self::Document>, Error>;
+}
+
+/// Extract the context of a loaded remote document.
+fn remote_context<J: Json>(remote_doc: Result<RemoteDocument<J>, Error>) -> Result<RemoteContext<J>, Error> {
+	match remote_doc {
+		Ok(remote_doc) => {
+			let (doc, url) = remote_doc.into_parts();
+			if let Some(obj) = doc.as_object() {
+				if let Some(context) = obj.get("@context") {
+					Ok(RemoteContext::from_parts(url, context.clone()))
+				} else {
+					Err(ErrorCode::InvalidRemoteContext.into())
 				}
+			} else {
+				Err(ErrorCode::InvalidRemoteContext.into())
 			}
+		},
+		Err(_) => {
+			Err(ErrorCode::LoadingRemoteContextFailed.into())
+		}
+	}
+}
+
+impl<L: Send + Sync + Loader> context::Loader for L where L::Document: Json {
+	type Output = L::Document;
+
+	fn load_context<'a>(&'a mut self, url: Iri) -> BoxFuture<'a, Result<RemoteContext<L::Document>, Error>> {
+		let url = IriBuf::from(url);
+		async move {
+			remote_context(self.load(url.as_iri()).await)
 		}.boxed()
 	}
 }
 
+impl<L: SyncLoader> context::SyncLoader for L where L::Document: Json {
+	type Output = L::Document;
+
+	fn load_context(&mut self, url: Iri) -> Result<RemoteContext<L::Document>, Error> {
+		remote_context(self.load(url))
+	}
+}
+
 /// Dummy loader.
 ///
 /// A dummy loader that does not load anything.
@@ -66,6 +94,14 @@ impl Loader for NoLoader {
 	}
 }
 
+impl SyncLoader for NoLoader {
+	type Document = JsonValue;
+
+	fn load(&mut self, _url: Iri<'_>) -> Result<RemoteDocument<Self::Document>, Error> {
+		Err(ErrorCode::LoadingDocumentFailed.into())
+	}
+}
+
 /// File-system loader.
 ///
 /// This is a special JSON-LD document loader that can load document from the file system by
@@ -88,49 +124,58 @@ impl FsLoader {
 	}
 }
 
-impl Loader for FsLoader {
+impl SyncLoader for FsLoader {
 	type Document = JsonValue;
 
-	fn load<'a>(&'a mut self, url: Iri<'_>) -> BoxFuture<'a, Result<RemoteDocument<Self::Document>, Error>> {
+	fn load(&mut self, url: Iri<'_>) -> Result<RemoteDocument<Self::Document>, Error> {
 		let url: IriBuf = url.into();
-		async move {
-			match self.cache.get(&url) {
-				Some(doc) => Ok(doc.clone()),
-				None => {
-					for (path, target_url) in &self.mount_points {
-						let url_ref = url.as_iri_ref();
-						match url_ref.suffix(target_url.as_iri_ref()) {
-							Some((suffix, _, _)) => {
-								let mut filepath = path.clone();
-								for seg in suffix.as_path().segments() {
-									filepath.push(seg.as_str())
-								}
+		match self.cache.get(&url) {
+			Some(doc) => Ok(doc.clone()),
+			None => {
+				for (path, target_url) in &self.mount_points {
+					let url_ref = url.as_iri_ref();
+					match url_ref.suffix(target_url.as_iri_ref()) {
+						Some((suffix, _, _)) => {
+							let mut filepath = path.clone();
+							for seg in suffix.as_path().segments() {
+								filepath.push(seg.as_str())
+							}
 
-								if let Ok(file) = File::open(filepath) {
-								    let mut buf_reader = BufReader::new(file);
-								    let mut contents = String::new();
-								    if buf_reader.read_to_string(&mut contents).is_ok() {
-										if let Ok(doc) = json::parse(contents.as_str()) {
-											let remote_doc = RemoteDocument::new(doc, url.as_iri());
-											self.cache.insert(url.clone(), remote_doc.clone());
-											return Ok(remote_doc)
-										} else {
-											return Err(ErrorCode::LoadingDocumentFailed.into())
-										}
+							if let Ok(file) = File::open(filepath) {
+							    let mut buf_reader = BufReader::new(file);
+							    let mut contents = String::new();
+							    if buf_reader.read_to_string(&mut contents).is_ok() {
+									if let Ok(doc) = json::parse(contents.as_str()) {
+										let remote_doc = RemoteDocument::new(doc, url.as_iri());
+										self.cache.insert(url.clone(), remote_doc.clone());
+										return Ok(remote_doc)
 									} else {
 										return Err(ErrorCode::LoadingDocumentFailed.into())
 									}
 								} else {
 									return Err(ErrorCode::LoadingDocumentFailed.into())
 								}
-							},
-							None => ()
-						}
+							} else {
+								return Err(ErrorCode::LoadingDocumentFailed.into())
+							}
+						},
+						None => ()
 					}
-
-					Err(ErrorCode::LoadingDocumentFailed.into())
 				}
+
+				Err(ErrorCode::LoadingDocumentFailed.into())
 			}
+		}
+	}
+}
+
+impl Loader for FsLoader {
+	type Document = JsonValue;
+
+	fn load<'a>(&'a mut self, url: Iri<'_>) -> BoxFuture<'a, Result<RemoteDocument<Self::Document>, Error>> {
+		let url: IriBuf = url.into();
+		async move {
+			SyncLoader::load(self, url.as_iri())
 		}.boxed()
 	}
 }
@@ -0,0 +1,72 @@
+//! Asynchronous and synchronous algorithm definitions.
+//!
+//! Algorithms that may need to load remote documents (context processing and expansion) are
+//! written once, in files that are included (using `include!`) in two modules:
+//! one compiling them as asynchronous functions, using a [`Loader`](crate::Loader),
+//! and the other as synchronous functions, using a [`SyncLoader`](crate::SyncLoader).
+//! The synchronous functions do not involve any future.
+//!
+//! Each module first defines the following macros using `asynchronous_mode!($)` or
+//! `synchronous_mode!($)`, and imports the loader trait under the name `Loader`:
+//!   - `maybe_await!(e)`: awaits the future `e`, or evaluates `e` in synchronous mode;
+//!   - `BoxFuture!('a, T)`: return type of recursive functions, a boxed future of `T`, or `T`
+//!     in synchronous mode;
+//!   - `boxed_async! { ... }`: body of recursive functions returning a `BoxFuture!`;
+//!   - `async_fn! { ... }`: non-recursive `async` function definition, defined without
+//!     `async` in synchronous mode;
+//!   - `process_with!(local_context, ...)`: processes the given local context with
+//!     [`Local::process_with`](crate::context::Local::process_with), or
+//!     [`Local::process_with_sync`](crate::context::Local::process_with_sync) in synchronous
+//!     mode.
+//!
+//! The `$` argument is used to define the nested macro parameters.
+
+/// Define the asynchronous mode macros.
+macro_rules! asynchronous_mode {
+	($d:tt) => {
+		macro_rules! maybe_await {
+			($d e:expr) => { $d e.await }
+		}
+
+		macro_rules! BoxFuture {
+			($d lifetime:lifetime, $d output:ty) => { ::futures::future::BoxFuture<$d lifetime, $d output> }
+		}
+
+		macro_rules! boxed_async {
+			($d ($d body:tt)*) => { ::futures::future::FutureExt::boxed(async move { $d ($d body)* }) }
+		}
+
+		macro_rules! async_fn {
+			($d (#[$d meta:meta])* $d vis:vis async fn $d ($d rest:tt)*) => { $d (#[$d meta])* $d vis async fn $d ($d rest)* }
+		}
+
+		macro_rules! process_with {
+			($d local_context:expr, $d ($d arg:expr),*) => { $d local_context.process_with($d ($d arg),*).await }
+		}
+	};
+}
+
+/// Define the synchronous mode macros.
+macro_rules! synchronous_mode {
+	($d:tt) => {
+		macro_rules! maybe_await {
+			($d e:expr) => { $d e }
+		}
+
+		macro_rules! BoxFuture {
+			($d lifetime:lifetime, $d output:ty) => { $d output }
+		}
+
+		macro_rules! boxed_async {
+			($d ($d body:tt)*) => { { $d ($d body)* } }
+		}
+
+		macro_rules! async_fn {
+			($d (#[$d meta:meta])* $d vis:vis async fn $d ($d rest:tt)*) => { $d (#[$d meta])* $d vis fn $d ($d rest)* }
+		}
+
+		macro_rules! process_with {
+			($d local_context:expr, $d ($d arg:expr),*) => { $d local_context.process_with_sync($d ($d arg),*) }
+		}
+	};
+}
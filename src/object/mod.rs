@@ -3,7 +3,7 @@
 pub mod value;
 pub mod node;
 
-use std::collections::HashSet;
+use indexmap::IndexSet;
 use std::hash::Hash;
 use std::fmt;
 use iref::{Iri, IriBuf};
@@ -23,11 +23,73 @@ pub use value::{
 };
 pub use node::Node;
 
+/// Object reference.
+///
+/// Borrowed view on any kind of object: a value, a node or a list.
+pub enum Ref<'a, T: Id> {
+	/// Value object.
+	Value(&'a Value<T>),
+
+	/// Node object.
+	Node(&'a Node<T>),
+
+	/// List object.
+	List(&'a [Indexed<Object<T>>])
+}
+
+impl<'a, T: Id> Clone for Ref<'a, T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<'a, T: Id> Copy for Ref<'a, T> {}
+
+/// Any object.
+///
+/// This trait is implemented by [`Object`], [`Node`] and [`Value`] so that the algorithms
+/// working on objects in general can also be directly used on nodes and values.
+pub trait Any<T: Id> {
+	/// Get a reference to the object.
+	fn as_ref(&self) -> Ref<'_, T>;
+
+	/// Identifier of the object, if it is a node object.
+	fn id(&self) -> Option<&Lenient<Reference<T>>> {
+		match self.as_ref() {
+			Ref::Node(node) => node.id.as_ref(),
+			_ => None
+		}
+	}
+
+	/// Tests if the object is a value.
+	fn is_value(&self) -> bool {
+		matches!(self.as_ref(), Ref::Value(_))
+	}
+
+	/// Tests if the object is a node.
+	fn is_node(&self) -> bool {
+		matches!(self.as_ref(), Ref::Node(_))
+	}
+
+	/// Tests if the object is a graph object (a node with a `@graph` field).
+	fn is_graph(&self) -> bool {
+		match self.as_ref() {
+			Ref::Node(node) => node.is_graph(),
+			_ => false
+		}
+	}
+
+	/// Tests if the object is a list.
+	fn is_list(&self) -> bool {
+		matches!(self.as_ref(), Ref::List(_))
+	}
+}
+
 /// Object.
 ///
 /// JSON-LD connects together multiple kinds of data objects.
 /// Objects may be nodes, values or lists of objects.
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash, Clone)]
 pub enum Object<T: Id = IriBuf> {
 	/// Value object.
 	Value(Value<T>),
@@ -105,7 +167,7 @@ impl<T: Id> Object<T> {
 	}
 
 	/// Try to convert this object into an unnamed graph.
-	pub fn into_unnamed_graph(self: Indexed<Self>) -> Result<HashSet<Indexed<Object<T>>>, Indexed<Self>> {
+	pub fn into_unnamed_graph(self: Indexed<Self>) -> Result<IndexSet<Indexed<Object<T>>>, Indexed<Self>> {
 		let (obj, index) = self.into_parts();
 		match obj {
 			Object::Node(n) => {
@@ -119,6 +181,16 @@ impl<T: Id> Object<T> {
 	}
 }
 
+impl<T: Id> Any<T> for Object<T> {
+	fn as_ref(&self) -> Ref<'_, T> {
+		match self {
+			Object::Value(value) => Ref::Value(value),
+			Object::Node(node) => Ref::Node(node),
+			Object::List(list) => Ref::List(list.as_ref())
+		}
+	}
+}
+
 impl<T: Id> fmt::Debug for Object<T> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "{}", self.as_json().pretty(2))
@@ -149,4 +221,15 @@ impl<T: Id> AsJson for Object<T> {
 			}
 		}
 	}
+	fn as_ordered_json(&self) -> JsonValue {
+		match self {
+			Object::Value(v) => v.as_ordered_json(),
+			Object::Node(n) => n.as_ordered_json(),
+			Object::List(items) => {
+				let mut obj = json::object::Object::new();
+				obj.insert(Keyword::List.into(), items.as_ordered_json());
+				JsonValue::Object(obj)
+			}
+		}
+	}
 }
@@ -1,7 +1,7 @@
-use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::convert::TryFrom;
 use std::borrow::Borrow;
+use indexmap::{IndexMap, IndexSet};
 use iref::{Iri, IriBuf};
 use json::JsonValue;
 use crate::{
@@ -11,6 +11,11 @@ use crate::{
 	Lenient,
 	Object,
 	Indexed,
+	object::{
+		Any,
+		Ref
+	},
+	framing::FrameEntries,
 	syntax::{
 		Keyword,
 		Term,
@@ -23,7 +28,7 @@ use crate::{
 /// A node is defined by its identifier (`@id` field), types, properties and reverse properties.
 /// In addition, a node may represent a graph (`@graph field`) and includes nodes
 /// (`@included` field).
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone)]
 pub struct Node<T: Id = IriBuf> {
 	/// Identifier.
 	///
@@ -38,22 +43,27 @@ pub struct Node<T: Id = IriBuf> {
 	/// Associated graph.
 	///
 	/// This is the `@graph` field.
-	pub(crate) graph: Option<HashSet<Indexed<Object<T>>>>,
+	pub(crate) graph: Option<Box<IndexSet<Indexed<Object<T>>>>>,
 
 	/// Included nodes.
 	///
 	/// This is the `@included` field.
-	pub(crate) included: Option<HashSet<Indexed<Node<T>>>>,
+	pub(crate) included: Option<Box<IndexSet<Indexed<Node<T>>>>>,
 
 	/// Properties.
 	///
 	/// Any non-keyword field.
-	pub(crate) properties: HashMap<Reference<T>, Vec<Indexed<Object<T>>>>,
+	pub(crate) properties: IndexMap<Reference<T>, Vec<Indexed<Object<T>>>>,
 
 	/// Reverse properties.
 	///
 	/// This is the `@reverse` field.
-	pub(crate) reverse_properties: HashMap<Reference<T>, Vec<Indexed<Node<T>>>>
+	pub(crate) reverse_properties: IndexMap<Reference<T>, Vec<Indexed<Node<T>>>>,
+
+	/// Framing entries.
+	///
+	/// Only set by the expansion algorithm when expanding a frame.
+	pub(crate) frame: Option<Box<FrameEntries<T>>>
 }
 
 /// Iterator through indexed objects.
@@ -78,11 +88,19 @@ impl<T: Id> Node<T> {
 			types: Vec::new(),
 			graph: None,
 			included: None,
-			properties: HashMap::new(),
-			reverse_properties: HashMap::new()
+			properties: IndexMap::new(),
+			reverse_properties: IndexMap::new(),
+			frame: None
 		}
 	}
 
+	/// Create a new empty node with the given identifier.
+	pub fn with_id(id: Lenient<Reference<T>>) -> Node<T> {
+		let mut node = Self::new();
+		node.id = Some(id);
+		node
+	}
+
 	/// Checks if the node object has the given term as key.
 	///
 	/// # Example
@@ -100,6 +118,7 @@ impl<T: Id> Node<T> {
 			Term::Keyword(Keyword::Included) => self.included.is_some(),
 			Term::Keyword(Keyword::Reverse) => !self.reverse_properties.is_empty(),
 			Term::Ref(prop) => self.properties.get(prop).is_some(),
+			Term::Keyword(keyword) => self.frame.as_ref().map(|frame| frame.has_key(*keyword)).unwrap_or(false),
 			_ => false
 		}
 	}
@@ -168,37 +187,47 @@ impl<T: Id> Node<T> {
 	}
 
 	/// If the node is a graph object, get the graph.
-	pub fn graph(&self) -> Option<&HashSet<Indexed<Object<T>>>> {
-		self.graph.as_ref()
+	pub fn graph(&self) -> Option<&IndexSet<Indexed<Object<T>>>> {
+		self.graph.as_deref()
 	}
 
 	/// If the node is a graph object, get the mutable graph.
-	pub fn graph_mut(&mut self) -> Option<&mut HashSet<Indexed<Object<T>>>> {
-		self.graph.as_mut()
+	pub fn graph_mut(&mut self) -> Option<&mut IndexSet<Indexed<Object<T>>>> {
+		self.graph.as_deref_mut()
 	}
 
 	/// Set the graph.
-	pub fn set_graph(&mut self, graph: Option<HashSet<Indexed<Object<T>>>>) {
-		self.graph = graph
+	pub fn set_graph(&mut self, graph: Option<IndexSet<Indexed<Object<T>>>>) {
+		self.graph = graph.map(Box::new)
+	}
+
+	/// Get the framing entries of the node, if it is part of an expanded frame.
+	pub fn frame_entries(&self) -> Option<&FrameEntries<T>> {
+		self.frame.as_ref().map(Box::as_ref)
+	}
+
+	/// Get the framing entries of the node, creating them if necessary.
+	pub fn frame_entries_mut(&mut self) -> &mut FrameEntries<T> {
+		self.frame.get_or_insert_with(|| Box::new(FrameEntries::new()))
 	}
 
 	/// Get the set of nodes included by this node.
 	///
 	/// This correspond to the `@included` field in the JSON representation.
-	pub fn included(&self) -> Option<&HashSet<Indexed<Node<T>>>> {
-		self.included.as_ref()
+	pub fn included(&self) -> Option<&IndexSet<Indexed<Node<T>>>> {
+		self.included.as_deref()
 	}
 
 	/// Get the mutable set of nodes included by this node.
 	///
 	/// This correspond to the `@included` field in the JSON representation.
-	pub fn included_mut(&mut self) -> Option<&mut HashSet<Indexed<Node<T>>>> {
-		self.included.as_mut()
+	pub fn included_mut(&mut self) -> Option<&mut IndexSet<Indexed<Node<T>>>> {
+		self.included.as_deref_mut()
 	}
 
 	/// Set the set of nodes included by the node.
-	pub fn set_included(&mut self, included: Option<HashSet<Indexed<Node<T>>>>) {
-		self.included = included
+	pub fn set_included(&mut self, included: Option<IndexSet<Indexed<Node<T>>>>) {
+		self.included = included.map(Box::new)
 	}
 
 	/// Get all the objects associated to the node with the given property.
@@ -275,15 +304,21 @@ impl<T: Id> Node<T> {
 	///
 	/// The unnamed graph is returned as a set of indexed objects.
 	/// Fails and returns itself if the node is *not* an unnamed graph.
-	pub fn into_unnamed_graph(self) -> Result<HashSet<Indexed<Object<T>>>, Node<T>> {
+	pub fn into_unnamed_graph(self) -> Result<IndexSet<Indexed<Object<T>>>, Node<T>> {
 		if self.is_unnamed_graph() {
-			Ok(self.graph.unwrap())
+			Ok(*self.graph.unwrap())
 		} else {
 			Err(self)
 		}
 	}
 }
 
+impl<T: Id> Any<T> for Node<T> {
+	fn as_ref(&self) -> Ref<'_, T> {
+		Ref::Node(self)
+	}
+}
+
 impl<T: Id> TryFrom<Object<T>> for Node<T> {
 	type Error = Object<T>;
 
@@ -299,15 +334,28 @@ impl<T: Id> Hash for Node<T> {
 	fn hash<H: Hasher>(&self, h: &mut H) {
 		self.id.hash(h);
 		self.types.hash(h);
-		util::hash_set_opt(&self.graph, h);
-		util::hash_set_opt(&self.included, h);
-		util::hash_map(&self.properties, h);
-		util::hash_map(&self.reverse_properties, h);
+		util::hash_index_set_opt(self.graph(), h);
+		util::hash_index_set_opt(self.included(), h);
+		util::hash_index_map(&self.properties, h);
+		util::hash_index_map(&self.reverse_properties, h);
+		self.frame.hash(h);
 	}
 }
 
-impl<T: Id> util::AsJson for Node<T> {
-	fn as_json(&self) -> JsonValue {
+impl<T: Id> Node<T> {
+	/// JSON representation of the node.
+	///
+	/// If `ordered` is true, properties are sorted by IRI and sub-objects are serialized
+	/// using `as_ordered_json`.
+	fn json(&self, ordered: bool) -> JsonValue {
+		use util::AsJson;
+
+		let as_json = |value: &dyn util::AsJson| if ordered {
+			value.as_ordered_json()
+		} else {
+			value.as_json()
+		};
+
 		let mut obj = json::object::Object::new();
 
 		if let Some(id) = &self.id {
@@ -318,27 +366,150 @@ impl<T: Id> util::AsJson for Node<T> {
 			obj.insert(Keyword::Type.into(), self.types.as_json())
 		}
 
-		if let Some(graph) = &self.graph {
-			obj.insert(Keyword::Graph.into(), graph.as_json())
+		if let Some(graph) = self.graph() {
+			obj.insert(Keyword::Graph.into(), as_json(graph))
 		}
 
-		if let Some(included) = &self.included {
-			obj.insert(Keyword::Included.into(), included.as_json())
+		if let Some(included) = self.included() {
+			obj.insert(Keyword::Included.into(), as_json(included))
 		}
 
 		if !self.reverse_properties.is_empty() {
+			let mut reverse_properties: Vec<_> = self.reverse_properties.iter().collect();
+			if ordered {
+				reverse_properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()))
+			}
+
 			let mut reverse = json::object::Object::new();
-			for (key, value) in &self.reverse_properties {
-				reverse.insert(key.as_str(), value.as_json())
+			for (key, value) in reverse_properties {
+				reverse.insert(key.as_str(), as_json(value))
 			}
 
 			obj.insert(Keyword::Reverse.into(), JsonValue::Object(reverse))
 		}
 
-		for (key, value) in &self.properties {
-			obj.insert(key.as_str(), value.as_json())
+		let mut properties: Vec<_> = self.properties.iter().collect();
+		if ordered {
+			properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()))
+		}
+
+		for (key, value) in properties {
+			obj.insert(key.as_str(), as_json(value))
+		}
+
+		if let Some(frame) = &self.frame {
+			frame.insert_json(&mut obj)
 		}
 
 		JsonValue::Object(obj)
 	}
 }
+
+impl<T: Id> util::AsJson for Node<T> {
+	fn as_json(&self) -> JsonValue {
+		self.json(false)
+	}
+
+	fn as_ordered_json(&self) -> JsonValue {
+		self.json(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{HashSet, hash_map::DefaultHasher};
+	use futures::executor::block_on;
+	use crate::{
+		Document,
+		NoLoader,
+		expansion,
+		context::JsonContext,
+		object::{
+			Value,
+			Literal
+		},
+		util::AsJson
+	};
+	use super::*;
+
+	fn expand(doc: &str) -> HashSet<Indexed<Object>> {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		block_on(doc.expand_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap()
+	}
+
+	fn hash<T: Hash>(value: &T) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		value.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	#[test]
+	fn ordered_json_is_stable() {
+		let a = expand(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@graph": [
+				{
+					"@id": "http://example.org/a",
+					"name": "a",
+					"age": 1,
+					"@reverse": {"knows": {"@id": "http://example.org/c"}, "likes": {"@id": "http://example.org/d"}},
+					"@included": [{"@id": "http://example.org/e"}, {"@id": "http://example.org/f", "name": "f"}]
+				},
+				{"@id": "http://example.org/g", "@graph": [{"@id": "http://example.org/h", "name": "h"}, {"@id": "http://example.org/i", "name": "i"}]}
+			]
+		}"#);
+
+		let b = expand(r#"{
+			"@context": {"@vocab": "http://example.org/"},
+			"@graph": [
+				{"@graph": [{"name": "i", "@id": "http://example.org/i"}, {"name": "h", "@id": "http://example.org/h"}], "@id": "http://example.org/g"},
+				{
+					"@included": [{"name": "f", "@id": "http://example.org/f"}, {"@id": "http://example.org/e"}],
+					"@reverse": {"likes": {"@id": "http://example.org/d"}, "knows": {"@id": "http://example.org/c"}},
+					"age": 1,
+					"name": "a",
+					"@id": "http://example.org/a"
+				}
+			]
+		}"#);
+
+		assert!(a == b);
+		assert_eq!(a.as_ordered_json().dump(), b.as_ordered_json().dump());
+
+		// Nodes built in different insertion orders.
+		let name = Reference::Id(IriBuf::new("http://example.org/name").unwrap());
+		let age = Reference::Id(IriBuf::new("http://example.org/age").unwrap());
+		let value = |n: i32| Indexed::new(Object::Value(Value::Literal(Literal::Number(n.into()), HashSet::new())), None);
+
+		let mut c: Node = Node::new();
+		c.insert(name.clone(), value(1));
+		c.insert(age.clone(), value(2));
+
+		let mut d: Node = Node::new();
+		d.insert(age, value(2));
+		d.insert(name, value(1));
+
+		assert!(c == d);
+		assert_eq!(hash(&c), hash(&d));
+		assert_eq!(c.as_ordered_json().dump(), d.as_ordered_json().dump())
+	}
+
+	#[test]
+	fn frame_entries_eq_and_hash() {
+		let mut a: Node = Node::with_id(Lenient::Ok(Reference::Id(IriBuf::new("http://example.org/a").unwrap())));
+		let mut b = a.clone();
+		assert!(a == b);
+		assert_eq!(hash(&a), hash(&b));
+
+		// Nodes differing by their framing entries are distinct, in a set too.
+		b.frame_entries_mut().explicit = Some(true);
+		assert!(a != b);
+		assert_ne!(hash(&a), hash(&b));
+		assert_eq!(vec![a.clone(), b.clone()].into_iter().collect::<HashSet<_>>().len(), 2);
+
+		a.frame_entries_mut().explicit = Some(true);
+		assert!(a == b);
+		assert_eq!(hash(&a), hash(&b))
+	}
+}
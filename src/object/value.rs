@@ -1,11 +1,21 @@
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
+use std::convert::TryFrom;
 use iref::IriBuf;
 use json::JsonValue;
 use crate::{
 	Id,
 	LangString,
+	object::{
+		Any,
+		Ref
+	},
 	syntax::Keyword,
+	rdf::{
+		XSD_BOOLEAN,
+		XSD_INTEGER,
+		XSD_DOUBLE
+	},
 	util
 };
 
@@ -38,6 +48,7 @@ impl PartialEq for Literal {
 				a.as_parts() == b.as_parts()
 			},
 			(String(a), String(b)) => a == b,
+			(Json(a), Json(b)) => util::canonical_json(a) == util::canonical_json(b),
 			_ => false
 		}
 	}
@@ -52,7 +63,7 @@ impl Hash for Literal {
 			Literal::Boolean(b) => b.hash(h),
 			Literal::Number(n) => util::hash_json_number(n, h),
 			Literal::String(s) => s.hash(h),
-			Literal::Json(value) => util::hash_json(value, h)
+			Literal::Json(value) => util::canonical_json(value).hash(h)
 		}
 	}
 }
@@ -66,6 +77,18 @@ impl Literal {
 	}
 }
 
+impl util::AsJson for Literal {
+	fn as_json(&self) -> JsonValue {
+		match self {
+			Literal::Null => JsonValue::Null,
+			Literal::Boolean(b) => b.as_json(),
+			Literal::Number(n) => JsonValue::Number(*n),
+			Literal::String(s) => s.as_json(),
+			Literal::Json(json) => json.clone()
+		}
+	}
+}
+
 /// Value object.
 ///
 /// Either a typed literal value, or an internationalized language string.
@@ -97,6 +120,136 @@ impl<T: Id> Value<T> {
 			Value::LangString(str) => Some(str.as_str())
 		}
 	}
+
+	/// Get the datatype of the value (its `@type`), if any.
+	///
+	/// Language strings and JSON literals (of type `@json`) have no datatype.
+	/// Returns `None` if the value has more than one type, since the datatype is then
+	/// ambiguous.
+	pub fn datatype(&self) -> Option<&T> {
+		match self {
+			Value::Literal(_, tys) if tys.len() == 1 => tys.iter().next(),
+			_ => None
+		}
+	}
+
+	/// Checks if the value has the given datatype.
+	fn has_datatype(&self, iri: &str) -> bool {
+		matches!(self.datatype(), Some(ty) if ty.as_iri() == iri)
+	}
+
+	/// Get the lexical form of a string literal with the given datatype.
+	fn typed_str(&self, iri: &str) -> Option<&str> {
+		match self {
+			Value::Literal(Literal::String(s), _) if self.has_datatype(iri) => Some(s.trim()),
+			_ => None
+		}
+	}
+
+	/// Get the value as a boolean.
+	///
+	/// This is either a native boolean, or a string typed as `xsd:boolean`.
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Value::Literal(Literal::Boolean(b), _) => Some(*b),
+			_ => match self.typed_str(XSD_BOOLEAN)? {
+				"true" | "1" => Some(true),
+				"false" | "0" => Some(false),
+				_ => None
+			}
+		}
+	}
+
+	/// Get the value as a 64-bit integer.
+	///
+	/// This is either a native number without fractional part, or a string typed as
+	/// `xsd:integer`.
+	/// Returns `None` if the integer does not fit in an `i64`.
+	pub fn as_i64(&self) -> Option<i64> {
+		match self {
+			Value::Literal(Literal::Number(n), _) => number_as_i64(n),
+			_ => self.typed_str(XSD_INTEGER)?.parse().ok()
+		}
+	}
+
+	/// Get the value as a 64-bit floating point number.
+	///
+	/// This is either a native number, or a string typed as `xsd:double` or `xsd:integer`.
+	pub fn as_f64(&self) -> Option<f64> {
+		match self {
+			Value::Literal(Literal::Number(n), _) => Some((*n).into()),
+			_ => match self.typed_str(XSD_DOUBLE) {
+				Some(s) => parse_double(s),
+				None => self.typed_str(XSD_INTEGER)?.parse().ok()
+			}
+		}
+	}
+
+	/// Get the value as a date and time with a timezone offset.
+	///
+	/// This is a string typed as `xsd:dateTime`.
+	/// Returns `None` if the timezone is not specified, see
+	/// [`as_naive_datetime`](Value::as_naive_datetime) in this case.
+	#[cfg(feature="chrono")]
+	pub fn as_datetime(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+		chrono::DateTime::parse_from_rfc3339(self.typed_str(crate::rdf::XSD_DATE_TIME)?).ok()
+	}
+
+	/// Get the value as a date and time, ignoring its timezone offset if any.
+	///
+	/// This is a string typed as `xsd:dateTime`.
+	#[cfg(feature="chrono")]
+	pub fn as_naive_datetime(&self) -> Option<chrono::NaiveDateTime> {
+		let s = self.typed_str(crate::rdf::XSD_DATE_TIME)?;
+		match chrono::DateTime::parse_from_rfc3339(s) {
+			Ok(datetime) => Some(datetime.naive_local()),
+			Err(_) => s.parse().ok()
+		}
+	}
+}
+
+/// Convert a JSON number into an `i64`, if it is an integer that fits.
+fn number_as_i64(n: &json::number::Number) -> Option<i64> {
+	let (positive, mut mantissa, mut exponent) = n.as_parts();
+
+	while exponent > 0 {
+		mantissa = mantissa.checked_mul(10)?;
+		exponent -= 1
+	}
+
+	while exponent < 0 {
+		if mantissa % 10 != 0 {
+			return None
+		}
+
+		mantissa /= 10;
+		exponent += 1
+	}
+
+	if positive {
+		i64::try_from(mantissa).ok()
+	} else {
+		0i64.checked_sub_unsigned(mantissa)
+	}
+}
+
+/// Parse a `xsd:double` lexical form.
+///
+/// Special values are written `INF`, `-INF` and `NaN` in XSD.
+fn parse_double(s: &str) -> Option<f64> {
+	match s {
+		"INF" | "+INF" => Some(f64::INFINITY),
+		"-INF" => Some(f64::NEG_INFINITY),
+		"NaN" => Some(f64::NAN),
+		_ if s.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')) => s.parse().ok(),
+		_ => None
+	}
+}
+
+impl<T: Id> Any<T> for Value<T> {
+	fn as_ref(&self) -> Ref<'_, T> {
+		Ref::Value(self)
+	}
 }
 
 impl<T: Id> util::AsJson for Value<T> {
@@ -151,15 +304,7 @@ impl<T: Id> util::AsJson for Value<T> {
 				}
 			},
 			Value::LangString(str) => {
-				obj.insert(Keyword::Value.into(), str.as_str().into());
-
-				if let Some(language) = str.language() {
-					obj.insert(Keyword::Language.into(), language.as_json());
-				}
-
-				if let Some(direction) = str.direction() {
-					obj.insert(Keyword::Direction.into(), direction.as_json());
-				}
+				return str.as_json()
 			}
 		}
 
@@ -167,3 +312,97 @@ impl<T: Id> util::AsJson for Value<T> {
 	}
 
 }
+
+#[cfg(test)]
+mod tests {
+	use iref::Iri;
+	use super::*;
+
+	fn typed(value: &str, ty: &str) -> Value {
+		let mut types = HashSet::new();
+		types.insert(IriBuf::from(Iri::new(ty).unwrap()));
+		Value::Literal(Literal::String(value.to_string()), types)
+	}
+
+	fn number(n: json::number::Number) -> Value {
+		Value::Literal(Literal::Number(n), HashSet::new())
+	}
+
+	#[test]
+	fn datatype() {
+		assert_eq!(typed("1", XSD_INTEGER).datatype().map(|ty| ty.as_str()), Some(XSD_INTEGER));
+		assert!(number(1.into()).datatype().is_none());
+		assert!(Value::<IriBuf>::LangString(LangString::new("a".to_string(), None, None)).datatype().is_none());
+
+		// The datatype of a value with several types is ambiguous.
+		let mut value = typed("1", XSD_INTEGER);
+		if let Value::Literal(_, types) = &mut value {
+			types.insert(IriBuf::from(Iri::new(XSD_DOUBLE).unwrap()));
+		}
+
+		assert!(value.datatype().is_none());
+		assert_eq!(value.as_i64(), None);
+		assert_eq!(value.as_f64(), None)
+	}
+
+	#[test]
+	fn as_bool() {
+		assert_eq!(Value::<IriBuf>::Literal(Literal::Boolean(true), HashSet::new()).as_bool(), Some(true));
+		assert_eq!(typed("true", XSD_BOOLEAN).as_bool(), Some(true));
+		assert_eq!(typed(" 0 ", XSD_BOOLEAN).as_bool(), Some(false));
+		assert_eq!(typed("yes", XSD_BOOLEAN).as_bool(), None);
+		assert_eq!(typed("true", XSD_INTEGER).as_bool(), None);
+		assert_eq!(Value::<IriBuf>::LangString(LangString::new("true".to_string(), None, None)).as_bool(), None)
+	}
+
+	#[test]
+	fn as_i64() {
+		assert_eq!(number(42.into()).as_i64(), Some(42));
+		assert_eq!(number(json::number::Number::from_parts(true, 15, 1)).as_i64(), Some(150));
+		assert_eq!(number(json::number::Number::from_parts(true, 150, -1)).as_i64(), Some(15));
+		assert_eq!(number(1.5.into()).as_i64(), None);
+		assert_eq!(number(json::number::Number::from_parts(false, 1 << 63, 0)).as_i64(), Some(i64::MIN));
+		assert_eq!(typed("-42", XSD_INTEGER).as_i64(), Some(-42));
+		assert_eq!(typed("1.0", XSD_INTEGER).as_i64(), None);
+
+		// Overflows.
+		assert_eq!(number((i64::MAX as u64 + 1).into()).as_i64(), None);
+		assert_eq!(number(json::number::Number::from_parts(true, u64::MAX, 1)).as_i64(), None);
+		assert_eq!(typed("9223372036854775807", XSD_INTEGER).as_i64(), Some(i64::MAX));
+		assert_eq!(typed("9223372036854775808", XSD_INTEGER).as_i64(), None)
+	}
+
+	#[test]
+	fn as_f64() {
+		assert_eq!(number(1.5.into()).as_f64(), Some(1.5));
+		assert_eq!(typed("1.5E2", XSD_DOUBLE).as_f64(), Some(150.0));
+		assert_eq!(typed("42", XSD_INTEGER).as_f64(), Some(42.0));
+		assert_eq!(typed("INF", XSD_DOUBLE).as_f64(), Some(f64::INFINITY));
+		assert_eq!(typed("-INF", XSD_DOUBLE).as_f64(), Some(f64::NEG_INFINITY));
+		assert!(typed("NaN", XSD_DOUBLE).as_f64().unwrap().is_nan());
+
+		// Rust spellings of the special values are not valid XSD.
+		assert_eq!(typed("inf", XSD_DOUBLE).as_f64(), None);
+		assert_eq!(typed("infinity", XSD_DOUBLE).as_f64(), None);
+		assert_eq!(typed("nan", XSD_DOUBLE).as_f64(), None)
+	}
+
+	#[cfg(feature="chrono")]
+	#[test]
+	fn as_datetime() {
+		use crate::rdf::XSD_DATE_TIME;
+
+		let value = typed("2020-06-01T12:30:00+02:00", XSD_DATE_TIME);
+		let datetime = value.as_datetime().unwrap();
+		assert_eq!(datetime.to_rfc3339(), "2020-06-01T12:30:00+02:00");
+		assert_eq!(value.as_naive_datetime().unwrap().to_string(), "2020-06-01 12:30:00");
+
+		// Without timezone, only the naive date and time is available.
+		let value = typed("2020-06-01T12:30:00", XSD_DATE_TIME);
+		assert!(value.as_datetime().is_none());
+		assert_eq!(value.as_naive_datetime().unwrap().to_string(), "2020-06-01 12:30:00");
+
+		assert!(typed("2020-06-01", XSD_DATE_TIME).as_datetime().is_none());
+		assert!(typed("2020-06-01T12:30:00Z", XSD_INTEGER).as_datetime().is_none())
+	}
+}
@@ -0,0 +1,640 @@
+//! RDF dataset canonicalization algorithm (URDNA2015).
+//!
+//! Computes canonical blank node labels for an RDF dataset, so that two isomorphic datasets
+//! are serialized into the exact same canonical N-Quads document.
+//! See <https://www.w3.org/TR/rdf-canon/>.
+
+use std::cell::Cell;
+use std::collections::{
+	HashMap,
+	BTreeMap
+};
+use sha2::{
+	Sha256,
+	Digest
+};
+use crate::{
+	Error,
+	ErrorCode,
+	Id,
+	BlankId,
+	BlankIdGenerator,
+	BlankIdCounter,
+	Reference,
+	ExpandedDocument
+};
+use super::{
+	Term,
+	Quad,
+	Dataset,
+	Options,
+	to_rdf,
+	nquads::NQuads
+};
+
+/// Identifier issuer.
+///
+/// Issues new blank node identifiers of the form `_:{prefix}{n}`, keeping track of the
+/// order in which existing identifiers have been issued a new one.
+#[derive(Clone)]
+struct IdentifierIssuer {
+	generator: BlankIdCounter,
+	issued: HashMap<BlankId, BlankId>,
+	order: Vec<BlankId>
+}
+
+impl IdentifierIssuer {
+	fn new(prefix: &str) -> IdentifierIssuer {
+		IdentifierIssuer {
+			generator: BlankIdCounter::with_prefix(prefix),
+			issued: HashMap::new(),
+			order: Vec::new()
+		}
+	}
+
+	fn get(&self, id: &BlankId) -> Option<&BlankId> {
+		self.issued.get(id)
+	}
+
+	fn contains(&self, id: &BlankId) -> bool {
+		self.issued.contains_key(id)
+	}
+
+	/// Issue an identifier for the given existing identifier.
+	///
+	/// If an identifier has already been issued for `id`, it is reused.
+	fn issue(&mut self, id: &BlankId) -> BlankId {
+		if let Some(issued) = self.issued.get(id) {
+			return issued.clone()
+		}
+
+		let issued = self.generator.generate();
+		self.issued.insert(id.clone(), issued.clone());
+		self.order.push(id.clone());
+		issued
+	}
+}
+
+/// Hexadecimal SHA-256 digest of the given string.
+fn hash(data: &str) -> String {
+	format!("{:x}", Sha256::digest(data.as_bytes()))
+}
+
+/// Get the blank node identifier of a reference, if any.
+fn blank<T: Id>(r: &Reference<T>) -> Option<&BlankId> {
+	match r {
+		Reference::Blank(id) => Some(id),
+		Reference::Id(_) => None
+	}
+}
+
+/// Get the blank node identifier of a term, if any.
+fn blank_term<T: Id>(term: &Term<T>) -> Option<&BlankId> {
+	term.as_reference().and_then(blank)
+}
+
+/// Apply the given function to every blank node identifier appearing in the subject, object
+/// or graph name of a quad.
+fn map_blank_ids<T: Id, F: Fn(&BlankId) -> BlankId>(quad: &Quad<T>, f: F) -> Quad<T> {
+	let map_reference = |r: &Reference<T>| match r {
+		Reference::Blank(id) => Reference::Blank(f(id)),
+		r => r.clone()
+	};
+
+	Quad {
+		subject: map_reference(&quad.subject),
+		predicate: quad.predicate.clone(),
+		object: match &quad.object {
+			Term::Ref(r) => Term::Ref(map_reference(r)),
+			object => object.clone()
+		},
+		graph: quad.graph.as_ref().map(map_reference)
+	}
+}
+
+/// Limits on the work done by the Hash N-Degree Quads algorithm.
+///
+/// The complexity of the algorithm is factorial in the number of related blank nodes sharing
+/// the same hash, so a crafted ("poison") dataset can make it run virtually forever.
+/// Canonicalization fails with a
+/// [`CanonicalizationLimitExceeded`](ErrorCode::CanonicalizationLimitExceeded) error when one
+/// of these limits is exceeded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Limits {
+	/// Maximum number of calls to the Hash N-Degree Quads algorithm.
+	pub max_calls: usize,
+
+	/// Maximum recursion depth of the Hash N-Degree Quads algorithm.
+	pub max_depth: usize,
+
+	/// Maximum number of related blank node permutations explored, in total.
+	pub max_permutations: usize
+}
+
+impl Default for Limits {
+	fn default() -> Limits {
+		Limits {
+			max_calls: 4096,
+			max_depth: 64,
+			max_permutations: 1 << 20
+		}
+	}
+}
+
+/// Canonicalization state.
+struct State<'a, T: Id> {
+	/// Blank node to quads map.
+	blank_node_to_quads: HashMap<BlankId, Vec<&'a Quad<T>>>,
+
+	/// Canonical issuer, issuing identifiers of the form `_:c14n{n}`.
+	canonical_issuer: IdentifierIssuer,
+
+	limits: Limits,
+
+	/// Number of calls to the Hash N-Degree Quads algorithm so far.
+	calls: Cell<usize>,
+
+	/// Number of permutations explored so far.
+	permutations: Cell<usize>
+}
+
+/// Position of a related blank node in a quad.
+#[derive(Clone, Copy)]
+enum Position {
+	Subject,
+	Object,
+	Graph
+}
+
+impl Position {
+	fn as_str(&self) -> &'static str {
+		match self {
+			Position::Subject => "s",
+			Position::Object => "o",
+			Position::Graph => "g"
+		}
+	}
+}
+
+impl<'a, T: Id> State<'a, T> {
+	fn new(dataset: &'a Dataset<T>, limits: Limits) -> State<'a, T> {
+		let mut blank_node_to_quads: HashMap<BlankId, Vec<&'a Quad<T>>> = HashMap::new();
+
+		// For each quad in input dataset, for each blank node that is a component of quad,
+		// add quad to the quads list of the blank node in the blank node to quads map.
+		for quad in dataset {
+			let components = [
+				blank(&quad.subject),
+				blank_term(&quad.object),
+				quad.graph.as_ref().and_then(blank)
+			];
+
+			for id in components.iter().flatten() {
+				let quads = blank_node_to_quads.entry((*id).clone()).or_default();
+				if !quads.iter().any(|q| std::ptr::eq(*q, quad)) {
+					quads.push(quad)
+				}
+			}
+		}
+
+		State {
+			blank_node_to_quads,
+			canonical_issuer: IdentifierIssuer::new("c14n"),
+			limits,
+			calls: Cell::new(0),
+			permutations: Cell::new(0)
+		}
+	}
+
+	/// Hash First Degree Quads algorithm.
+	fn hash_first_degree_quads(&self, reference: &BlankId) -> String {
+		// Initialize nquads to an empty list.
+		let mut nquads = Vec::new();
+
+		// For each quad in the quads of the reference blank node, serialize the quad in
+		// canonical N-Quads form, replacing the reference blank node identifier by `_:a` and
+		// every other blank node identifier by `_:z`.
+		if let Some(quads) = self.blank_node_to_quads.get(reference) {
+			for quad in quads {
+				let quad = map_blank_ids(quad, |id| {
+					if id == reference {
+						BlankId::new("a")
+					} else {
+						BlankId::new("z")
+					}
+				});
+
+				nquads.push(format!("{}\n", NQuads(&quad)))
+			}
+		}
+
+		// Sort nquads in Unicode code point order and return the hash of their
+		// concatenation.
+		nquads.sort();
+		hash(&nquads.concat())
+	}
+
+	/// Hash Related Blank Node algorithm.
+	fn hash_related_blank_node(&self, related: &BlankId, quad: &Quad<T>, issuer: &IdentifierIssuer, position: Position) -> String {
+		// Set the identifier to use for related, preferring first the canonical identifier
+		// for related if issued, second the identifier issued by issuer if issued, and
+		// last, if necessary, the result of the Hash First Degree Quads algorithm.
+		let identifier = match self.canonical_issuer.get(related).or_else(|| issuer.get(related)) {
+			Some(id) => id.as_str().to_string(),
+			None => self.hash_first_degree_quads(related)
+		};
+
+		// Initialize a string input to the value of position.
+		let mut input = position.as_str().to_string();
+
+		// If position is not g, append `<`, the value of the predicate in quad, and `>`.
+		match position {
+			Position::Graph => (),
+			_ => input += &NQuads(&quad.predicate).to_string()
+		}
+
+		// Append identifier to input and return the hash.
+		input += &identifier;
+		hash(&input)
+	}
+
+	/// Hash N-Degree Quads algorithm.
+	///
+	/// The `depth` is the current recursion depth, starting at 0.
+	fn hash_n_degree_quads(&self, identifier: &BlankId, mut issuer: IdentifierIssuer, depth: usize) -> Result<(String, IdentifierIssuer), Error> {
+		self.calls.set(self.calls.get() + 1);
+		if self.calls.get() > self.limits.max_calls || depth > self.limits.max_depth {
+			return Err(ErrorCode::CanonicalizationLimitExceeded.into())
+		}
+
+		// Create a hash to related blank nodes map for storing hashes that identify related
+		// blank nodes.
+		let mut hash_to_related: BTreeMap<String, Vec<BlankId>> = BTreeMap::new();
+
+		// For each quad in the quads of identifier, for each component that is a blank node
+		// other than identifier, add the related blank node to the map under its hash.
+		if let Some(quads) = self.blank_node_to_quads.get(identifier) {
+			for quad in quads {
+				let components = [
+					(blank(&quad.subject), Position::Subject),
+					(blank_term(&quad.object), Position::Object),
+					(quad.graph.as_ref().and_then(blank), Position::Graph)
+				];
+
+				for (related, position) in components.iter() {
+					if let Some(related) = related {
+						if *related != identifier {
+							let hash = self.hash_related_blank_node(related, quad, &issuer, *position);
+							hash_to_related.entry(hash).or_default().push((*related).clone())
+						}
+					}
+				}
+			}
+		}
+
+		// Create an empty string, data to hash.
+		let mut data_to_hash = String::new();
+
+		// For each related hash to blank node list mapping, code point ordered by related
+		// hash:
+		for (related_hash, blank_nodes) in hash_to_related {
+			// Append the related hash to the data to hash.
+			data_to_hash += &related_hash;
+
+			// Create a string chosen path, and chosen issuer.
+			let mut chosen_path = String::new();
+			let mut chosen_issuer = None;
+
+			// For each permutation of blank node list:
+			for permutation in Permutations::new(&blank_nodes) {
+				self.permutations.set(self.permutations.get() + 1);
+				if self.permutations.get() > self.limits.max_permutations {
+					return Err(ErrorCode::CanonicalizationLimitExceeded.into())
+				}
+
+				// Create a copy of issuer, path and recursion list.
+				let mut issuer_copy = issuer.clone();
+				let mut path = String::new();
+				let mut recursion_list = Vec::new();
+
+				let mut skip = false;
+
+				// For each related in permutation:
+				for related in permutation {
+					// If a canonical identifier has been issued for related, append it to
+					// path.
+					if let Some(id) = self.canonical_issuer.get(related) {
+						path += id.as_str()
+					} else {
+						// Otherwise, if no identifier has been issued for related by issuer
+						// copy, append related to recursion list.
+						if !issuer_copy.contains(related) {
+							recursion_list.push(related.clone())
+						}
+
+						// Use the identifier issued by issuer copy.
+						path += issuer_copy.issue(related).as_str()
+					}
+
+					// If chosen path is not empty and the length of path is greater than or
+					// equal to the length of chosen path and path is lexicographically
+					// greater than chosen path, then skip to the next permutation.
+					if !chosen_path.is_empty() && path.len() >= chosen_path.len() && path > chosen_path {
+						skip = true;
+						break
+					}
+				}
+
+				if skip {
+					continue
+				}
+
+				// For each related in recursion list:
+				for related in recursion_list {
+					// Set result to the result of recursively executing the Hash N-Degree
+					// Quads algorithm, passing related for identifier and issuer copy for
+					// path identifier issuer.
+					let (result_hash, result_issuer) = self.hash_n_degree_quads(&related, issuer_copy.clone(), depth + 1)?;
+
+					// Use the identifier issued by issuer copy for related, then append
+					// `<`, the hash of the result and `>` to path.
+					path += issuer_copy.issue(&related).as_str();
+					path += "<";
+					path += &result_hash;
+					path += ">";
+
+					// Set issuer copy to the identifier issuer in result.
+					issuer_copy = result_issuer;
+
+					if !chosen_path.is_empty() && path.len() >= chosen_path.len() && path > chosen_path {
+						skip = true;
+						break
+					}
+				}
+
+				if skip {
+					continue
+				}
+
+				// If chosen path is empty or path is lexicographically less than chosen
+				// path, set chosen path to path and chosen issuer to issuer copy.
+				if chosen_path.is_empty() || path < chosen_path {
+					chosen_path = path;
+					chosen_issuer = Some(issuer_copy)
+				}
+			}
+
+			// Append chosen path to data to hash and replace issuer with chosen issuer.
+			data_to_hash += &chosen_path;
+			if let Some(chosen_issuer) = chosen_issuer {
+				issuer = chosen_issuer
+			}
+		}
+
+		// Return the hash of data to hash and issuer.
+		Ok((hash(&data_to_hash), issuer))
+	}
+}
+
+/// Iterator over every permutation of a list.
+///
+/// Permutations are computed lazily, in the lexicographic order of the indexes in the list.
+struct Permutations<'a> {
+	list: &'a [BlankId],
+
+	/// Indexes of the next permutation, if any.
+	indexes: Option<Vec<usize>>
+}
+
+impl<'a> Permutations<'a> {
+	fn new(list: &'a [BlankId]) -> Permutations<'a> {
+		Permutations {
+			list,
+			indexes: Some((0..list.len()).collect())
+		}
+	}
+}
+
+impl<'a> Iterator for Permutations<'a> {
+	type Item = Vec<&'a BlankId>;
+
+	fn next(&mut self) -> Option<Vec<&'a BlankId>> {
+		let list = self.list;
+		let indexes = self.indexes.as_mut()?;
+		let permutation = indexes.iter().map(|i| &list[*i]).collect();
+
+		// Find the longest non-increasing suffix, and the pivot on its left.
+		match (1..indexes.len()).rev().find(|i| indexes[i - 1] < indexes[*i]) {
+			Some(i) => {
+				// Swap the pivot with the rightmost greater element of the suffix, then
+				// reverse the suffix.
+				let j = (i..indexes.len()).rev().find(|j| indexes[*j] > indexes[i - 1]).unwrap();
+				indexes.swap(i - 1, j);
+				indexes[i..].reverse()
+			},
+			None => self.indexes = None
+		}
+
+		Some(permutation)
+	}
+}
+
+/// Canonical blank node labeling algorithm (URDNA2015).
+///
+/// Returns the canonical identifier, of the form `_:c14n{n}`, issued for every blank node
+/// identifier appearing in the subject, object or graph name of a quad of the dataset.
+pub fn issue_identifiers<T: Id>(dataset: &Dataset<T>, limits: Limits) -> Result<HashMap<BlankId, BlankId>, Error> {
+	let mut state = State::new(dataset, limits);
+
+	// For each blank node identifier, create a hash using the Hash First Degree Quads
+	// algorithm, and add it to the hash to blank nodes map.
+	let mut hash_to_blank_nodes: BTreeMap<String, Vec<BlankId>> = BTreeMap::new();
+	let mut blank_nodes: Vec<_> = state.blank_node_to_quads.keys().collect();
+	blank_nodes.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+	for id in blank_nodes {
+		let hash = state.hash_first_degree_quads(id);
+		hash_to_blank_nodes.entry(hash).or_default().push(id.clone())
+	}
+
+	// For each hash to identifier list mapping in hash to blank nodes map, code point
+	// ordered by hash, if the identifier list has a single identifier, issue a canonical
+	// identifier for it. Otherwise, keep the list for the next step.
+	let mut non_unique = Vec::new();
+	for (_, ids) in hash_to_blank_nodes {
+		if ids.len() == 1 {
+			state.canonical_issuer.issue(&ids[0]);
+		} else {
+			non_unique.push(ids)
+		}
+	}
+
+	// For each remaining identifier list, in hash order:
+	for ids in non_unique {
+		// Create hash path list.
+		let mut hash_path_list = Vec::new();
+
+		// For each blank node identifier in the list:
+		for id in &ids {
+			// If a canonical identifier has already been issued for identifier, continue.
+			if state.canonical_issuer.contains(id) {
+				continue
+			}
+
+			// Create temporary issuer, an identifier issuer initialized with the prefix `b`,
+			// use it to issue a new temporary blank node identifier for identifier, and run
+			// the Hash N-Degree Quads algorithm.
+			let mut temporary_issuer = IdentifierIssuer::new("b");
+			temporary_issuer.issue(id);
+			hash_path_list.push(state.hash_n_degree_quads(id, temporary_issuer, 0)?)
+		}
+
+		// For each result in the hash path list, code point ordered by the hash in result,
+		// issue a canonical identifier for each existing identifier in the result's issuer,
+		// in the order they were issued.
+		hash_path_list.sort_by(|(a, _), (b, _)| a.cmp(b));
+		for (_, issuer) in hash_path_list {
+			for id in &issuer.order {
+				state.canonical_issuer.issue(id);
+			}
+		}
+	}
+
+	Ok(state.canonical_issuer.issued)
+}
+
+/// Canonicalize the given RDF dataset.
+///
+/// Every blank node identifier is replaced with its canonical identifier,
+/// and the quads are sorted in the code point order of their canonical N-Quads form.
+pub fn canonicalize<T: Id>(dataset: &Dataset<T>, limits: Limits) -> Result<Dataset<T>, Error> {
+	let labels = issue_identifiers(dataset, limits)?;
+
+	let mut quads: Vec<_> = dataset.iter().map(|quad| {
+		let quad = map_blank_ids(quad, |id| labels.get(id).cloned().unwrap_or_else(|| id.clone()));
+		let nquad = NQuads(&quad).to_string();
+		(nquad, quad)
+	}).collect();
+
+	quads.sort_by(|(a, _), (b, _)| a.cmp(b));
+	Ok(quads.into_iter().map(|(_, quad)| quad).collect())
+}
+
+/// Serialize the given RDF dataset into its canonical N-Quads form.
+pub fn to_nquads<T: Id>(dataset: &Dataset<T>, limits: Limits) -> Result<String, Error> {
+	Ok(super::nquads::to_string(&canonicalize(dataset, limits)?))
+}
+
+/// Convert the given expanded document into an RDF dataset and serialize it into its
+/// canonical N-Quads form.
+///
+/// The generator is only used to label the nodes without identifier before
+/// canonicalization, hence it has no effect on the output.
+pub fn canonicalize_expanded<T: Id, G: BlankIdGenerator>(input: &ExpandedDocument<T>, generator: G, options: Options, limits: Limits) -> Result<String, Error> {
+	let dataset = to_rdf(input, generator, options)?;
+	to_nquads(&dataset, limits)
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::IriBuf;
+	use super::*;
+	use crate::rdf::nquads;
+
+	fn canonical(input: &str, limits: Limits) -> Result<String, Error> {
+		let dataset: Dataset<IriBuf> = nquads::parse(input).unwrap();
+		to_nquads(&dataset, limits)
+	}
+
+	// RDFC-1.0, "unique hashes" example.
+	#[test]
+	fn unique_hashes() {
+		let input = "<http://example.com/#p> <http://example.com/#q> _:e0 .\n<http://example.com/#p> <http://example.com/#r> _:e1 .\n_:e0 <http://example.com/#s> <http://example.com/#u> .\n_:e1 <http://example.com/#t> <http://example.com/#u> .\n";
+		let dataset: Dataset<IriBuf> = nquads::parse(input).unwrap();
+
+		let state = State::new(&dataset, Limits::default());
+		assert_eq!(state.hash_first_degree_quads(&BlankId::new("e0")), "21d1dd5ba21f3dee9d76c0c00c260fa6f5d5d65315099e553026f4828d0dc77a");
+		assert_eq!(state.hash_first_degree_quads(&BlankId::new("e1")), "6fa0b9bdb376852b5743ff39ca4cbf7ea14d34966b2828478fbf222e7c764473");
+
+		assert_eq!(canonical(input, Limits::default()).unwrap(), "<http://example.com/#p> <http://example.com/#q> _:c14n0 .\n<http://example.com/#p> <http://example.com/#r> _:c14n1 .\n_:c14n0 <http://example.com/#s> <http://example.com/#u> .\n_:c14n1 <http://example.com/#t> <http://example.com/#u> .\n")
+	}
+
+	// RDFC-1.0, "shared hashes" example.
+	const SHARED_HASHES: &str = "<http://example.com/#p> <http://example.com/#q> _:e0 .\n<http://example.com/#p> <http://example.com/#q> _:e1 .\n_:e0 <http://example.com/#p> _:e2 .\n_:e1 <http://example.com/#p> _:e3 .\n_:e2 <http://example.com/#r> _:e3 .\n";
+
+	#[test]
+	fn shared_hashes() {
+		assert_eq!(canonical(SHARED_HASHES, Limits::default()).unwrap(), "<http://example.com/#p> <http://example.com/#q> _:c14n2 .\n<http://example.com/#p> <http://example.com/#q> _:c14n3 .\n_:c14n0 <http://example.com/#r> _:c14n1 .\n_:c14n2 <http://example.com/#p> _:c14n1 .\n_:c14n3 <http://example.com/#p> _:c14n0 .\n")
+	}
+
+	#[test]
+	fn relabelled_blank_nodes() {
+		let input = "_:x <http://example.com/#r> _:y .\n_:z <http://example.com/#p> _:y .\n<http://example.com/#p> <http://example.com/#q> _:w .\n_:w <http://example.com/#p> _:x .\n<http://example.com/#p> <http://example.com/#q> _:z .\n";
+		assert_eq!(canonical(input, Limits::default()).unwrap(), canonical(SHARED_HASHES, Limits::default()).unwrap())
+	}
+
+	#[test]
+	fn permutations() {
+		let list: Vec<_> = ["a", "b", "c", "d"].iter().map(|id| BlankId::new(id)).collect();
+		let permutations: Vec<Vec<&str>> = Permutations::new(&list).map(|p| p.iter().map(|id| id.as_str()).collect()).collect();
+		assert_eq!(permutations.len(), 24);
+		assert_eq!(permutations[0], ["_:a", "_:b", "_:c", "_:d"]);
+		assert_eq!(permutations[1], ["_:a", "_:b", "_:d", "_:c"]);
+		assert_eq!(permutations[23], ["_:d", "_:c", "_:b", "_:a"]);
+
+		let mut sorted = permutations.clone();
+		sorted.sort();
+		sorted.dedup();
+		assert_eq!(sorted, permutations);
+
+		assert_eq!(Permutations::new(&[]).count(), 1)
+	}
+
+	#[test]
+	fn poison_graph() {
+		// Every blank node is related to every other blank node, so that no hash ever
+		// distinguishes them.
+		let ids = ["a", "b", "c", "d", "e", "f"];
+		let mut input = String::new();
+		for a in &ids {
+			for b in &ids {
+				if a != b {
+					input += &format!("_:{} <http://example.com/#p> _:{} .\n", a, b)
+				}
+			}
+		}
+
+		let limits = Limits {
+			max_calls: 100,
+			..Limits::default()
+		};
+
+		assert_eq!(canonical(&input, limits).unwrap_err().code(), ErrorCode::CanonicalizationLimitExceeded);
+
+		let limits = Limits {
+			max_permutations: 100,
+			..Limits::default()
+		};
+
+		assert_eq!(canonical(&input, limits).unwrap_err().code(), ErrorCode::CanonicalizationLimitExceeded);
+
+	}
+
+	#[test]
+	fn recursion_depth() {
+		// A ring of blank nodes, explored one node deeper at each recursion.
+		let ids = ["a", "b", "c", "d", "e", "f"];
+		let mut input = String::new();
+		for (i, a) in ids.iter().enumerate() {
+			input += &format!("_:{} <http://example.com/#p> _:{} .\n", a, ids[(i + 1) % ids.len()])
+		}
+
+		let limits = Limits {
+			max_depth: 2,
+			..Limits::default()
+		};
+
+		assert_eq!(canonical(&input, limits).unwrap_err().code(), ErrorCode::CanonicalizationLimitExceeded);
+		assert!(canonical(&input, Limits::default()).is_ok());
+		assert_eq!(canonical("_:a <http://example.com/#p> _:b .\n_:b <http://example.com/#p> _:a .\n", limits).unwrap(), "_:c14n0 <http://example.com/#p> _:c14n1 .\n_:c14n1 <http://example.com/#p> _:c14n0 .\n")
+	}
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+use std::collections::HashSet;
+use iref::{Iri, IriBuf};
+use crate::{
+	Id,
+	Reference,
+	LanguageTag
+};
+
+/// RDF literal.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Literal<T: Id = IriBuf> {
+	/// Typed literal, with its lexical value and datatype.
+	Typed(String, T),
+
+	/// Language-tagged string, with its lexical value and language tag.
+	///
+	/// Its datatype is `rdf:langString`.
+	/// The language tag should be a well-formed [BCP47](https://tools.ietf.org/html/bcp47)
+	/// tag, which is checked by the N-Quads parser.
+	LangString(String, String)
+}
+
+impl<T: Id> Literal<T> {
+	/// Get the lexical value of the literal.
+	pub fn value(&self) -> &str {
+		match self {
+			Literal::Typed(value, _) => value.as_str(),
+			Literal::LangString(value, _) => value.as_str()
+		}
+	}
+
+	/// Get the datatype IRI of the literal.
+	pub fn datatype(&self) -> Iri<'_> {
+		match self {
+			Literal::Typed(_, ty) => ty.as_iri(),
+			Literal::LangString(_, _) => Iri::new(super::RDF_LANG_STRING).unwrap()
+		}
+	}
+
+	/// Get the language tag of the literal, if any.
+	pub fn language(&self) -> Option<&str> {
+		match self {
+			Literal::Typed(_, _) => None,
+			Literal::LangString(_, lang) => Some(lang.as_str())
+		}
+	}
+
+	/// Get the language tag of the literal, if any, that can be checked or normalized.
+	pub fn language_tag(&self) -> Option<LanguageTag<'_>> {
+		self.language().map(LanguageTag::new)
+	}
+}
+
+/// RDF term that can appear in the object position of a triple.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Term<T: Id = IriBuf> {
+	/// IRI or blank node.
+	Ref(Reference<T>),
+
+	/// Literal value.
+	Literal(Literal<T>)
+}
+
+impl<T: Id> Term<T> {
+	/// Get the term as a node reference, if it is not a literal.
+	pub fn as_reference(&self) -> Option<&Reference<T>> {
+		match self {
+			Term::Ref(r) => Some(r),
+			Term::Literal(_) => None
+		}
+	}
+
+	/// Get the term as a literal, if it is one.
+	pub fn as_literal(&self) -> Option<&Literal<T>> {
+		match self {
+			Term::Ref(_) => None,
+			Term::Literal(lit) => Some(lit)
+		}
+	}
+}
+
+impl<T: Id> From<Reference<T>> for Term<T> {
+	fn from(r: Reference<T>) -> Term<T> {
+		Term::Ref(r)
+	}
+}
+
+impl<T: Id> From<Literal<T>> for Term<T> {
+	fn from(lit: Literal<T>) -> Term<T> {
+		Term::Literal(lit)
+	}
+}
+
+/// RDF quad.
+///
+/// A triple (subject, predicate, object) along with the graph it belongs to.
+/// The graph is `None` for the default graph.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Quad<T: Id = IriBuf> {
+	/// Subject.
+	pub subject: Reference<T>,
+
+	/// Predicate.
+	///
+	/// May only be a blank node when generalized RDF is produced.
+	pub predicate: Reference<T>,
+
+	/// Object.
+	pub object: Term<T>,
+
+	/// Graph name, or `None` for the default graph.
+	pub graph: Option<Reference<T>>
+}
+
+impl<T: Id> Quad<T> {
+	/// Create a new quad.
+	pub fn new(subject: Reference<T>, predicate: Reference<T>, object: Term<T>, graph: Option<Reference<T>>) -> Quad<T> {
+		Quad {
+			subject, predicate, object, graph
+		}
+	}
+}
+
+/// RDF dataset.
+///
+/// A set of quads.
+/// The quads are kept in insertion order.
+#[derive(Clone)]
+pub struct Dataset<T: Id = IriBuf> {
+	quads: Vec<Quad<T>>,
+	set: HashSet<Quad<T>>
+}
+
+impl<T: Id> Dataset<T> {
+	/// Create a new empty dataset.
+	pub fn new() -> Dataset<T> {
+		Dataset {
+			quads: Vec::new(),
+			set: HashSet::new()
+		}
+	}
+
+	/// Number of quads in the dataset.
+	pub fn len(&self) -> usize {
+		self.quads.len()
+	}
+
+	/// Checks if the dataset is empty.
+	pub fn is_empty(&self) -> bool {
+		self.quads.is_empty()
+	}
+
+	/// Checks if the dataset contains the given quad.
+	pub fn contains(&self, quad: &Quad<T>) -> bool {
+		self.set.contains(quad)
+	}
+
+	/// Insert a quad in the dataset.
+	///
+	/// Returns `false` if the quad was already in the dataset.
+	pub fn insert(&mut self, quad: Quad<T>) -> bool {
+		if self.set.insert(quad.clone()) {
+			self.quads.push(quad);
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Iterate through the quads of the dataset.
+	pub fn iter(&self) -> std::slice::Iter<'_, Quad<T>> {
+		self.quads.iter()
+	}
+
+	/// Get the quads of the dataset.
+	pub fn quads(&self) -> &[Quad<T>] {
+		&self.quads
+	}
+}
+
+impl<T: Id> Default for Dataset<T> {
+	fn default() -> Dataset<T> {
+		Dataset::new()
+	}
+}
+
+impl<T: Id> PartialEq for Dataset<T> {
+	fn eq(&self, other: &Dataset<T>) -> bool {
+		self.set == other.set
+	}
+}
+
+impl<T: Id> Eq for Dataset<T> {}
+
+impl<T: Id> IntoIterator for Dataset<T> {
+	type Item = Quad<T>;
+	type IntoIter = std::vec::IntoIter<Quad<T>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.quads.into_iter()
+	}
+}
+
+impl<'a, T: Id> IntoIterator for &'a Dataset<T> {
+	type Item = &'a Quad<T>;
+	type IntoIter = std::slice::Iter<'a, Quad<T>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.quads.iter()
+	}
+}
+
+impl<T: Id> std::iter::FromIterator<Quad<T>> for Dataset<T> {
+	fn from_iter<I: IntoIterator<Item=Quad<T>>>(iter: I) -> Dataset<T> {
+		let mut dataset = Dataset::new();
+		for quad in iter {
+			dataset.insert(quad);
+		}
+
+		dataset
+	}
+}
+
+impl<T: Id> Extend<Quad<T>> for Dataset<T> {
+	fn extend<I: IntoIterator<Item=Quad<T>>>(&mut self, iter: I) {
+		for quad in iter {
+			self.insert(quad);
+		}
+	}
+}
+
+impl<T: Id + fmt::Debug> fmt::Debug for Dataset<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_list().entries(self.quads.iter()).finish()
+	}
+}
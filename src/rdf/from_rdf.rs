@@ -0,0 +1,539 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use crate::{
+	Error,
+	ErrorCode,
+	Id,
+	Direction,
+	LangString,
+	Reference,
+	Lenient,
+	Indexed,
+	Object,
+	Node,
+	ExpandedDocument,
+	util::integer_number,
+	object::{
+		Value,
+		Literal as JsonLiteral
+	}
+};
+use super::*;
+
+/// Location of a value in a graph: subject, property and position in the property values.
+type Location<T> = (Reference<T>, Reference<T>, usize);
+
+/// Graph being built by the RDF to JSON-LD algorithm.
+struct GraphBuilder<T: Id> {
+	/// Nodes of the graph.
+	nodes: HashMap<Reference<T>, Node<T>>,
+
+	/// Location of every `rdf:nil` usage.
+	nil_usages: Vec<Location<T>>,
+
+	/// Location of the only usage of blank nodes used once as object, or `None` if used more
+	/// than once.
+	referenced_once: HashMap<Reference<T>, Option<Location<T>>>,
+
+	/// Subjects of `rdf:direction` triples, when the compound literal representation is used.
+	compound_literal_subjects: Vec<Reference<T>>,
+
+	/// List values, by location of the list head.
+	lists: HashMap<Location<T>, Vec<Location<T>>>,
+
+	/// Nodes that have been merged into list or compound literal values.
+	removed: HashSet<Reference<T>>
+}
+
+impl<T: Id> GraphBuilder<T> {
+	fn new() -> GraphBuilder<T> {
+		GraphBuilder {
+			nodes: HashMap::new(),
+			nil_usages: Vec::new(),
+			referenced_once: HashMap::new(),
+			compound_literal_subjects: Vec::new(),
+			lists: HashMap::new(),
+			removed: HashSet::new()
+		}
+	}
+
+	fn declare_node(&mut self, id: &Reference<T>) -> &mut Node<T> {
+		self.nodes.entry(id.clone()).or_insert_with(|| Node::with_id(Lenient::Ok(id.clone())))
+	}
+
+	fn get(&self, location: &Location<T>) -> Option<&Indexed<Object<T>>> {
+		let (subject, property, i) = location;
+		self.nodes.get(subject).and_then(|node| node.properties.get(property)).and_then(|values| values.get(*i))
+	}
+
+	fn get_mut(&mut self, location: &Location<T>) -> Option<&mut Indexed<Object<T>>> {
+		let (subject, property, i) = location;
+		self.nodes.get_mut(subject).and_then(|node| node.properties.get_mut(property)).and_then(|values| values.get_mut(*i))
+	}
+
+	/// Checks if the given node is a well-formed list node.
+	///
+	/// A well-formed list node has only `rdf:first` and `rdf:rest` entries (and an optional
+	/// `rdf:List` type), both of which have a single value.
+	fn is_list_node(&self, id: &Reference<T>) -> bool {
+		match self.nodes.get(id) {
+			Some(node) => {
+				let first = Reference::Id(vocab(RDF_FIRST));
+				let rest = Reference::Id(vocab(RDF_REST));
+				let list_type = Lenient::Ok(Reference::Id(vocab(RDF_LIST)));
+
+				node.properties.len() == 2
+				&& node.properties.get(&first).map(|values| values.len() == 1).unwrap_or(false)
+				&& node.properties.get(&rest).map(|values| values.len() == 1).unwrap_or(false)
+				&& (node.types.is_empty() || (node.types.len() == 1 && node.types[0] == list_type))
+			},
+			None => false
+		}
+	}
+
+	/// Build the value at the given location, replacing list heads by their list.
+	fn resolve(&self, location: &Location<T>) -> Indexed<Object<T>> {
+		match self.lists.get(location) {
+			Some(items) => Indexed::new(Object::List(items.iter().map(|item| self.resolve(item)).collect()), None),
+			None => self.get(location).unwrap().clone()
+		}
+	}
+
+	/// Build the final nodes of the graph.
+	fn into_nodes(self) -> HashMap<Reference<T>, Node<T>> {
+		let mut result = HashMap::new();
+
+		for (id, node) in &self.nodes {
+			if !self.removed.contains(id) {
+				let mut resolved = Node::with_id(Lenient::Ok(id.clone()));
+				resolved.types = node.types.clone();
+
+				for (property, values) in &node.properties {
+					let resolved_values = (0..values.len()).map(|i| self.resolve(&(id.clone(), property.clone(), i))).collect();
+					resolved.properties.insert(property.clone(), resolved_values);
+				}
+
+				result.insert(id.clone(), resolved);
+			}
+		}
+
+		result
+	}
+}
+
+/// Serialize RDF as JSON-LD algorithm.
+///
+/// Convert the given RDF dataset into an expanded JSON-LD document.
+/// Well-formed `rdf:first`/`rdf:rest` chains of blank nodes are converted back into lists.
+/// See <https://www.w3.org/TR/json-ld11-api/#serialize-rdf-as-json-ld-algorithm>.
+pub fn from_rdf<'a, T: 'a + Id, D: IntoIterator<Item=&'a Quad<T>>>(dataset: D, options: Options) -> Result<ExpandedDocument<T>, Error> {
+	// Initialize default graph to an empty map.
+	// Initialize graph map to a map consisting of a single entry @default whose value
+	// references default graph.
+	let mut graph_map: HashMap<Option<Reference<T>>, GraphBuilder<T>> = HashMap::new();
+	graph_map.insert(None, GraphBuilder::new());
+
+	let rdf_type = Reference::Id(vocab(RDF_TYPE));
+	let rdf_direction = Reference::Id(vocab(RDF_DIRECTION));
+	let rdf_nil = Reference::Id(vocab(RDF_NIL));
+
+	// For each graph in dataset:
+	// For each triple in graph:
+	for quad in dataset {
+		// If graph map has no name entry, create one and set its value to an empty map.
+		// If graph is not the default graph and default graph does not have a name entry,
+		// create such an entry and initialize its value to a new map with a single entry @id
+		// whose value is name.
+		if let Some(name) = &quad.graph {
+			graph_map.get_mut(&None).unwrap().declare_node(name);
+		}
+
+		let graph = graph_map.entry(quad.graph.clone()).or_insert_with(GraphBuilder::new);
+
+		// If node map does not have a subject entry, create one and initialize its value to
+		// a new map consisting of a single entry @id whose value is set to subject.
+		graph.declare_node(&quad.subject);
+
+		// If object is an IRI or blank node identifier, and node map does not have an object
+		// entry, create one and initialize its value to a new map consisting of a single
+		// entry @id whose value is set to object.
+		if let Term::Ref(object) = &quad.object {
+			graph.declare_node(object);
+
+			// If predicate equals rdf:type, the useRdfType flag is not true, and object is an
+			// IRI or blank node identifier, append object to the value of the @type entry of
+			// node; unless such an item already exists.
+			// Continue with the next RDF triple.
+			if quad.predicate == rdf_type && !options.use_rdf_type {
+				let node = graph.declare_node(&quad.subject);
+				let ty = Lenient::Ok(object.clone());
+				if !node.types.contains(&ty) {
+					node.types.push(ty)
+				}
+
+				continue
+			}
+		}
+
+		// Set value to the result of using the RDF to Object Conversion algorithm, passing
+		// object, rdfDirection, and useNativeTypes.
+		let value = rdf_to_object(&quad.object, options)?;
+
+		// If node does not have a predicate entry, create one and initialize its value to an
+		// array containing value.
+		// Otherwise, if value is not already in the array, append it.
+		let node = graph.declare_node(&quad.subject);
+		let values = node.properties.entry(quad.predicate.clone()).or_default();
+		let index = match values.iter().position(|v| *v == value) {
+			Some(index) => index,
+			None => {
+				values.push(value);
+				values.len() - 1
+			}
+		};
+
+		let location = (quad.subject.clone(), quad.predicate.clone(), index);
+
+		match &quad.object {
+			// If object is rdf:nil, append a new usage map to the usages array of the node
+			// object rdf:nil of node map.
+			Term::Ref(object) if *object == rdf_nil => {
+				graph.nil_usages.push(location)
+			},
+			// Otherwise, if referenced once has an entry for object, set the object entry of
+			// referenced once to false.
+			// Otherwise, if object is a blank node identifier, it might represent a list
+			// node: set the object entry of referenced once to a new usage map.
+			Term::Ref(object @ Reference::Blank(_)) => {
+				if graph.referenced_once.contains_key(object) {
+					graph.referenced_once.insert(object.clone(), None);
+				} else {
+					graph.referenced_once.insert(object.clone(), Some(location));
+				}
+			},
+			_ => ()
+		}
+
+		// If rdfDirection is compound-literal and predicate is rdf:direction, add subject to
+		// the compound literal subjects of the graph.
+		if options.rdf_direction == Some(RdfDirection::CompoundLiteral) && quad.predicate == rdf_direction && !graph.compound_literal_subjects.contains(&quad.subject) {
+			graph.compound_literal_subjects.push(quad.subject.clone())
+		}
+	}
+
+	// For each name and graph object in graph map:
+	for graph in graph_map.values_mut() {
+		// If compound literal subjects has an entry for name, then for each cl in that:
+		for cl in std::mem::take(&mut graph.compound_literal_subjects) {
+			compound_literal_to_value(graph, &cl)?;
+		}
+
+		// If graph object has no rdf:nil entry, continue with the next name-graph object
+		// pair as the graph does not contain any lists that need to be converted.
+		// For each item usage in the usages entry of nil:
+		for usage in std::mem::take(&mut graph.nil_usages) {
+			convert_list(graph, usage)
+		}
+	}
+
+	// Initialize an empty array result.
+	// For each subject and node in default graph ordered by subject:
+	let default_graph = graph_map.remove(&None).unwrap().into_nodes();
+	let mut named_graphs: HashMap<_, _> = graph_map.into_iter().map(|(name, graph)| (name.unwrap(), graph.into_nodes())).collect();
+
+	let mut result = HashSet::new();
+	for (subject, mut node) in default_graph {
+		// If graph map has a subject entry:
+		// Add an @graph entry to node and initialize its value to an empty array.
+		// For each key-value pair s-n in the subject entry of graph map ordered by s, append
+		// n to the @graph entry of node after removing its usages entry, unless the only
+		// remaining entry of n is @id.
+		if let Some(graph) = named_graphs.remove(&subject) {
+			node.graph = Some(Box::new(graph.into_iter().filter(|(_, n)| !is_only_id(n)).map(|(_, n)| Indexed::new(Object::Node(n), None)).collect()));
+		}
+
+		// Append node to result after removing its usages entry, unless the only remaining
+		// entry of node is @id.
+		if !is_only_id(&node) {
+			result.insert(Indexed::new(Object::Node(node), None));
+		}
+	}
+
+	Ok(result)
+}
+
+/// Checks if the given node has no other entry than `@id`.
+fn is_only_id<T: Id>(node: &Node<T>) -> bool {
+	node.types.is_empty() && node.graph.is_none() && node.properties.is_empty()
+}
+
+/// Replace the references to the given compound literal node by a language-tagged string.
+fn compound_literal_to_value<T: Id>(graph: &mut GraphBuilder<T>, cl: &Reference<T>) -> Result<(), Error> {
+	// Initialize cl entry to the value of cl in referenced once, continuing to the next cl
+	// if cl entry is not a map.
+	let location = match graph.referenced_once.get(cl) {
+		Some(Some(location)) => location.clone(),
+		_ => return Ok(())
+	};
+
+	// Initialize cl node to the value of cl in graph object, and remove that entry from
+	// graph object, as it will be converted to a string value.
+	let cl_node = match graph.nodes.get(cl) {
+		Some(node) => node,
+		None => return Ok(())
+	};
+
+	let first_string = |iri: &str| {
+		cl_node.properties.get(&Reference::Id(vocab(iri))).and_then(|values| values.first()).and_then(|value| match value.inner() {
+			Object::Value(value) => value.as_str().map(str::to_string),
+			_ => None
+		})
+	};
+
+	let value = first_string(RDF_VALUE).unwrap_or_default();
+
+	// If cl node has an rdf:language property, set the @language entry of cl reference to
+	// the value of its @value entry.
+	let language = first_string(RDF_LANGUAGE);
+
+	// If cl node has an rdf:direction property, set the @direction entry of cl reference
+	// to the value of its @value entry.
+	// If it is not "ltr" or "rtl", an invalid base direction has been detected and
+	// processing is aborted.
+	let direction = match first_string(RDF_DIRECTION) {
+		Some(direction) => match Direction::try_from(direction.as_str()) {
+			Ok(direction) => Some(direction),
+			Err(_) => return Err(ErrorCode::InvalidBaseDirection.into())
+		},
+		None => None
+	};
+
+	graph.removed.insert(cl.clone());
+
+	// For each cl reference in the property entry of node, if its @id entry equals cl,
+	// replace it with the string value.
+	if let Some(cl_reference) = graph.get_mut(&location) {
+		*cl_reference = Indexed::new(Object::Value(Value::LangString(LangString::new(value, language, direction))), None);
+	}
+
+	Ok(())
+}
+
+/// Convert the list ending with the given `rdf:nil` usage.
+fn convert_list<T: Id>(graph: &mut GraphBuilder<T>, usage: Location<T>) {
+	let rdf_first = Reference::Id(vocab(RDF_FIRST));
+	let rdf_rest = Reference::Id(vocab(RDF_REST));
+
+	// Initialize node to the value of the node entry of usage, property to the value of the
+	// property entry of usage, and head to the value of the value entry of usage.
+	let (mut node, mut property, _) = usage.clone();
+	let mut head = usage;
+
+	// Initialize two empty arrays list and list nodes.
+	let mut list = Vec::new();
+	let mut list_nodes = Vec::new();
+
+	// While property equals rdf:rest, the value of the @id entry of node is a blank node
+	// identifier, the value of the entry of referenced once associated with the @id entry
+	// of node is a map, node has rdf:first and rdf:rest entries, both of which have as value
+	// an array consisting of a single element, and node has no other entries apart from an
+	// optional @type entry whose value is an array with a single item equal to rdf:List,
+	// node represents a well-formed list node.
+	while property == rdf_rest && matches!(node, Reference::Blank(_)) && graph.is_list_node(&node) {
+		let node_usage = match graph.referenced_once.get(&node) {
+			Some(Some(node_usage)) => node_usage.clone(),
+			_ => break
+		};
+
+		// Append the only item of rdf:first entry of node to the list array.
+		// Append the value of the @id entry of node to the list nodes array.
+		list.push((node.clone(), rdf_first.clone(), 0));
+		list_nodes.push(node);
+
+		// Initialize node usage to the value of the entry of referenced once associated with
+		// the @id entry of node.
+		// Set node to the value of the node entry of node usage, property to the value of
+		// the property entry of node usage, and head to the value of the value entry of node
+		// usage.
+		let (next_node, next_property, _) = node_usage.clone();
+		node = next_node;
+		property = next_property;
+		head = node_usage;
+
+		// If the @id entry of node is an IRI instead of a blank node identifier, exit the
+		// while loop.
+		if !matches!(node, Reference::Blank(_)) {
+			break
+		}
+	}
+
+	// Remove the @id entry from head.
+	// Reverse the order of the list array.
+	// Add an @list entry to head and initialize its value to the list array.
+	list.reverse();
+	graph.lists.insert(head, list);
+
+	// For each item node id in list nodes, remove the node id entry from graph object.
+	graph.removed.extend(list_nodes);
+}
+
+/// RDF to object conversion algorithm.
+///
+/// See <https://www.w3.org/TR/json-ld11-api/#rdf-to-object-conversion>.
+fn rdf_to_object<T: Id>(object: &Term<T>, options: Options) -> Result<Indexed<Object<T>>, Error> {
+	let literal = match object {
+		// If value is an IRI or a blank node identifier, return a new map consisting of a
+		// single entry @id whose value is set to value.
+		Term::Ref(r) => return Ok(Indexed::new(Object::Node(Node::with_id(Lenient::Ok(r.clone()))), None)),
+		Term::Literal(literal) => literal
+	};
+
+	let value = match literal {
+		// Otherwise, if value is a language-tagged string, add an entry @language to result
+		// and set its value to the language tag of value.
+		Literal::LangString(value, language) => Value::LangString(LangString::new(value.clone(), Some(language.clone()), None)),
+		Literal::Typed(value, datatype) => {
+			let datatype_iri = datatype.as_iri();
+			let datatype_iri = datatype_iri.as_str();
+
+			match datatype_iri {
+				// If useNativeTypes is true, convert xsd:string, xsd:boolean, xsd:integer and
+				// xsd:double literals into native JSON values when their lexical form is
+				// valid.
+				XSD_STRING => Value::Literal(JsonLiteral::String(value.clone()), HashSet::new()),
+				XSD_BOOLEAN if options.use_native_types && (value == "true" || value == "false") => {
+					Value::Literal(JsonLiteral::Boolean(value == "true"), HashSet::new())
+				},
+				XSD_INTEGER if options.use_native_types && is_integer(value) => {
+					// Integers that do not fit in an `i64` are converted into doubles.
+					let n = match value.parse::<i64>() {
+						Ok(i) => integer_number(i),
+						Err(_) => value.parse::<f64>().map_err(|e| Error::new(ErrorCode::InvalidTypedValue, e))?.into()
+					};
+
+					Value::Literal(JsonLiteral::Number(n), HashSet::new())
+				},
+				XSD_DOUBLE if options.use_native_types && is_double(value) => {
+					let n = value.parse::<f64>().map_err(|e| Error::new(ErrorCode::InvalidTypedValue, e))?;
+					Value::Literal(JsonLiteral::Number(n.into()), HashSet::new())
+				},
+				// Otherwise, if processing mode is not json-ld-1.0, and value is a JSON literal,
+				// set converted value to the result of turning the lexical value of value
+				// into the JSON-LD internal representation, and set type to @json.
+				// If the lexical value of value is not valid JSON according to the JSON
+				// Grammar [RFC8259], an invalid JSON literal error has been detected and
+				// processing is aborted.
+				RDF_JSON => match json::parse(value) {
+					Ok(json) => Value::Literal(JsonLiteral::Json(json), HashSet::new()),
+					Err(_) => return Err(ErrorCode::InvalidJsonLiteral.into())
+				},
+				// Otherwise, if the datatype IRI of value starts with
+				// https://www.w3.org/ns/i18n#, and rdfDirection is i18n-datatype, set the
+				// @language and @direction entries from the fragment.
+				datatype_iri if options.rdf_direction == Some(RdfDirection::I18nDatatype) && datatype_iri.starts_with(I18N_BASE) => {
+					match i18n_language_direction(&datatype_iri[I18N_BASE.len()..]) {
+						Some((language, direction)) => Value::LangString(LangString::new(value.clone(), language, Some(direction))),
+						None => typed_value(value, datatype)
+					}
+				},
+				// Otherwise, if the datatype IRI of value is not xsd:string, set the @type
+				// entry of result to datatype.
+				_ => typed_value(value, datatype)
+			}
+		}
+	};
+
+	Ok(Indexed::new(Object::Value(value), None))
+}
+
+fn typed_value<T: Id>(value: &str, datatype: &T) -> Value<T> {
+	let mut types = HashSet::new();
+	types.insert(datatype.clone());
+	Value::Literal(JsonLiteral::String(value.to_string()), types)
+}
+
+/// Split the fragment of an `https://www.w3.org/ns/i18n#` datatype into a language and
+/// direction.
+fn i18n_language_direction(fragment: &str) -> Option<(Option<String>, Direction)> {
+	let i = fragment.rfind('_')?;
+	let direction = Direction::try_from(&fragment[i+1..]).ok()?;
+	let language = &fragment[..i];
+
+	if language.is_empty() {
+		Some((None, direction))
+	} else {
+		Some((Some(language.to_string()), direction))
+	}
+}
+
+/// Checks if the given string is a valid `xsd:integer` lexical form.
+fn is_integer(value: &str) -> bool {
+	let digits = value.strip_prefix(|c| c == '+' || c == '-').unwrap_or(value);
+	!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Checks if the given string is a valid (finite) `xsd:double` lexical form.
+fn is_double(value: &str) -> bool {
+	let value = value.strip_prefix(|c| c == '+' || c == '-').unwrap_or(value);
+
+	let (mantissa, exponent) = match value.find(['e', 'E']) {
+		Some(i) => (&value[..i], Some(&value[i+1..])),
+		None => (value, None)
+	};
+
+	let valid_mantissa = match mantissa.find('.') {
+		Some(i) => {
+			let (int, frac) = (&mantissa[..i], &mantissa[i+1..]);
+			(!int.is_empty() || !frac.is_empty()) && int.chars().all(|c| c.is_ascii_digit()) && frac.chars().all(|c| c.is_ascii_digit())
+		},
+		None => !mantissa.is_empty() && mantissa.chars().all(|c| c.is_ascii_digit())
+	};
+
+	let valid_exponent = match exponent {
+		Some(exponent) => is_integer(exponent),
+		None => true
+	};
+
+	valid_mantissa && valid_exponent
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::IriBuf;
+	use crate::util::AsJson;
+	use super::*;
+
+	fn native(value: &str, datatype: &str) -> String {
+		let quad = Quad::new(
+			Reference::Id(IriBuf::new("http://e.org/s").unwrap()),
+			Reference::Id(IriBuf::new("http://e.org/p").unwrap()),
+			Term::Literal(Literal::Typed(value.to_string(), IriBuf::new(datatype).unwrap())),
+			None
+		);
+
+		let dataset: Dataset = std::iter::once(quad).collect();
+		let options = Options {
+			use_native_types: true,
+			..Options::default()
+		};
+
+		from_rdf(&dataset, options).unwrap().as_json().dump()
+	}
+
+	#[test]
+	fn native_integer_bounds() {
+		assert!(native("-9223372036854775808", XSD_INTEGER).contains("-9223372036854775808"));
+		assert!(native("9223372036854775807", XSD_INTEGER).contains("9223372036854775807"));
+	}
+
+	#[test]
+	fn native_integer_overflow() {
+		// Integers that do not fit in an `i64` are converted into doubles.
+		assert!(native("100000000000000000000", XSD_INTEGER).contains("1e20"));
+	}
+
+	#[test]
+	fn native_double() {
+		assert!(native("1.5E300", XSD_DOUBLE).contains("1.5e300"));
+	}
+}
@@ -0,0 +1,128 @@
+use crate::{
+	Error,
+	Id,
+	BlankIdCounter,
+	ExpandedDocument
+};
+use super::{
+	Dataset,
+	Options,
+	RdfDirection,
+	to_rdf,
+	canonicalization
+};
+
+/// Checks if the two given RDF datasets are isomorphic.
+///
+/// Two datasets are isomorphic if there exists a bijection between their blank node
+/// identifiers that makes them equal.
+/// This is decided by comparing the canonical forms of the datasets, computed within the
+/// default canonicalization [`Limits`](canonicalization::Limits).
+pub fn is_isomorphic_dataset<T: Id>(a: &Dataset<T>, b: &Dataset<T>) -> Result<bool, Error> {
+	let limits = canonicalization::Limits::default();
+	Ok(a.len() == b.len() && canonicalization::canonicalize(a, limits)? == canonicalization::canonicalize(b, limits)?)
+}
+
+/// Checks if the two given expanded documents denote isomorphic RDF datasets.
+///
+/// Both documents are converted into RDF datasets, which are then compared up to blank node
+/// renaming using [`is_isomorphic_dataset`].
+/// Blank node identifiers are hence treated as existential, and nodes are compared whatever
+/// their position in the document (embedded, in named graphs, in `@included` or lists).
+///
+/// This is RDF dataset equivalence, not JSON-LD document equality:
+/// everything that is lost by the conversion into RDF is ignored.
+/// In particular, two documents differing only by their `@index` entries, their
+/// free-floating values or their properties with a blank node identifier are considered
+/// isomorphic.
+pub fn is_rdf_isomorphic<T: Id>(a: &ExpandedDocument<T>, b: &ExpandedDocument<T>) -> Result<bool, Error> {
+	let options = Options {
+		rdf_direction: Some(RdfDirection::I18nDatatype),
+		..Options::default()
+	};
+
+	let a = to_rdf(a, BlankIdCounter::default(), options)?;
+	let b = to_rdf(b, BlankIdCounter::default(), options)?;
+
+	is_isomorphic_dataset(&a, &b)
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use iref::IriBuf;
+	use crate::{
+		Document,
+		NoLoader,
+		expansion,
+		context::JsonContext
+	};
+	use super::*;
+
+	fn expand(doc: &str) -> ExpandedDocument<IriBuf> {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		block_on(doc.expand_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap()
+	}
+
+	fn is_isomorphic(a: &str, b: &str) -> bool {
+		is_rdf_isomorphic(&expand(a), &expand(b)).unwrap()
+	}
+
+	#[test]
+	fn relabelled_blank_nodes() {
+		let a = r#"{
+			"@id": "_:a",
+			"http://example.org/knows": {"@id": "_:b", "http://example.org/name": "B"},
+			"http://example.org/name": "A"
+		}"#;
+
+		// Same graph with other labels, flattened and without label for one of the nodes.
+		let b = r#"[
+			{"@id": "_:y", "http://example.org/name": "B"},
+			{"http://example.org/name": "A", "http://example.org/knows": {"@id": "_:y"}}
+		]"#;
+
+		assert!(is_isomorphic(a, b));
+
+		// The blank nodes are swapped.
+		let c = r#"{
+			"@id": "_:a",
+			"http://example.org/knows": {"@id": "_:b", "http://example.org/name": "A"},
+			"http://example.org/name": "B"
+		}"#;
+
+		assert!(!is_isomorphic(a, c))
+	}
+
+	#[test]
+	fn named_graphs() {
+		let a = r#"{"@id": "_:g", "@graph": {"@id": "http://example.org/a", "http://example.org/p": {"@id": "_:n"}}}"#;
+		let b = r#"{"@id": "_:h", "@graph": {"@id": "http://example.org/a", "http://example.org/p": {"@id": "_:m"}}}"#;
+		let c = r#"{"@id": "http://example.org/g", "@graph": {"@id": "http://example.org/a", "http://example.org/p": {"@id": "_:m"}}}"#;
+		assert!(is_isomorphic(a, b));
+		assert!(!is_isomorphic(a, c))
+	}
+
+	#[test]
+	fn list_order() {
+		let a = r#"{"@id": "http://example.org/a", "http://example.org/p": {"@list": [1, 2, 3]}}"#;
+		let b = r#"{"@id": "http://example.org/a", "http://example.org/p": {"@list": [1, 3, 2]}}"#;
+		assert!(is_isomorphic(a, a));
+		assert!(!is_isomorphic(a, b));
+
+		// The order of the values of a set is not significant.
+		let c = r#"{"@id": "http://example.org/a", "http://example.org/p": [1, 2, 3]}"#;
+		let d = r#"{"@id": "http://example.org/a", "http://example.org/p": [3, 1, 2]}"#;
+		assert!(is_isomorphic(c, d))
+	}
+
+	#[test]
+	fn index_is_ignored() {
+		let a = r#"{"@id": "http://example.org/a", "http://example.org/p": {"@value": "v", "@index": "i"}}"#;
+		let b = r#"{"@id": "http://example.org/a", "http://example.org/p": {"@value": "v", "@index": "j"}}"#;
+		let c = r#"{"@id": "http://example.org/a", "http://example.org/p": "v"}"#;
+		assert!(is_isomorphic(a, b));
+		assert!(is_isomorphic(a, c))
+	}
+}
@@ -0,0 +1,92 @@
+//! RDF dataset types and conversion algorithms.
+
+mod dataset;
+mod to_rdf;
+mod from_rdf;
+mod isomorphism;
+pub mod nquads;
+pub mod canonicalization;
+
+use iref::Iri;
+use crate::Id;
+
+pub use dataset::*;
+pub use to_rdf::*;
+pub use from_rdf::*;
+pub use isomorphism::*;
+
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+pub const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+pub const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+pub const RDF_LIST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#List";
+pub const RDF_VALUE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#value";
+pub const RDF_LANGUAGE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#language";
+pub const RDF_DIRECTION: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#direction";
+pub const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+pub const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+pub const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+pub const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+pub const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+pub const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+pub const XSD_DATE_TIME: &str = "http://www.w3.org/2001/XMLSchema#dateTime";
+pub const I18N_BASE: &str = "https://www.w3.org/ns/i18n#";
+
+/// Build an identifier from one of the vocabulary IRIs above.
+pub(crate) fn vocab<T: Id>(iri: &str) -> T {
+	T::from_iri(Iri::new(iri).unwrap())
+}
+
+/// How to represent the base direction of strings in RDF.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RdfDirection {
+	/// Encode the language and direction in the datatype IRI
+	/// (`https://www.w3.org/ns/i18n#{language}_{direction}`).
+	I18nDatatype,
+
+	/// Use a blank node with the `rdf:value`, `rdf:language` and `rdf:direction` properties.
+	CompoundLiteral
+}
+
+impl RdfDirection {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RdfDirection::I18nDatatype => "i18n-datatype",
+			RdfDirection::CompoundLiteral => "compound-literal"
+		}
+	}
+}
+
+impl<'a> std::convert::TryFrom<&'a str> for RdfDirection {
+	type Error = &'a str;
+
+	/// Convert the strings `"i18n-datatype"` and `"compound-literal"` into a `RdfDirection`.
+	fn try_from(name: &'a str) -> Result<RdfDirection, &'a str> {
+		match name {
+			"i18n-datatype" => Ok(RdfDirection::I18nDatatype),
+			"compound-literal" => Ok(RdfDirection::CompoundLiteral),
+			_ => Err(name)
+		}
+	}
+}
+
+/// RDF conversion options.
+#[derive(Clone, Copy, Default)]
+pub struct Options {
+	/// If set to true, triples with a blank node predicate are produced.
+	/// Such triples are only valid in generalized RDF.
+	pub produce_generalized_rdf: bool,
+
+	/// How the base direction of strings is represented, if at all.
+	///
+	/// If `None`, the direction is dropped.
+	pub rdf_direction: Option<RdfDirection>,
+
+	/// If set to true, `xsd:boolean`, `xsd:integer` and `xsd:double` literals are converted
+	/// into native JSON values when converting from RDF.
+	pub use_native_types: bool,
+
+	/// If set to true, `rdf:type` triples are kept as regular properties instead of being
+	/// converted into `@type` entries when converting from RDF.
+	pub use_rdf_type: bool
+}
@@ -0,0 +1,495 @@
+//! N-Quads parser and serializer.
+//!
+//! See <https://www.w3.org/TR/n-quads/>.
+
+use std::fmt;
+use std::io;
+use iref::Iri;
+use crate::{
+	Id,
+	BlankId,
+	Reference,
+	LanguageTag
+};
+use super::{
+	Literal,
+	Term,
+	Quad,
+	Dataset,
+	XSD_STRING,
+	RDF_LANG_STRING
+};
+
+/// N-Quads parsing error.
+#[derive(Debug)]
+pub enum Error {
+	/// Error while reading the input.
+	Io(io::Error),
+
+	/// Syntax error at the given line (starting from 1).
+	Syntax(usize, String)
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Io(e) => e.fmt(f),
+			Error::Syntax(line, message) => write!(f, "line {}: {}", line, message)
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Io(e) => Some(e),
+			Error::Syntax(_, _) => None
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Error {
+		Error::Io(e)
+	}
+}
+
+/// Write a string literal, escaping it.
+///
+/// `"`, `\`, tabulations, backspaces, line feeds, form feeds and carriage returns are escaped
+/// with their short form. Other control characters (`U+0000` to `U+001F`, and `U+007F`) are
+/// escaped as `\u00XX`.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+	write!(f, "\"")?;
+	for c in s.chars() {
+		match c {
+			'"' => write!(f, "\\\"")?,
+			'\\' => write!(f, "\\\\")?,
+			'\t' => write!(f, "\\t")?,
+			'\u{08}' => write!(f, "\\b")?,
+			'\n' => write!(f, "\\n")?,
+			'\u{0c}' => write!(f, "\\f")?,
+			'\r' => write!(f, "\\r")?,
+			c if c <= '\u{1f}' || c == '\u{7f}' => write!(f, "\\u{:04X}", c as u32)?,
+			c => write!(f, "{}", c)?
+		}
+	}
+	write!(f, "\"")
+}
+
+/// N-Quads representation of a value.
+pub struct NQuads<'a, V>(pub &'a V);
+
+impl<'a, T: Id> fmt::Display for NQuads<'a, Reference<T>> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0 {
+			Reference::Id(id) => write!(f, "<{}>", id.as_iri()),
+			Reference::Blank(id) => id.fmt(f)
+		}
+	}
+}
+
+impl<'a, T: Id> fmt::Display for NQuads<'a, Literal<T>> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0 {
+			Literal::Typed(value, ty) => {
+				write_string(f, value)?;
+				let ty = ty.as_iri();
+				if ty.as_str() != XSD_STRING {
+					write!(f, "^^<{}>", ty)?
+				}
+
+				Ok(())
+			},
+			Literal::LangString(value, lang) => {
+				write_string(f, value)?;
+				write!(f, "@{}", lang)
+			}
+		}
+	}
+}
+
+impl<'a, T: Id> fmt::Display for NQuads<'a, Term<T>> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.0 {
+			Term::Ref(r) => NQuads(r).fmt(f),
+			Term::Literal(lit) => NQuads(lit).fmt(f)
+		}
+	}
+}
+
+impl<'a, T: Id> fmt::Display for NQuads<'a, Quad<T>> {
+	/// Displays the quad statement, without the trailing new line.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let quad = self.0;
+		write!(f, "{} {} {}", NQuads(&quad.subject), NQuads(&quad.predicate), NQuads(&quad.object))?;
+
+		if let Some(graph) = &quad.graph {
+			write!(f, " {}", NQuads(graph))?
+		}
+
+		write!(f, " .")
+	}
+}
+
+/// Write the given quads in the N-Quads format, one statement per line.
+pub fn write<'a, T: 'a + Id, W: io::Write, Q: IntoIterator<Item=&'a Quad<T>>>(out: &mut W, quads: Q) -> io::Result<()> {
+	for quad in quads {
+		writeln!(out, "{}", NQuads(quad))?
+	}
+
+	Ok(())
+}
+
+/// Serialize the given quads in the N-Quads format, one statement per line.
+pub fn to_string<'a, T: 'a + Id, Q: IntoIterator<Item=&'a Quad<T>>>(quads: Q) -> String {
+	let mut result = String::new();
+	for quad in quads {
+		result += &NQuads(quad).to_string();
+		result.push('\n');
+	}
+
+	result
+}
+
+/// Parse a whole N-Quads document.
+pub fn parse<T: Id>(input: &str) -> Result<Dataset<T>, Error> {
+	let mut dataset = Dataset::new();
+
+	for (i, line) in input.lines().enumerate() {
+		if let Some(quad) = parse_line(line).map_err(|e| Error::Syntax(i + 1, e))? {
+			dataset.insert(quad);
+		}
+	}
+
+	Ok(dataset)
+}
+
+/// Streaming N-Quads reader.
+///
+/// Reads the input line by line, and iterates through the parsed quads.
+pub struct Reader<R: io::BufRead, T: Id> {
+	input: R,
+	line: usize,
+	buffer: String,
+	id: std::marker::PhantomData<T>
+}
+
+impl<R: io::BufRead, T: Id> Reader<R, T> {
+	/// Create a new reader from a buffered input.
+	pub fn new(input: R) -> Reader<R, T> {
+		Reader {
+			input,
+			line: 0,
+			buffer: String::new(),
+			id: std::marker::PhantomData
+		}
+	}
+}
+
+impl<R: io::BufRead, T: Id> Iterator for Reader<R, T> {
+	type Item = Result<Quad<T>, Error>;
+
+	fn next(&mut self) -> Option<Result<Quad<T>, Error>> {
+		loop {
+			self.buffer.clear();
+			match self.input.read_line(&mut self.buffer) {
+				Ok(0) => return None,
+				Ok(_) => {
+					self.line += 1;
+					match parse_line(&self.buffer) {
+						Ok(Some(quad)) => return Some(Ok(quad)),
+						Ok(None) => (),
+						Err(e) => return Some(Err(Error::Syntax(self.line, e)))
+					}
+				},
+				Err(e) => return Some(Err(e.into()))
+			}
+		}
+	}
+}
+
+/// Parse a single line of a N-Quads document.
+///
+/// Returns `None` if the line contains no statement (empty line or comment).
+pub fn parse_line<T: Id>(line: &str) -> Result<Option<Quad<T>>, String> {
+	let mut parser = Parser {
+		chars: line.chars().peekable()
+	};
+
+	parser.skip_whitespaces();
+	if parser.is_end() {
+		return Ok(None)
+	}
+
+	let subject = parser.parse_reference()?;
+	parser.skip_whitespaces();
+	let predicate = parser.parse_reference()?;
+	parser.skip_whitespaces();
+	let object = parser.parse_term()?;
+	parser.skip_whitespaces();
+
+	let graph = match parser.chars.peek() {
+		Some('.') => None,
+		_ => {
+			let graph = parser.parse_reference()?;
+			parser.skip_whitespaces();
+			Some(graph)
+		}
+	};
+
+	parser.expect('.')?;
+	parser.skip_whitespaces();
+
+	if parser.is_end() {
+		Ok(Some(Quad::new(subject, predicate, object, graph)))
+	} else {
+		Err("unexpected characters after the end of the statement".to_string())
+	}
+}
+
+struct Parser<'a> {
+	chars: std::iter::Peekable<std::str::Chars<'a>>
+}
+
+impl<'a> Parser<'a> {
+	/// Checks if the end of the line (or a comment) has been reached.
+	fn is_end(&mut self) -> bool {
+		matches!(self.chars.peek(), None | Some('#'))
+	}
+
+	fn skip_whitespaces(&mut self) {
+		while let Some(c) = self.chars.peek() {
+			if c.is_whitespace() {
+				self.chars.next();
+			} else {
+				break
+			}
+		}
+	}
+
+	fn expect(&mut self, expected: char) -> Result<(), String> {
+		match self.chars.next() {
+			Some(c) if c == expected => Ok(()),
+			Some(c) => Err(format!("expected `{}`, found `{}`", expected, c)),
+			None => Err(format!("expected `{}`, found end of line", expected))
+		}
+	}
+
+	fn parse_reference<T: Id>(&mut self) -> Result<Reference<T>, String> {
+		match self.chars.peek() {
+			Some('<') => Ok(Reference::Id(self.parse_iri()?)),
+			Some('_') => Ok(Reference::Blank(self.parse_blank_id()?)),
+			Some(c) => Err(format!("expected an IRI or a blank node identifier, found `{}`", c)),
+			None => Err("expected an IRI or a blank node identifier, found end of line".to_string())
+		}
+	}
+
+	fn parse_term<T: Id>(&mut self) -> Result<Term<T>, String> {
+		match self.chars.peek() {
+			Some('"') => Ok(Term::Literal(self.parse_literal()?)),
+			_ => Ok(Term::Ref(self.parse_reference()?))
+		}
+	}
+
+	fn parse_iri<T: Id>(&mut self) -> Result<T, String> {
+		self.expect('<')?;
+		let mut iri = String::new();
+
+		loop {
+			match self.chars.next() {
+				Some('>') => break,
+				Some('\\') => match self.chars.next() {
+					Some('u') => iri.push(self.parse_unicode(4)?),
+					Some('U') => iri.push(self.parse_unicode(8)?),
+					_ => return Err("invalid escape sequence in IRI".to_string())
+				},
+				Some(c) => iri.push(c),
+				None => return Err("unterminated IRI".to_string())
+			}
+		}
+
+		match Iri::new(&iri) {
+			Ok(iri) => Ok(T::from_iri(iri)),
+			Err(_) => Err(format!("invalid IRI `{}`", iri))
+		}
+	}
+
+	fn parse_blank_id(&mut self) -> Result<BlankId, String> {
+		self.expect('_')?;
+		self.expect(':')?;
+		let mut name = String::new();
+
+		while let Some(c) = self.chars.peek().cloned() {
+			if c.is_whitespace() || c == '<' || c == '"' || c == '#' {
+				break
+			}
+
+			// A blank node label cannot end with a `.`, which is then the end of the
+			// statement.
+			if c == '.' {
+				let mut ahead = self.chars.clone();
+				ahead.next();
+				match ahead.peek() {
+					None => break,
+					Some(c) if c.is_whitespace() || *c == '#' => break,
+					_ => ()
+				}
+			}
+
+			name.push(c);
+			self.chars.next();
+		}
+
+		if name.is_empty() {
+			Err("empty blank node identifier".to_string())
+		} else {
+			Ok(BlankId::new(&name))
+		}
+	}
+
+	fn parse_unicode(&mut self, len: usize) -> Result<char, String> {
+		let mut code = String::new();
+		for _ in 0..len {
+			match self.chars.next() {
+				Some(c) => code.push(c),
+				None => return Err("unterminated unicode escape sequence".to_string())
+			}
+		}
+
+		u32::from_str_radix(&code, 16).ok().and_then(std::char::from_u32).ok_or_else(|| format!("invalid unicode escape sequence `{}`", code))
+	}
+
+	fn parse_literal<T: Id>(&mut self) -> Result<Literal<T>, String> {
+		self.expect('"')?;
+		let mut value = String::new();
+
+		loop {
+			match self.chars.next() {
+				Some('"') => break,
+				Some('\\') => match self.chars.next() {
+					Some('t') => value.push('\t'),
+					Some('b') => value.push('\u{08}'),
+					Some('n') => value.push('\n'),
+					Some('r') => value.push('\r'),
+					Some('f') => value.push('\u{0c}'),
+					Some('"') => value.push('"'),
+					Some('\'') => value.push('\''),
+					Some('\\') => value.push('\\'),
+					Some('u') => value.push(self.parse_unicode(4)?),
+					Some('U') => value.push(self.parse_unicode(8)?),
+					_ => return Err("invalid escape sequence in string literal".to_string())
+				},
+				Some(c) => value.push(c),
+				None => return Err("unterminated string literal".to_string())
+			}
+		}
+
+		match self.chars.peek() {
+			Some('@') => {
+				self.chars.next();
+				let mut lang = String::new();
+				while let Some(c) = self.chars.peek() {
+					if c.is_ascii_alphanumeric() || *c == '-' {
+						lang.push(*c);
+						self.chars.next();
+					} else {
+						break
+					}
+				}
+
+				if lang.is_empty() {
+					Err("empty language tag".to_string())
+				} else if !LanguageTag::new(&lang).is_well_formed() {
+					Err(format!("malformed language tag `{}`", lang))
+				} else {
+					Ok(Literal::LangString(value, lang))
+				}
+			},
+			Some('^') => {
+				self.chars.next();
+				self.expect('^')?;
+				let ty: T = self.parse_iri()?;
+
+				if ty.as_iri().as_str() == RDF_LANG_STRING {
+					Err("language-tagged string without language tag".to_string())
+				} else {
+					Ok(Literal::Typed(value, ty))
+				}
+			},
+			_ => Ok(Literal::Typed(value, super::vocab(XSD_STRING)))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use iref::IriBuf;
+	use super::*;
+
+	fn reformat(line: &str) -> String {
+		let quad: Quad<IriBuf> = parse_line(line).unwrap().unwrap();
+		NQuads(&quad).to_string()
+	}
+
+	#[test]
+	fn escape_control_characters() {
+		let line = r#"<http://example.org/s> <http://example.org/p> "a\tb\bc\nd\re\ff\"g\\h\u0000i\u001Fj\u007Fké" ."#;
+		assert_eq!(reformat(line), "<http://example.org/s> <http://example.org/p> \"a\\tb\\bc\\nd\\re\\ff\\\"g\\\\h\\u0000i\\u001Fj\\u007Fk\u{e9}\" .");
+		assert_eq!(reformat(&reformat(line)), reformat(line))
+	}
+
+	#[test]
+	fn parse_document() {
+		let input = "# comment\n\n<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n_:a <http://example.org/p> \"b\"@en-US <http://example.org/g> . # trailing comment\n_:a <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> _:g .\n";
+		let dataset: Dataset<IriBuf> = parse(input).unwrap();
+		assert_eq!(dataset.len(), 3);
+
+		let language = dataset.iter().find_map(|quad| quad.object.as_literal().and_then(Literal::language_tag)).unwrap();
+		assert_eq!(language.as_str(), "en-US");
+		assert_eq!(language.normalized(), "en-us");
+
+		assert_eq!(to_string(&dataset), "<http://example.org/s> <http://example.org/p> <http://example.org/o> .\n_:a <http://example.org/p> \"b\"@en-US <http://example.org/g> .\n_:a <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> _:g .\n")
+	}
+
+	#[test]
+	fn xsd_string_is_implicit() {
+		assert_eq!(reformat("<http://example.org/s> <http://example.org/p> \"a\"^^<http://www.w3.org/2001/XMLSchema#string> ."), "<http://example.org/s> <http://example.org/p> \"a\" .")
+	}
+
+	#[test]
+	fn unicode_escapes() {
+		assert_eq!(reformat(r#"<http://example.org/\u00E9> <http://example.org/p> "\u00E9\U0001F600" ."#), "<http://example.org/\u{e9}> <http://example.org/p> \"\u{e9}\u{1f600}\" .")
+	}
+
+	#[test]
+	fn reader() {
+		let input = "<http://example.org/s> <http://example.org/p> \"a\" .\n\n<http://example.org/s> <http://example.org/p> \"b\" .\n";
+		let quads: Vec<Quad<IriBuf>> = Reader::new(input.as_bytes()).collect::<Result<_, _>>().unwrap();
+		assert_eq!(quads.len(), 2)
+	}
+
+	#[test]
+	fn syntax_errors() {
+		for line in &[
+			"<http://example.org/s> <http://example.org/p> \"a\"",
+			"<http://example.org/s> <http://example.org/p> \"a\" . <http://example.org/o>",
+			"<http://example.org/s> <http://example.org/p> \"a",
+			"<http://example.org/s> <http://example.org/p> \"a\\z\" .",
+			"<http://example.org/s> <http://example.org/p> \"a\"@ .",
+			"<http://example.org/s> <http://example.org/p> \"a\"@en- .",
+			"<http://example.org/s> <http://example.org/p> \"a\"@toolonglanguage .",
+			"<http://example.org/s> <http://example.org/p> \"a\"@en--US .",
+			"<http://example.org/s> <http://example.org/p> \"a\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#langString> .",
+			"\"a\" <http://example.org/p> <http://example.org/o> .",
+			"<not an iri> <http://example.org/p> <http://example.org/o> ."
+		] {
+			assert!(parse_line::<IriBuf>(line).is_err(), "`{}` should be rejected", line)
+		}
+
+		match parse::<IriBuf>("<http://example.org/s> <http://example.org/p> \"a\" .\n<http://example.org/s> <http://example.org/p> .") {
+			Err(Error::Syntax(2, _)) => (),
+			other => panic!("unexpected result {:?}", other.map(|dataset| dataset.len()))
+		}
+	}
+}
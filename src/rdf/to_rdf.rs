@@ -0,0 +1,385 @@
+use iref::Iri;
+use crate::{
+	Error,
+	Id,
+	BlankIdGenerator,
+	Reference,
+	Lenient,
+	Indexed,
+	Object,
+	object::{
+		Value,
+		Literal as JsonLiteral
+	},
+	flattening::{
+		generate_node_map,
+		NodeMapGraph
+	},
+	util
+};
+use super::*;
+
+/// Triple generated while converting a list or a compound literal.
+type Triple<T> = (Reference<T>, Reference<T>, Term<T>);
+
+/// Deserialize JSON-LD to RDF algorithm.
+///
+/// Convert the given expanded document into an RDF dataset.
+/// Nodes without identifier, list nodes and compound literals are given fresh blank node
+/// identifiers using the given generator.
+/// See <https://www.w3.org/TR/json-ld11-api/#deserialize-json-ld-to-rdf-algorithm>.
+pub fn to_rdf<'a, T: 'a + Id, I: IntoIterator<Item = &'a Indexed<Object<T>>>, G: BlankIdGenerator>(input: I, mut generator: G, options: Options) -> Result<Dataset<T>, Error> {
+	// Create a node map for the document.
+	let node_map = generate_node_map(input, &mut generator)?;
+	let mut dataset = Dataset::new();
+
+	// For each graph name and graph in node map ordered by graph name:
+	graph_to_rdf(&mut dataset, &mut generator, None, node_map.default_graph(), options);
+
+	let mut named_graphs: Vec<_> = node_map.named_graphs().collect();
+	named_graphs.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+	for (graph_name, graph) in named_graphs {
+		graph_to_rdf(&mut dataset, &mut generator, Some(graph_name), graph, options);
+	}
+
+	Ok(dataset)
+}
+
+/// Add the triples of the given graph to the dataset.
+fn graph_to_rdf<T: Id, G: BlankIdGenerator>(dataset: &mut Dataset<T>, generator: &mut G, graph_name: Option<&Reference<T>>, graph: &NodeMapGraph<T>, options: Options) {
+	// For each subject and node in graph ordered by subject:
+	let mut nodes: Vec<_> = graph.iter().collect();
+	nodes.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+	for (subject, node) in nodes {
+		// For each type in the @type entry of node, append a triple composed of subject,
+		// rdf:type, and type to triples.
+		for ty in node.types() {
+			// Types that are not well-formed IRIs or blank node identifiers are ignored.
+			if let Lenient::Ok(ty) = ty {
+				dataset.insert(Quad::new(subject.clone(), Reference::Id(vocab(RDF_TYPE)), Term::Ref(ty.clone()), graph_name.cloned()));
+			}
+		}
+
+		// For each property and values in node ordered by property:
+		let mut properties: Vec<_> = node.properties.iter().collect();
+		properties.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+		for (property, values) in properties {
+			// Otherwise, if property is a blank node identifier and the produceGeneralizedRdf
+			// option is not true, continue to the next property-values pair.
+			if let Reference::Blank(_) = property {
+				if !options.produce_generalized_rdf {
+					continue
+				}
+			}
+
+			for item in values {
+				// Initialize list triples as an empty array.
+				let mut list_triples = Vec::new();
+
+				// Add a triple composed of subject, property, and the result of using the
+				// Object to RDF Conversion algorithm passing item and list triples to
+				// triples, unless the result is null, indicating a non-well-formed resource
+				// that has to be ignored.
+				if let Some(object) = object_to_rdf(item, generator, &mut list_triples, options) {
+					dataset.insert(Quad::new(subject.clone(), property.clone(), object, graph_name.cloned()));
+				}
+
+				// Add all triples from list triples to triples.
+				for (s, p, o) in list_triples {
+					dataset.insert(Quad::new(s, p, o, graph_name.cloned()));
+				}
+			}
+		}
+	}
+}
+
+/// Object to RDF conversion algorithm.
+///
+/// Returns `None` if the item is a node reference that is not well-formed.
+/// Triples generated to represent lists and compound literals are pushed in `triples`.
+/// See <https://www.w3.org/TR/json-ld11-api/#object-to-rdf-conversion>.
+fn object_to_rdf<T: Id, G: BlankIdGenerator>(item: &Indexed<Object<T>>, generator: &mut G, triples: &mut Vec<Triple<T>>, options: Options) -> Option<Term<T>> {
+	match item.inner() {
+		// If item is a node object and the value of its @id entry is not well-formed, return
+		// null.
+		// If item is a node object, return the IRI or blank node identifier associated with
+		// its @id entry.
+		Object::Node(node) => match node.id() {
+			Some(Lenient::Ok(id)) => Some(Term::Ref(id.clone())),
+			_ => None
+		},
+		// If item is a list object return the result of the List Conversion algorithm,
+		// passing the value associated with the @list entry from item and list triples.
+		Object::List(list) => Some(Term::Ref(list_to_rdf(list, generator, triples, options))),
+		Object::Value(value) => value_to_rdf(value, generator, triples, options)
+	}
+}
+
+/// Value object to RDF conversion.
+///
+/// Part of the object to RDF conversion algorithm dealing with value objects.
+fn value_to_rdf<T: Id, G: BlankIdGenerator>(value: &Value<T>, generator: &mut G, triples: &mut Vec<Triple<T>>, options: Options) -> Option<Term<T>> {
+	match value {
+		Value::Literal(lit, types) => {
+			// Initialize datatype to the value associated with the @type entry of item or
+			// null if item does not have such an entry.
+			let datatype = types.iter().next();
+			let is_datatype = |iri: &str| datatype.map(|ty| ty.as_iri().as_str() == iri).unwrap_or(false);
+
+			let (value, datatype) = match lit {
+				JsonLiteral::Null => return None,
+				// If datatype is @json, convert value to the canonical lexical form using the
+				// result of transforming the internal representation of value to JSON and set
+				// datatype to rdf:JSON.
+				JsonLiteral::Json(json) => (util::canonical_json(json), vocab(RDF_JSON)),
+				// If value is true or false, set value to the string true or false which is
+				// the canonical lexical form as described in § 8.6 Data Round Tripping. If
+				// datatype is null, set datatype to xsd:boolean.
+				JsonLiteral::Boolean(b) => (if *b { "true" } else { "false" }.to_string(), datatype.cloned().unwrap_or_else(|| vocab(XSD_BOOLEAN))),
+				JsonLiteral::Number(n) => {
+					let f: f64 = (*n).into();
+					if f.fract() != 0.0 || f.abs() >= 1e21 || is_datatype(XSD_DOUBLE) {
+						// Otherwise, if value is a number with a non-zero fractional part (the
+						// result of a modulo‑1 operation) or an absolute value greater or equal
+						// to 10^21, or value is a number and datatype equals xsd:double, convert
+						// value to a string in canonical lexical form of an xsd:double as
+						// defined in [XMLSCHEMA11-2] and described in § 8.6 Data Round
+						// Tripping. If datatype is null, set datatype to xsd:double.
+						(canonical_double(f), datatype.cloned().unwrap_or_else(|| vocab(XSD_DOUBLE)))
+					} else {
+						// Otherwise, if value is a number with no non-zero fractional part
+						// (the result of a modulo‑1 operation) and datatype is not
+						// xsd:double, convert value to a string in canonical lexical form of
+						// an xsd:integer as defined in [XMLSCHEMA11-2] and described in § 8.6
+						// Data Round Tripping. If datatype is null, set datatype to
+						// xsd:integer.
+						(format!("{:.0}", f), datatype.cloned().unwrap_or_else(|| vocab(XSD_INTEGER)))
+					}
+				},
+				// Otherwise, if datatype is null, set datatype to xsd:string or
+				// rdf:langString, depending on if item has an @language entry.
+				JsonLiteral::String(s) => (s.clone(), datatype.cloned().unwrap_or_else(|| vocab(XSD_STRING)))
+			};
+
+			Some(Term::Literal(Literal::Typed(value, datatype)))
+		},
+		Value::LangString(lang_str) => {
+			let value = lang_str.as_str().to_string();
+
+			match (lang_str.direction(), options.rdf_direction) {
+				// If item contains an @direction entry and rdfDirection is i18n-datatype,
+				// set datatype to the result of appending the lowercased value of @language
+				// (if any) and the value of @direction, separated by an underscore, to
+				// https://www.w3.org/ns/i18n#.
+				(Some(direction), Some(RdfDirection::I18nDatatype)) => {
+					let language = lang_str.language_tag().map(|l| l.normalized()).unwrap_or_default();
+					let datatype = format!("{}{}_{}", I18N_BASE, language, direction);
+					match Iri::new(&datatype) {
+						Ok(datatype) => Some(Term::Literal(Literal::Typed(value, T::from_iri(datatype)))),
+						Err(_) => None
+					}
+				},
+				// Otherwise, if item contains an @direction entry and rdfDirection is
+				// compound-literal, create a new blank node literal with rdf:value,
+				// rdf:language (if any) and rdf:direction entries.
+				(Some(direction), Some(RdfDirection::CompoundLiteral)) => {
+					let literal = Reference::Blank(generator.generate());
+
+					triples.push((literal.clone(), Reference::Id(vocab(RDF_VALUE)), Term::Literal(Literal::Typed(value, vocab(XSD_STRING)))));
+
+					if let Some(language) = lang_str.language() {
+						triples.push((literal.clone(), Reference::Id(vocab(RDF_LANGUAGE)), Term::Literal(Literal::Typed(language.to_string(), vocab(XSD_STRING)))));
+					}
+
+					triples.push((literal.clone(), Reference::Id(vocab(RDF_DIRECTION)), Term::Literal(Literal::Typed(direction.to_string(), vocab(XSD_STRING)))));
+
+					Some(Term::Ref(literal))
+				},
+				// Otherwise, the direction is dropped.
+				_ => match lang_str.language() {
+					Some(language) => Some(Term::Literal(Literal::LangString(value, language.to_string()))),
+					None => Some(Term::Literal(Literal::Typed(value, vocab(XSD_STRING))))
+				}
+			}
+		}
+	}
+}
+
+/// List conversion algorithm.
+///
+/// Returns the head of the list (`rdf:nil` if the list is empty), and pushes the
+/// `rdf:first`/`rdf:rest` triples in `triples`.
+/// See <https://www.w3.org/TR/json-ld11-api/#list-to-rdf-conversion>.
+fn list_to_rdf<T: Id, G: BlankIdGenerator>(list: &[Indexed<Object<T>>], generator: &mut G, triples: &mut Vec<Triple<T>>, options: Options) -> Reference<T> {
+	// If list is empty, return rdf:nil.
+	if list.is_empty() {
+		return Reference::Id(vocab(RDF_NIL))
+	}
+
+	// Otherwise, create an array bnodes composed of a newly generated blank node identifier
+	// for each entry in list.
+	let bnodes: Vec<_> = list.iter().map(|_| Reference::Blank(generator.generate())).collect();
+
+	// For each pair of subject from bnodes and item from list:
+	for (i, item) in list.iter().enumerate() {
+		let subject = &bnodes[i];
+
+		// Initialize embedded triples to a new empty array.
+		let mut embedded_triples = Vec::new();
+
+		// Initialize object to the result of using the Object to RDF Conversion algorithm
+		// passing item and embedded triples for list triples.
+		// Unless object is null, append a triple composed of subject, rdf:first, and object
+		// to list triples.
+		if let Some(object) = object_to_rdf(item, generator, &mut embedded_triples, options) {
+			triples.push((subject.clone(), Reference::Id(vocab(RDF_FIRST)), object));
+		}
+
+		// Initialize rest as the next entry in bnodes, or if that does not exist, rdf:nil.
+		// Append a triple composed of subject, rdf:rest, and rest to list triples.
+		let rest = match bnodes.get(i + 1) {
+			Some(next) => next.clone(),
+			None => Reference::Id(vocab(RDF_NIL))
+		};
+
+		triples.push((subject.clone(), Reference::Id(vocab(RDF_REST)), Term::Ref(rest)));
+
+		// Append embedded triples to list triples.
+		triples.extend(embedded_triples);
+	}
+
+	// Return the first blank node from bnodes or rdf:nil if bnodes is empty.
+	bnodes[0].clone()
+}
+
+/// Canonical lexical form of a `xsd:double`.
+///
+/// For instance `1.1E0` or `1.0E21`.
+fn canonical_double(f: f64) -> String {
+	let s = format!("{:E}", f);
+	match s.find('E') {
+		Some(i) if !s[..i].contains('.') => format!("{}.0{}", &s[..i], &s[i..]),
+		_ => s
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::executor::block_on;
+	use crate::{
+		Document,
+		NoLoader,
+		BlankIdCounter,
+		expansion,
+		context::JsonContext
+	};
+	use super::*;
+
+	/// Convert the given document into N-Quads, sorted line by line.
+	fn to_nquads(doc: &str, options: Options) -> Vec<String> {
+		let doc = json::parse(doc).unwrap();
+		let context: JsonContext = JsonContext::new(None);
+		let expanded = block_on(doc.expand_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+		let dataset = to_rdf(&expanded, BlankIdCounter::default(), options).unwrap();
+		let mut lines: Vec<_> = nquads::to_string(&dataset).lines().map(|line| line.to_string()).collect();
+		lines.sort();
+		lines
+	}
+
+	#[test]
+	fn literals() {
+		let doc = r#"{
+			"@id": "http://example.org/s",
+			"http://example.org/p": [
+				true,
+				42,
+				1.5,
+				{"@value": 2, "@type": "http://www.w3.org/2001/XMLSchema#double"},
+				"a",
+				{"@value": "b", "@language": "en"},
+				{"@value": null}
+			]
+		}"#;
+
+		assert_eq!(to_nquads(doc, Options::default()), vec![
+			"<http://example.org/s> <http://example.org/p> \"1.5E0\"^^<http://www.w3.org/2001/XMLSchema#double> .",
+			"<http://example.org/s> <http://example.org/p> \"2.0E0\"^^<http://www.w3.org/2001/XMLSchema#double> .",
+			"<http://example.org/s> <http://example.org/p> \"42\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+			"<http://example.org/s> <http://example.org/p> \"a\" .",
+			"<http://example.org/s> <http://example.org/p> \"b\"@en .",
+			"<http://example.org/s> <http://example.org/p> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> ."
+		])
+	}
+
+	#[test]
+	fn json_literal() {
+		let doc = r#"{
+			"@id": "http://example.org/s",
+			"http://example.org/p": {"@value": {"b": 1.0, "a": [1e21, "x"]}, "@type": "@json"}
+		}"#;
+
+		assert_eq!(to_nquads(doc, Options::default()), vec![
+			"<http://example.org/s> <http://example.org/p> \"{\\\"a\\\":[1e+21,\\\"x\\\"],\\\"b\\\":1}\"^^<http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON> ."
+		])
+	}
+
+	#[test]
+	fn lists() {
+		let doc = r#"{
+			"@id": "http://example.org/s",
+			"http://example.org/p": {"@list": ["a", "b"]},
+			"http://example.org/q": {"@list": []}
+		}"#;
+
+		assert_eq!(to_nquads(doc, Options::default()), vec![
+			"<http://example.org/s> <http://example.org/p> _:b0 .",
+			"<http://example.org/s> <http://example.org/q> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> .",
+			"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"a\" .",
+			"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:b1 .",
+			"_:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> \"b\" .",
+			"_:b1 <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil> ."
+		])
+	}
+
+	#[test]
+	fn named_graphs_and_types() {
+		let doc = r#"{
+			"@id": "http://example.org/g",
+			"@graph": {"@id": "http://example.org/s", "@type": "http://example.org/T"}
+		}"#;
+
+		assert_eq!(to_nquads(doc, Options::default()), vec![
+			"<http://example.org/s> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.org/T> <http://example.org/g> ."
+		])
+	}
+
+	#[test]
+	fn directions() {
+		let doc = r#"{
+			"@id": "http://example.org/s",
+			"http://example.org/p": {"@value": "a", "@language": "en-US", "@direction": "rtl"}
+		}"#;
+
+		assert_eq!(to_nquads(doc, Options::default()), vec![
+			"<http://example.org/s> <http://example.org/p> \"a\"@en-US ."
+		]);
+
+		let mut options = Options {
+			rdf_direction: Some(RdfDirection::I18nDatatype),
+			..Options::default()
+		};
+		assert_eq!(to_nquads(doc, options), vec![
+			"<http://example.org/s> <http://example.org/p> \"a\"^^<https://www.w3.org/ns/i18n#en-us_rtl> ."
+		]);
+
+		options.rdf_direction = Some(RdfDirection::CompoundLiteral);
+		assert_eq!(to_nquads(doc, options), vec![
+			"<http://example.org/s> <http://example.org/p> _:b0 .",
+			"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#direction> \"rtl\" .",
+			"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#language> \"en-US\" .",
+			"_:b0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#value> \"a\" ."
+		])
+	}
+}
@@ -8,14 +8,24 @@ use crate::{
 	Error,
 	ErrorCode,
 	RemoteDocument,
+	generic_json::Json,
 	context::{
 		self,
 		RemoteContext
 	}
 };
 
+/// Checks if the given `Content-Type` header value is a JSON media type.
+///
+/// This is `application/json`, or any media type with the `+json` suffix (such as
+/// `application/ld+json`), ignoring parameters (such as `charset` or `profile`).
 pub fn is_json_media_type(ty: &str) -> bool {
-	ty == "application/json" || ty == "application/ld+json"
+	let ty = match ty.find(';') {
+		Some(i) => &ty[..i],
+		None => ty
+	}.trim().to_ascii_lowercase();
+
+	ty == "application/json" || (ty.starts_with("application/") && ty.ends_with("+json"))
 }
 
 pub async fn load_remote_json_ld_document(url: Iri<'_>) -> Result<RemoteDocument, Error> {
@@ -37,10 +47,12 @@ pub async fn load_remote_json_ld_document(url: Iri<'_>) -> Result<RemoteDocument
 
 		match json::parse(body.as_str()) {
 			Ok(doc) => Ok(RemoteDocument::new(doc, url.into())),
-			Err(e) => panic!("invalid json: {:?}: {}", e, body.as_str())
+			Err(e) => Err(Error::new(ErrorCode::LoadingDocumentFailed, e))
 		}
 	} else {
-		panic!("not a json document")
+		// The retrieved resource's `Content-Type` is not a JSON media type:
+		// a loading document failed error has been detected.
+		Err(ErrorCode::LoadingDocumentFailed.into())
 	}
 }
 
@@ -79,7 +91,7 @@ impl context::Loader for Loader {
 			match self.load(url.as_iri()).await {
 				Ok(remote_doc) => {
 					let (doc, url) = remote_doc.into_parts();
-					if let JsonValue::Object(obj) = doc {
+					if let Some(obj) = doc.as_object() {
 						if let Some(context) = obj.get("@context") {
 							Ok(RemoteContext::from_parts(url, context.clone()))
 						} else {
@@ -89,8 +101,8 @@ impl context::Loader for Loader {
 						Err(ErrorCode::InvalidRemoteContext.into())
 					}
 				},
-				Err(_) => {
-					Err(ErrorCode::LoadingRemoteContextFailed.into())
+				Err(e) => {
+					Err(Error::new(ErrorCode::LoadingRemoteContextFailed, e))
 				}
 			}
 		}.boxed()
@@ -102,3 +114,20 @@ impl From<reqwest::Error> for Error {
 		Error::new(ErrorCode::LoadingDocumentFailed, e)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn json_media_types() {
+		for ty in &["application/json", "application/ld+json", "application/ld+json; profile=\"http://www.w3.org/ns/json-ld#expanded\"", "Application/JSON; charset=utf-8", "application/activity+json"] {
+			assert!(is_json_media_type(ty), "`{}` is a JSON media type", ty)
+		}
+
+		// Documents of other types fail to load instead of panicking.
+		for ty in &["text/html", "application/xml", "text/json+xml", "application/jsonp"] {
+			assert!(!is_json_media_type(ty), "`{}` is not a JSON media type", ty)
+		}
+	}
+}
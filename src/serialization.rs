@@ -0,0 +1,797 @@
+//! Serde support for the object model.
+//!
+//! Objects are serialized in their expanded JSON-LD form, as given by [`AsJson`], and
+//! deserialized from it.
+//! Human-readable formats (such as JSON) receive the JSON structure as is.
+//! Compact formats (such as CBOR or `bincode`) may not be able to deserialize a value
+//! without knowing its type in advance, so each JSON value is serialized as a tagged
+//! enumeration (`Null`, `Boolean`, `Number`, `String`, `Array` or `Object`) instead.
+//!
+//! Since expanded documents do not distinguish between strings and language strings without
+//! language or direction, a `@value` string without `@type` is always deserialized as a
+//! [`LangString`].
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use iref::Iri;
+use json::{
+	JsonValue,
+	number::Number
+};
+use serde::{
+	Serialize,
+	Serializer,
+	Deserialize,
+	Deserializer,
+	ser::{
+		SerializeSeq,
+		SerializeMap
+	},
+	de::{
+		self,
+		Visitor,
+		SeqAccess,
+		MapAccess,
+		EnumAccess,
+		VariantAccess
+	}
+};
+use crate::{
+	Id,
+	BlankId,
+	Reference,
+	Lenient,
+	Indexed,
+	LangString,
+	Direction,
+	Object,
+	Node,
+	Value,
+	object::Literal,
+	util::{
+		AsJson,
+		as_array
+	}
+};
+
+/// Name of the tagged JSON enumeration, used by non-human-readable formats.
+const JSON_ENUM: &str = "Json";
+
+/// Variants of the tagged JSON enumeration.
+const JSON_VARIANTS: &[&str] = &["Null", "Boolean", "Number", "String", "Array", "Object"];
+
+/// Serializable JSON value.
+struct SerializeJson<'a>(&'a JsonValue);
+
+/// Serializable JSON array.
+struct SerializeJsonArray<'a>(&'a [JsonValue]);
+
+/// Serializable JSON object.
+struct SerializeJsonObject<'a>(&'a json::object::Object);
+
+impl<'a> Serialize for SerializeJson<'a> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		if serializer.is_human_readable() {
+			match self.0 {
+				JsonValue::Null => serializer.serialize_unit(),
+				JsonValue::Boolean(b) => serializer.serialize_bool(*b),
+				JsonValue::Number(n) => {
+					// Integers are serialized as such to avoid any loss of precision.
+					let (positive, mantissa, exponent) = n.as_parts();
+					if exponent == 0 {
+						if positive {
+							return serializer.serialize_u64(mantissa)
+						} else if mantissa <= i64::MAX as u64 {
+							return serializer.serialize_i64(-(mantissa as i64))
+						}
+					}
+
+					serializer.serialize_f64((*n).into())
+				},
+				JsonValue::Short(_) | JsonValue::String(_) => serializer.serialize_str(self.0.as_str().unwrap()),
+				JsonValue::Array(items) => SerializeJsonArray(items).serialize(serializer),
+				JsonValue::Object(obj) => SerializeJsonObject(obj).serialize(serializer)
+			}
+		} else {
+			match self.0 {
+				JsonValue::Null => serializer.serialize_unit_variant(JSON_ENUM, 0, JSON_VARIANTS[0]),
+				JsonValue::Boolean(b) => serializer.serialize_newtype_variant(JSON_ENUM, 1, JSON_VARIANTS[1], b),
+				JsonValue::Number(n) => serializer.serialize_newtype_variant(JSON_ENUM, 2, JSON_VARIANTS[2], &n.as_parts()),
+				JsonValue::Short(_) | JsonValue::String(_) => serializer.serialize_newtype_variant(JSON_ENUM, 3, JSON_VARIANTS[3], self.0.as_str().unwrap()),
+				JsonValue::Array(items) => serializer.serialize_newtype_variant(JSON_ENUM, 4, JSON_VARIANTS[4], &SerializeJsonArray(items)),
+				JsonValue::Object(obj) => serializer.serialize_newtype_variant(JSON_ENUM, 5, JSON_VARIANTS[5], &SerializeJsonObject(obj))
+			}
+		}
+	}
+}
+
+impl<'a> Serialize for SerializeJsonArray<'a> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+		for item in self.0 {
+			seq.serialize_element(&SerializeJson(item))?
+		}
+
+		seq.end()
+	}
+}
+
+impl<'a> Serialize for SerializeJsonObject<'a> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.0.len()))?;
+		for (key, value) in self.0.iter() {
+			map.serialize_entry(key, &SerializeJson(value))?
+		}
+
+		map.end()
+	}
+}
+
+/// Deserialized JSON value.
+struct DeserializeJson(JsonValue);
+
+/// Deserialized JSON object.
+struct DeserializeJsonObject(JsonValue);
+
+/// Visitor for untagged JSON values.
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+	type Value = JsonValue;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a JSON value")
+	}
+
+	fn visit_unit<E: de::Error>(self) -> Result<JsonValue, E> {
+		Ok(JsonValue::Null)
+	}
+
+	fn visit_none<E: de::Error>(self) -> Result<JsonValue, E> {
+		Ok(JsonValue::Null)
+	}
+
+	fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+		DeserializeJson::deserialize(deserializer).map(|json| json.0)
+	}
+
+	fn visit_bool<E: de::Error>(self, b: bool) -> Result<JsonValue, E> {
+		Ok(JsonValue::Boolean(b))
+	}
+
+	fn visit_i64<E: de::Error>(self, n: i64) -> Result<JsonValue, E> {
+		Ok(JsonValue::Number(n.into()))
+	}
+
+	fn visit_u64<E: de::Error>(self, n: u64) -> Result<JsonValue, E> {
+		Ok(JsonValue::Number(n.into()))
+	}
+
+	fn visit_f64<E: de::Error>(self, n: f64) -> Result<JsonValue, E> {
+		Ok(JsonValue::Number(n.into()))
+	}
+
+	fn visit_str<E: de::Error>(self, s: &str) -> Result<JsonValue, E> {
+		Ok(s.into())
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+		let mut items = Vec::new();
+		while let Some(DeserializeJson(item)) = seq.next_element()? {
+			items.push(item)
+		}
+
+		Ok(JsonValue::Array(items))
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+		let mut obj = json::object::Object::new();
+		while let Some((key, DeserializeJson(value))) = map.next_entry::<String, DeserializeJson>()? {
+			obj.insert(&key, value)
+		}
+
+		Ok(JsonValue::Object(obj))
+	}
+}
+
+/// Variant of the tagged JSON enumeration.
+enum JsonTag {
+	Null,
+	Boolean,
+	Number,
+	String,
+	Array,
+	Object
+}
+
+impl JsonTag {
+	fn from_index(i: u64) -> Option<JsonTag> {
+		match i {
+			0 => Some(JsonTag::Null),
+			1 => Some(JsonTag::Boolean),
+			2 => Some(JsonTag::Number),
+			3 => Some(JsonTag::String),
+			4 => Some(JsonTag::Array),
+			5 => Some(JsonTag::Object),
+			_ => None
+		}
+	}
+}
+
+struct JsonTagVisitor;
+
+impl<'de> Visitor<'de> for JsonTagVisitor {
+	type Value = JsonTag;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a JSON value variant")
+	}
+
+	fn visit_u64<E: de::Error>(self, i: u64) -> Result<JsonTag, E> {
+		JsonTag::from_index(i).ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(i), &self))
+	}
+
+	fn visit_str<E: de::Error>(self, name: &str) -> Result<JsonTag, E> {
+		match JSON_VARIANTS.iter().position(|variant| *variant == name) {
+			Some(i) => Ok(JsonTag::from_index(i as u64).unwrap()),
+			None => Err(E::unknown_variant(name, JSON_VARIANTS))
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for JsonTag {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<JsonTag, D::Error> {
+		deserializer.deserialize_identifier(JsonTagVisitor)
+	}
+}
+
+/// Visitor for tagged JSON values.
+struct TaggedJsonVisitor;
+
+impl<'de> Visitor<'de> for TaggedJsonVisitor {
+	type Value = JsonValue;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "a tagged JSON value")
+	}
+
+	fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<JsonValue, A::Error> {
+		let (tag, variant) = data.variant()?;
+		match tag {
+			JsonTag::Null => {
+				variant.unit_variant()?;
+				Ok(JsonValue::Null)
+			},
+			JsonTag::Boolean => Ok(JsonValue::Boolean(variant.newtype_variant()?)),
+			JsonTag::Number => {
+				let (positive, mantissa, exponent) = variant.newtype_variant()?;
+				Ok(JsonValue::Number(Number::from_parts(positive, mantissa, exponent)))
+			},
+			JsonTag::String => Ok(JsonValue::String(variant.newtype_variant()?)),
+			JsonTag::Array => {
+				let items: Vec<DeserializeJson> = variant.newtype_variant()?;
+				Ok(JsonValue::Array(items.into_iter().map(|item| item.0).collect()))
+			},
+			JsonTag::Object => {
+				let DeserializeJsonObject(obj) = variant.newtype_variant()?;
+				Ok(obj)
+			}
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for DeserializeJson {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<DeserializeJson, D::Error> {
+		if deserializer.is_human_readable() {
+			deserializer.deserialize_any(JsonVisitor).map(DeserializeJson)
+		} else {
+			deserializer.deserialize_enum(JSON_ENUM, JSON_VARIANTS, TaggedJsonVisitor).map(DeserializeJson)
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for DeserializeJsonObject {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<DeserializeJsonObject, D::Error> {
+		deserializer.deserialize_map(JsonVisitor).map(DeserializeJsonObject)
+	}
+}
+
+/// Serialize any value through its JSON representation.
+fn serialize_as_json<V: AsJson + ?Sized, S: Serializer>(value: &V, serializer: S) -> Result<S::Ok, S::Error> {
+	SerializeJson(&value.as_json()).serialize(serializer)
+}
+
+/// Deserialize a JSON value.
+fn deserialize_json<'de, D: Deserializer<'de>>(deserializer: D) -> Result<JsonValue, D::Error> {
+	DeserializeJson::deserialize(deserializer).map(|json| json.0)
+}
+
+fn invalid<E: de::Error>(json: &JsonValue, expected: &str) -> E {
+	E::custom(format!("invalid expanded JSON-LD, expected {}: {}", expected, json.dump()))
+}
+
+fn as_object<'a, E: de::Error>(json: &'a JsonValue, expected: &str) -> Result<&'a json::object::Object, E> {
+	match json {
+		JsonValue::Object(obj) => Ok(obj),
+		_ => Err(invalid(json, expected))
+	}
+}
+
+fn as_str<'a, E: de::Error>(json: &'a JsonValue, expected: &str) -> Result<&'a str, E> {
+	json.as_str().ok_or_else(|| invalid(json, expected))
+}
+
+/// Parse a node reference (IRI or blank node identifier).
+fn reference_from_str<T: Id>(s: &str) -> Option<Reference<T>> {
+	match BlankId::try_from(s) {
+		Ok(blank) => Some(Reference::Blank(blank)),
+		Err(_) => match Iri::new(s) {
+			Ok(iri) => Some(Reference::Id(T::from_iri(iri))),
+			Err(_) => None
+		}
+	}
+}
+
+fn reference_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Reference<T>, E> {
+	let s = as_str(json, "a node reference")?;
+	reference_from_str(s).ok_or_else(|| invalid(json, "an IRI or blank node identifier"))
+}
+
+fn lenient_reference_from_str<T: Id>(s: &str) -> Lenient<Reference<T>> {
+	match reference_from_str(s) {
+		Some(r) => Lenient::Ok(r),
+		None => Lenient::Unknown(s.to_string())
+	}
+}
+
+fn index_from_json<E: de::Error>(obj: &json::object::Object) -> Result<Option<String>, E> {
+	match obj.get("@index") {
+		Some(index) => Ok(Some(as_str(index, "an `@index` string")?.to_string())),
+		None => Ok(None)
+	}
+}
+
+fn lang_string_from_json<E: de::Error>(json: &JsonValue) -> Result<LangString, E> {
+	let obj = as_object(json, "a value object")?;
+	let mut value = None;
+	let mut language = None;
+	let mut direction = None;
+
+	for (key, entry) in obj.iter() {
+		match key {
+			"@value" => value = Some(as_str(entry, "a string value")?.to_string()),
+			"@language" => language = Some(as_str(entry, "a language tag")?.to_string()),
+			"@direction" => {
+				let dir = as_str(entry, "a base direction")?;
+				direction = Some(Direction::try_from(dir).map_err(|_| invalid(entry, "a base direction"))?)
+			},
+			"@index" => (),
+			_ => return Err(invalid(json, "a language string"))
+		}
+	}
+
+	match value {
+		Some(value) => Ok(LangString::new(value, language, direction)),
+		None => Err(invalid(json, "a language string"))
+	}
+}
+
+fn value_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Value<T>, E> {
+	let obj = as_object(json, "a value object")?;
+	if obj.get("@language").is_some() || obj.get("@direction").is_some() {
+		return Ok(Value::LangString(lang_string_from_json(json)?))
+	}
+
+	let value = obj.get("@value").ok_or_else(|| invalid(json, "a value object"))?;
+	let mut is_json = false;
+	let mut types = HashSet::new();
+	if let Some(tys) = obj.get("@type") {
+		for ty in as_array(tys) {
+			match as_str(ty, "a type IRI")? {
+				"@json" => is_json = true,
+				ty_str => match Iri::new(ty_str) {
+					Ok(iri) => {
+						types.insert(T::from_iri(iri));
+					},
+					Err(_) => return Err(invalid(ty, "a type IRI"))
+				}
+			}
+		}
+	}
+
+	let lit = if is_json {
+		Literal::Json(value.clone())
+	} else {
+		match value {
+			JsonValue::Null => Literal::Null,
+			JsonValue::Boolean(b) => Literal::Boolean(*b),
+			JsonValue::Number(n) => Literal::Number(*n),
+			JsonValue::Short(_) | JsonValue::String(_) => {
+				if types.is_empty() {
+					return Ok(Value::LangString(LangString::new(value.as_str().unwrap().to_string(), None, None)))
+				}
+
+				Literal::String(value.as_str().unwrap().to_string())
+			},
+			_ => return Err(invalid(value, "a literal value"))
+		}
+	};
+
+	for (key, _) in obj.iter() {
+		match key {
+			"@value" | "@type" | "@index" => (),
+			_ => return Err(invalid(json, "a value object"))
+		}
+	}
+
+	Ok(Value::Literal(lit, types))
+}
+
+fn objects_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Vec<Indexed<Object<T>>>, E> {
+	as_array(json).iter().map(object_from_json).collect()
+}
+
+fn nodes_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Vec<Indexed<Node<T>>>, E> {
+	as_array(json).iter().map(node_from_json).collect()
+}
+
+fn node_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Indexed<Node<T>>, E> {
+	let obj = as_object(json, "a node object")?;
+	let mut node = Node::new();
+
+	for (key, value) in obj.iter() {
+		match key {
+			"@id" => node.id = Some(lenient_reference_from_str(as_str(value, "an `@id` string")?)),
+			"@type" => {
+				for ty in as_array(value) {
+					node.types.push(lenient_reference_from_str(as_str(ty, "a type")?))
+				}
+			},
+			"@graph" => node.graph = Some(Box::new(objects_from_json(value)?.into_iter().collect())),
+			"@included" => node.included = Some(Box::new(nodes_from_json(value)?.into_iter().collect())),
+			"@reverse" => {
+				for (reverse_key, reverse_value) in as_object(value, "a reverse properties map")?.iter() {
+					let prop = reference_from_json(&JsonValue::from(reverse_key))?;
+					node.insert_all_reverse(prop, nodes_from_json(reverse_value)?.into_iter())
+				}
+			},
+			"@index" => (),
+			_ => {
+				let prop = reference_from_json(&JsonValue::from(key))?;
+				node.insert_all(prop, objects_from_json(value)?.into_iter())
+			}
+		}
+	}
+
+	Ok(Indexed::new(node, index_from_json(obj)?))
+}
+
+fn object_from_json<T: Id, E: de::Error>(json: &JsonValue) -> Result<Indexed<Object<T>>, E> {
+	let obj = as_object(json, "an object")?;
+	if obj.get("@value").is_some() {
+		Ok(Indexed::new(Object::Value(value_from_json(json)?), index_from_json(obj)?))
+	} else if let Some(list) = obj.get("@list") {
+		for (key, _) in obj.iter() {
+			match key {
+				"@list" | "@index" => (),
+				_ => return Err(invalid(json, "a list object"))
+			}
+		}
+
+		Ok(Indexed::new(Object::List(objects_from_json(list)?), index_from_json(obj)?))
+	} else {
+		Ok(node_from_json(json)?.cast())
+	}
+}
+
+impl Serialize for BlankId {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.as_str())
+	}
+}
+
+impl<'de> Deserialize<'de> for BlankId {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BlankId, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		BlankId::try_from(s.as_str()).map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), &"a blank node identifier"))
+	}
+}
+
+impl<T: Id> Serialize for Reference<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.as_str())
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Reference<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Reference<T>, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		reference_from_str(&s).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&s), &"an IRI or blank node identifier"))
+	}
+}
+
+impl<T: Serialize> Serialize for Lenient<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Lenient::Ok(value) => value.serialize(serializer),
+			Lenient::Unknown(s) => serializer.serialize_str(s)
+		}
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Lenient<Reference<T>> {
+	/// Malformed references are deserialized as [`Lenient::Unknown`].
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Lenient<Reference<T>>, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		Ok(lenient_reference_from_str(&s))
+	}
+}
+
+impl Serialize for LangString {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_as_json(self, serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for LangString {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LangString, D::Error> {
+		lang_string_from_json(&deserialize_json(deserializer)?)
+	}
+}
+
+impl<T: Id> Serialize for Value<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_as_json(self, serializer)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Value<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value<T>, D::Error> {
+		value_from_json(&deserialize_json(deserializer)?)
+	}
+}
+
+impl<T: Id> Serialize for Node<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_as_json(self, serializer)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Node<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Node<T>, D::Error> {
+		let json = deserialize_json(deserializer)?;
+		let node = node_from_json(&json)?;
+		if node.index().is_some() {
+			return Err(invalid(&json, "a node object without `@index`"))
+		}
+
+		Ok(node.into_inner())
+	}
+}
+
+impl<T: Id> Serialize for Object<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_as_json(self, serializer)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Object<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Object<T>, D::Error> {
+		let json = deserialize_json(deserializer)?;
+		let object = object_from_json(&json)?;
+		if object.index().is_some() {
+			return Err(invalid(&json, "an object without `@index`"))
+		}
+
+		Ok(object.into_inner())
+	}
+}
+
+impl<T: AsJson> Serialize for Indexed<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serialize_as_json(self, serializer)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Indexed<Object<T>> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Indexed<Object<T>>, D::Error> {
+		object_from_json(&deserialize_json(deserializer)?)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Indexed<Node<T>> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Indexed<Node<T>>, D::Error> {
+		node_from_json(&deserialize_json(deserializer)?)
+	}
+}
+
+impl<'de, T: Id> Deserialize<'de> for Indexed<Value<T>> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Indexed<Value<T>>, D::Error> {
+		let json = deserialize_json(deserializer)?;
+		let index = index_from_json(as_object(&json, "a value object")?)?;
+		Ok(Indexed::new(value_from_json(&json)?, index))
+	}
+}
+
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+	use iref::IriBuf;
+	use serde::de::DeserializeOwned;
+	use serde_json::json;
+	use super::*;
+
+	/// Deserialize the given JSON value, check that it serializes back to the same value, and
+	/// that serializing it to a string and parsing it again gives the same object.
+	fn round_trip<T: Serialize + DeserializeOwned + PartialEq>(json: serde_json::Value) -> T {
+		let value: T = serde_json::from_value(json.clone()).unwrap();
+		assert_eq!(serde_json::to_value(&value).unwrap(), json);
+
+		let parsed: T = serde_json::from_str(&serde_json::to_string(&value).unwrap()).unwrap();
+		assert!(parsed == value);
+		value
+	}
+
+	#[test]
+	fn node() {
+		let node: Node<IriBuf> = round_trip(json!({
+			"@id": "http://example.org/a",
+			"@type": ["http://example.org/T"],
+			"http://example.org/name": [{"@value": "A"}],
+			"http://example.org/knows": [{"@id": "_:b"}],
+			"@reverse": {
+				"http://example.org/parent": [{"@id": "http://example.org/c"}]
+			}
+		}));
+
+		assert_eq!(node.id().unwrap().as_str(), "http://example.org/a");
+		assert_eq!(node.types().len(), 1);
+		assert!(!node.reverse_properties.is_empty())
+	}
+
+	#[test]
+	fn graph_and_included() {
+		round_trip::<Node<IriBuf>>(json!({
+			"@id": "http://example.org/g",
+			"@graph": [{"@id": "http://example.org/a", "http://example.org/p": [{"@value": true}]}],
+			"@included": [{"@id": "http://example.org/b"}]
+		}));
+	}
+
+	#[test]
+	fn typed_value() {
+		let value: Value<IriBuf> = round_trip(json!({
+			"@value": "2020-01-01",
+			"@type": "http://www.w3.org/2001/XMLSchema#date"
+		}));
+
+		match value {
+			Value::Literal(Literal::String(s), types) => {
+				assert_eq!(s, "2020-01-01");
+				assert_eq!(types.len(), 1)
+			},
+			_ => panic!("expected a typed literal")
+		}
+
+		round_trip::<Value<IriBuf>>(json!({"@value": 1.5}));
+		round_trip::<Value<IriBuf>>(json!({"@value": null}));
+	}
+
+	#[test]
+	fn lang_string() {
+		let value: Value<IriBuf> = round_trip(json!({
+			"@value": "chat",
+			"@language": "fr",
+			"@direction": "ltr"
+		}));
+
+		match value {
+			Value::LangString(s) => {
+				assert_eq!(s.as_str(), "chat");
+				assert_eq!(s.language(), Some("fr"));
+				assert_eq!(s.direction(), Some(Direction::Ltr))
+			},
+			_ => panic!("expected a language string")
+		}
+
+		// A string without type is a language string without language.
+		let value: Value<IriBuf> = round_trip(json!({"@value": "plain"}));
+		assert!(matches!(value, Value::LangString(_)));
+
+		round_trip::<LangString>(json!({"@value": "hello", "@language": "en"}));
+	}
+
+	#[test]
+	fn json_literal() {
+		let value: Value<IriBuf> = round_trip(json!({
+			"@value": {"a": [1, true, null], "b": "c"},
+			"@type": "@json"
+		}));
+
+		assert!(matches!(value, Value::Literal(Literal::Json(_), _)));
+
+		// JSON literals are compared regardless of the order of their entries.
+		let other: Value<IriBuf> = serde_json::from_str(r#"{"@value": {"b": "c", "a": [1, true, null]}, "@type": "@json"}"#).unwrap();
+		assert!(other == value)
+	}
+
+	#[test]
+	fn list() {
+		let object: Object<IriBuf> = round_trip(json!({
+			"@list": [
+				{"@value": "a"},
+				{"@id": "http://example.org/b"},
+				{"@list": []}
+			]
+		}));
+
+		match object {
+			Object::List(items) => assert_eq!(items.len(), 3),
+			_ => panic!("expected a list")
+		}
+	}
+
+	#[test]
+	fn lenient_unknown() {
+		let reference: Lenient<Reference<IriBuf>> = serde_json::from_value(json!("not an IRI")).unwrap();
+		assert!(matches!(&reference, Lenient::Unknown(s) if s == "not an IRI"));
+		assert_eq!(serde_json::to_value(&reference).unwrap(), json!("not an IRI"));
+
+		let node: Node<IriBuf> = round_trip(json!({
+			"@id": "unknown",
+			"@type": ["Unknown"]
+		}));
+
+		assert!(matches!(node.id(), Some(Lenient::Unknown(_))));
+		assert!(matches!(node.types()[0], Lenient::Unknown(_)))
+	}
+
+	#[test]
+	fn indexed() {
+		let object: Indexed<Object<IriBuf>> = round_trip(json!({
+			"@id": "http://example.org/a",
+			"@index": "first"
+		}));
+
+		assert_eq!(object.index(), Some("first"));
+
+		let node: Indexed<Node<IriBuf>> = round_trip(json!({"@id": "http://example.org/a", "@index": "i"}));
+		assert_eq!(node.index(), Some("i"));
+
+		let value: Indexed<Value<IriBuf>> = round_trip(json!({"@value": 1, "@index": "v"}));
+		assert_eq!(value.index(), Some("v"));
+
+		let list: Indexed<Object<IriBuf>> = round_trip(json!({"@list": [{"@value": 1}], "@index": "l"}));
+		assert_eq!(list.index(), Some("l"))
+	}
+
+	#[test]
+	fn malformed() {
+		// Unexpected value object entry.
+		assert!(serde_json::from_value::<Value<IriBuf>>(json!({"@value": 1, "@foo": 2})).is_err());
+
+		// Not a value object.
+		assert!(serde_json::from_value::<Value<IriBuf>>(json!("value")).is_err());
+
+		// Invalid type IRI.
+		assert!(serde_json::from_value::<Value<IriBuf>>(json!({"@value": "a", "@type": "not an IRI"})).is_err());
+
+		// Invalid base direction.
+		assert!(serde_json::from_value::<LangString>(json!({"@value": "a", "@direction": "up"})).is_err());
+
+		// Invalid property.
+		assert!(serde_json::from_value::<Node<IriBuf>>(json!({"not an IRI": [{"@value": 1}]})).is_err());
+
+		// Unexpected list object entry.
+		assert!(serde_json::from_value::<Object<IriBuf>>(json!({"@list": [], "@id": "http://example.org/a"})).is_err());
+
+		// Non-indexed objects must not have an `@index`.
+		assert!(serde_json::from_value::<Object<IriBuf>>(json!({"@id": "http://example.org/a", "@index": "i"})).is_err());
+		assert!(serde_json::from_value::<Node<IriBuf>>(json!({"@id": "http://example.org/a", "@index": "i"})).is_err());
+
+		// Malformed blank node identifier.
+		assert!(serde_json::from_value::<BlankId>(json!("b0")).is_err());
+		assert!(serde_json::from_value::<Reference<IriBuf>>(json!("not an IRI")).is_err())
+	}
+}
@@ -1,4 +1,6 @@
 use std::convert::TryFrom;
+use json::JsonValue;
+use crate::util;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ContainerType {
@@ -11,6 +13,21 @@ pub enum ContainerType {
 	Type
 }
 
+impl ContainerType {
+	pub fn into_str(self) -> &'static str {
+		use ContainerType::*;
+		match self {
+			Graph => "@graph",
+			Id => "@id",
+			Index => "@index",
+			Language => "@language",
+			List => "@list",
+			Set => "@set",
+			Type => "@type"
+		}
+	}
+}
+
 impl<'a> TryFrom<&'a str> for ContainerType {
 	type Error = &'a str;
 
@@ -49,6 +66,10 @@ impl Container {
 		self.0.contains(&c)
 	}
 
+	pub fn as_slice(&self) -> &[ContainerType] {
+		self.0.as_ref()
+	}
+
 	pub fn add(&mut self, c: ContainerType) -> bool {
 		if self.is_empty() {
 			self.0.push(c);
@@ -98,3 +119,9 @@ impl Container {
 		}
 	}
 }
+
+impl util::AsJson for Container {
+	fn as_json(&self) -> JsonValue {
+		JsonValue::Array(self.0.iter().map(|c| c.into_str().into()).collect())
+	}
+}
@@ -19,11 +19,26 @@ pub enum Keyword {
 	/// Used to define the short-hand names that are used throughout a JSON-LD document.
 	Context,
 
+	/// `@default`.
+	/// Used in a frame to set the default value of a property, or type, that is not present
+	/// in the matched node.
+	Default,
+
 	/// `@direction`.
 	/// Used to set the base direction of a JSON-LD value, which are not typed values.
 	/// (e.g. strings, or language-tagged strings).
 	Direction,
 
+	/// `@embed`.
+	/// Used in a frame to specify how matched nodes are embedded in the output.
+	/// Either `@always`, `@once` or `@never`.
+	Embed,
+
+	/// `@explicit`.
+	/// Used in a frame to specify that only the properties present in the frame must be
+	/// included in the output.
+	Explicit,
+
 	/// `@graph`.
 	/// Used to express a graph.
 	Graph,
@@ -72,6 +87,16 @@ pub enum Keyword {
 	/// being indexed.
 	None,
 
+	/// `@null`.
+	/// Used as the `@default` value of a frame to specify that the property must be set to
+	/// `null` in the output.
+	Null,
+
+	/// `@omitDefault`.
+	/// Used in a frame to specify that properties absent from the matched node must not be
+	/// included in the output.
+	OmitDefault,
+
 	/// `@prefix`.
 	/// With the value true, allows this term to be used to construct a compact IRI when
 	/// compacting.
@@ -90,6 +115,11 @@ pub enum Keyword {
 	/// Used to prevent term definitions of a context to be overridden by other contexts.
 	Protected,
 
+	/// `@requireAll`.
+	/// Used in a frame to specify that all the properties of the frame must be matched by a
+	/// node for it to match.
+	RequireAll,
+
 	/// `@reverse`.
 	/// Used to express reverse properties.
 	Reverse,
@@ -123,7 +153,10 @@ impl Keyword {
 			Base => "@base",
 			Container => "@container",
 			Context => "@context",
+			Default => "@default",
 			Direction => "@direction",
+			Embed => "@embed",
+			Explicit => "@explicit",
 			Graph => "@graph",
 			Id => "@id",
 			Import => "@import",
@@ -134,9 +167,12 @@ impl Keyword {
 			List => "@list",
 			Nest => "@nest",
 			None => "@none",
+			Null => "@null",
+			OmitDefault => "@omitDefault",
 			Prefix => "@prefix",
 			Propagate => "@propagate",
 			Protected => "@protected",
+			RequireAll => "@requireAll",
 			Reverse => "@reverse",
 			Set => "@set",
 			Type => "@type",
@@ -156,7 +192,10 @@ impl<'a> TryFrom<&'a str> for Keyword {
 			"@base" => Ok(Base),
 			"@container" => Ok(Container),
 			"@context" => Ok(Context),
+			"@default" => Ok(Default),
 			"@direction" => Ok(Direction),
+			"@embed" => Ok(Embed),
+			"@explicit" => Ok(Explicit),
 			"@graph" => Ok(Graph),
 			"@id" => Ok(Id),
 			"@import" => Ok(Import),
@@ -167,9 +206,12 @@ impl<'a> TryFrom<&'a str> for Keyword {
 			"@list" => Ok(List),
 			"@nest" => Ok(Nest),
 			"@none" => Ok(None),
+			"@null" => Ok(Null),
+			"@omitDefault" => Ok(OmitDefault),
 			"@prefix" => Ok(Prefix),
 			"@propagate" => Ok(Propagate),
 			"@protected" => Ok(Protected),
+			"@requireAll" => Ok(RequireAll),
 			"@reverse" => Ok(Reverse),
 			"@set" => Ok(Set),
 			"@type" => Ok(Type),
@@ -1,8 +1,18 @@
 use std::collections::HashSet;
+use indexmap::IndexSet;
 use json::JsonValue;
 
 pub trait AsJson {
 	fn as_json(&self) -> JsonValue;
+
+	/// Same as `as_json` but with a stable output order.
+	///
+	/// Node properties are sorted by IRI and the items of sets (such as an expanded document)
+	/// are sorted by `@id`.
+	/// The items of arrays and lists are kept in order.
+	fn as_ordered_json(&self) -> JsonValue {
+		self.as_json()
+	}
 }
 
 impl AsJson for JsonValue {
@@ -38,12 +48,40 @@ impl<T: AsJson> AsJson for [T] {
 
 		JsonValue::Array(ary)
 	}
+
+	fn as_ordered_json(&self) -> JsonValue {
+		let mut ary = Vec::with_capacity(self.len());
+		for item in self {
+			ary.push(item.as_ordered_json())
+		}
+
+		JsonValue::Array(ary)
+	}
 }
 
 impl<T: AsJson> AsJson for Vec<T> {
 	fn as_json(&self) -> JsonValue {
 		self.as_slice().as_json()
 	}
+
+	fn as_ordered_json(&self) -> JsonValue {
+		self.as_slice().as_ordered_json()
+	}
+}
+
+/// JSON array of the given set items, sorted by `@id`, then by their serialized form.
+fn ordered_set_json<'a, T: 'a + AsJson, I: Iterator<Item=&'a T>>(set: I) -> JsonValue {
+	let mut items: Vec<_> = set.map(|item| {
+		let json = item.as_ordered_json();
+		let dump = json.dump();
+		(json, dump)
+	}).collect();
+
+	items.sort_by(|(a, a_dump), (b, b_dump)| {
+		a["@id"].as_str().cmp(&b["@id"].as_str()).then_with(|| a_dump.cmp(b_dump))
+	});
+
+	JsonValue::Array(items.into_iter().map(|(json, _)| json).collect())
 }
 
 impl<T: AsJson> AsJson for HashSet<T> {
@@ -55,6 +93,28 @@ impl<T: AsJson> AsJson for HashSet<T> {
 
 		JsonValue::Array(ary)
 	}
+
+	/// Items are sorted by `@id`, then by their serialized form.
+	fn as_ordered_json(&self) -> JsonValue {
+		ordered_set_json(self.iter())
+	}
+}
+
+impl<T: AsJson> AsJson for IndexSet<T> {
+	/// Items are kept in insertion order.
+	fn as_json(&self) -> JsonValue {
+		let mut ary = Vec::with_capacity(self.len());
+		for item in self {
+			ary.push(item.as_json())
+		}
+
+		JsonValue::Array(ary)
+	}
+
+	/// Items are sorted by `@id`, then by their serialized form.
+	fn as_ordered_json(&self) -> JsonValue {
+		ordered_set_json(self.iter())
+	}
 }
 
 pub fn json_ld_eq(a: &JsonValue, b: &JsonValue) -> bool {
@@ -107,3 +167,71 @@ pub fn json_ld_eq(a: &JsonValue, b: &JsonValue) -> bool {
 
 	true
 }
+
+/// Serialize a JSON number using the ECMAScript number serialization.
+pub fn canonical_json_number(number: &::json::number::Number) -> String {
+	let f: f64 = (*number).into();
+
+	if f == 0.0 {
+		// Negative zero is serialized as `0`.
+		"0".to_string()
+	} else if f.fract() == 0.0 && f.abs() < 1e21 {
+		format!("{:.0}", f)
+	} else if f.abs() >= 1e21 || f.abs() < 1e-6 {
+		let s = format!("{:e}", f);
+		match s.find('e') {
+			Some(i) if !s[i+1..].starts_with('-') => format!("{}e+{}", &s[..i], &s[i+1..]),
+			_ => s
+		}
+	} else {
+		format!("{}", f)
+	}
+}
+
+/// JSON Canonicalization Scheme.
+///
+/// Serialize the given JSON value with no whitespace, sorted object keys and ECMAScript
+/// number serialization.
+/// See <https://tools.ietf.org/html/rfc8785>.
+pub fn canonical_json(value: &JsonValue) -> String {
+	match value {
+		JsonValue::Number(n) => canonical_json_number(n),
+		JsonValue::Array(ary) => {
+			let items: Vec<_> = ary.iter().map(canonical_json).collect();
+			format!("[{}]", items.join(","))
+		},
+		JsonValue::Object(obj) => {
+			let mut entries: Vec<_> = obj.iter().collect();
+			entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+			let entries: Vec<_> = entries.into_iter().map(|(key, value)| format!("{}:{}", JsonValue::from(key).dump(), canonical_json(value))).collect();
+			format!("{{{}}}", entries.join(","))
+		},
+		value => value.dump()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn canonical_numbers() {
+		for (n, expected) in &[(0.0, "0"), (-0.0, "0"), (42.0, "42"), (1.5, "1.5"), (-1e-7, "-1e-7"), (1e21, "1e+21"), (1e20, "100000000000000000000"), (0.000001, "0.000001")] {
+			assert_eq!(canonical_json(&JsonValue::from(*n)), *expected)
+		}
+	}
+
+	#[test]
+	fn canonical_structures() {
+		let value = json::parse(r#"{ "b": [ 1.0, "x", null, true ], "a": { "d": {}, "c": [] } }"#).unwrap();
+		assert_eq!(canonical_json(&value), r#"{"a":{"c":[],"d":{}},"b":[1,"x",null,true]}"#)
+	}
+
+	#[test]
+	fn canonical_key_order() {
+		// Keys are sorted by UTF-16 code units: the surrogate pair of `U+1F600` sorts before
+		// `U+FB33`, although it is after it in code point order.
+		let value = json::parse("{\"\u{fb33}\": 1, \"\u{1f600}\": 2, \"\u{e9}\": 3, \"\\r\": 4}").unwrap();
+		assert_eq!(canonical_json(&value), "{\"\\r\":4,\"\u{e9}\":3,\"\u{1f600}\":2,\"\u{fb33}\":1}")
+	}
+}
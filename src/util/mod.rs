@@ -2,15 +2,19 @@
 
 use std::hash::{Hash, Hasher};
 use std::collections::{HashSet, HashMap, hash_map::DefaultHasher};
+use indexmap::{IndexSet, IndexMap};
 use ::json::{JsonValue, number::Number};
+use crate::generic_json::{Json, JsonRef};
 
 mod json;
+mod pointer;
 pub use self::json::*;
+pub use self::pointer::*;
 
-pub fn as_array(json: &JsonValue) -> &[JsonValue] {
-	match json {
-		JsonValue::Array(ary) => ary,
-		_ => unsafe { std::mem::transmute::<&JsonValue, &[JsonValue; 1]>(json) as &[JsonValue] }
+pub fn as_array<J: Json>(json: &J) -> &[J] {
+	match json.as_json_ref() {
+		JsonRef::Array(ary) => ary,
+		_ => std::slice::from_ref(json)
 	}
 }
 
@@ -21,6 +25,14 @@ pub fn hash_json_number<H: Hasher>(number: &Number, hasher: &mut H) {
 	exponent.hash(hasher);
 }
 
+/// Convert an integer into a `json` number.
+///
+/// Unlike `Number::from(i64)`, which negates negative integers, this does not overflow on
+/// `i64::MIN`.
+pub fn integer_number(n: i64) -> Number {
+	Number::from_parts(n >= 0, n.unsigned_abs(), 0)
+}
+
 pub fn hash_json<H: Hasher>(value: &JsonValue, hasher: &mut H) {
 	match value {
 		JsonValue::Null => (),
@@ -78,6 +90,36 @@ pub fn hash_map<K: Hash, V: Hash, H: Hasher>(map: &HashMap<K, V>, hasher: &mut H
 	hasher.write_u64(hash);
 }
 
+pub fn hash_index_set<T: Hash, H: Hasher>(set: &IndexSet<T>, hasher: &mut H) {
+	// Same as `hash_set`: the hash must not depend on the insertion order.
+	let mut hash = 0;
+	for item in set {
+		let mut h = DefaultHasher::new();
+		item.hash(&mut h);
+		hash = u64::wrapping_add(hash, h.finish());
+	}
+
+	hasher.write_u64(hash);
+}
+
+pub fn hash_index_set_opt<T: Hash, H: Hasher>(set_opt: Option<&IndexSet<T>>, hasher: &mut H) {
+	if let Some(set) = set_opt {
+		hash_index_set(set, hasher)
+	}
+}
+
+pub fn hash_index_map<K: Hash, V: Hash, H: Hasher>(map: &IndexMap<K, V>, hasher: &mut H) {
+	// Same as `hash_map`: the hash must not depend on the insertion order.
+	let mut hash = 0;
+	for entry in map {
+		let mut h = DefaultHasher::new();
+		entry.hash(&mut h);
+		hash = u64::wrapping_add(hash, h.finish());
+	}
+
+	hasher.write_u64(hash);
+}
+
 // pub fn hash_map_of_sets<K: Hash, V: Hash, H: Hasher>(map: &HashMap<K, HashSet<V>>, hasher: &mut H) {
 // 	// Elements must be combined with a associative and commutative operation •.
 // 	// (u64, •, 0) must form a commutative monoid.
@@ -0,0 +1,263 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Format the given (unescaped) segments into a [JSON Pointer](https://tools.ietf.org/html/rfc6901).
+///
+/// The `~` and `/` characters of each segment are escaped into `~0` and `~1`.
+/// The empty pointer `""` designates the whole document.
+pub fn json_pointer<'a, P: IntoIterator<Item = &'a str>>(segments: P) -> String {
+	let mut pointer = String::new();
+	for segment in segments {
+		pointer.push('/');
+		for c in segment.chars() {
+			match c {
+				'~' => pointer.push_str("~0"),
+				'/' => pointer.push_str("~1"),
+				c => pointer.push(c)
+			}
+		}
+	}
+
+	pointer
+}
+
+/// Find the line and column (both starting at 1) of a JSON value in a JSON source text.
+///
+/// The value is designated by the (unescaped) segments of its
+/// [JSON Pointer](https://tools.ietf.org/html/rfc6901).
+/// The `json` parser does not keep track of the position of the values it parses,
+/// so this function scans the source text again, skipping the values that are not on the path.
+/// Returns `None` if the source text is not valid JSON or does not contain the value.
+pub fn json_pointer_position<'a, P: IntoIterator<Item = &'a str>>(source: &str, pointer: P) -> Option<(usize, usize)> {
+	let mut scanner = Scanner {
+		chars: source.char_indices().peekable()
+	};
+
+	let mut offset = scanner.value_start()?;
+	for segment in pointer {
+		offset = match scanner.chars.next()?.1 {
+			'{' => scanner.find_entry(segment)?,
+			'[' => scanner.find_item(segment.parse().ok()?)?,
+			_ => return None
+		}
+	}
+
+	let before = &source[..offset];
+	let line = before.matches('\n').count() + 1;
+	let column = match before.rfind('\n') {
+		Some(i) => before[(i + 1)..].chars().count() + 1,
+		None => before.chars().count() + 1
+	};
+
+	Some((line, column))
+}
+
+struct Scanner<'a> {
+	chars: Peekable<CharIndices<'a>>
+}
+
+impl<'a> Scanner<'a> {
+	fn skip_whitespaces(&mut self) {
+		while let Some((_, c)) = self.chars.peek() {
+			if c.is_whitespace() {
+				self.chars.next();
+			} else {
+				break
+			}
+		}
+	}
+
+	/// Skip the whitespaces and return the offset of the next value.
+	fn value_start(&mut self) -> Option<usize> {
+		self.skip_whitespaces();
+		self.chars.peek().map(|(i, _)| *i)
+	}
+
+	/// Expect the given character, after optional whitespaces.
+	fn expect(&mut self, expected: char) -> Option<()> {
+		self.skip_whitespaces();
+		match self.chars.next() {
+			Some((_, c)) if c == expected => Some(()),
+			_ => None
+		}
+	}
+
+	/// Parse a string, the opening quote being already consumed.
+	fn string(&mut self) -> Option<String> {
+		let mut result = String::new();
+		loop {
+			match self.chars.next()?.1 {
+				'"' => break,
+				'\\' => {
+					let c = match self.chars.next()?.1 {
+						'b' => '\u{08}',
+						'f' => '\u{0c}',
+						'n' => '\n',
+						'r' => '\r',
+						't' => '\t',
+						'u' => {
+							let high = self.code_unit()?;
+							if (0xd800..0xdc00).contains(&high) {
+								self.expect('\\')?;
+								self.expect('u')?;
+								let low = self.code_unit()?;
+								std::char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low.checked_sub(0xdc00)?))?
+							} else {
+								std::char::from_u32(high)?
+							}
+						},
+						c => c
+					};
+
+					result.push(c)
+				},
+				c => result.push(c)
+			}
+		}
+
+		Some(result)
+	}
+
+	/// Parse the four hexadecimal digits of an `\u` escape sequence.
+	fn code_unit(&mut self) -> Option<u32> {
+		let mut result = 0;
+		for _ in 0..4 {
+			result = result * 16 + self.chars.next()?.1.to_digit(16)?
+		}
+
+		Some(result)
+	}
+
+	/// Skip the value starting at the next character.
+	fn skip_value(&mut self) -> Option<()> {
+		self.skip_whitespaces();
+		match self.chars.next()?.1 {
+			'"' => {
+				self.string()?;
+			},
+			'{' => {
+				self.skip_whitespaces();
+				if self.chars.peek()?.1 == '}' {
+					self.chars.next();
+				} else {
+					loop {
+						self.expect('"')?;
+						self.string()?;
+						self.expect(':')?;
+						self.skip_value()?;
+						self.skip_whitespaces();
+						match self.chars.next()?.1 {
+							',' => (),
+							'}' => break,
+							_ => return None
+						}
+					}
+				}
+			},
+			'[' => {
+				self.skip_whitespaces();
+				if self.chars.peek()?.1 == ']' {
+					self.chars.next();
+				} else {
+					loop {
+						self.skip_value()?;
+						self.skip_whitespaces();
+						match self.chars.next()?.1 {
+							',' => (),
+							']' => break,
+							_ => return None
+						}
+					}
+				}
+			},
+			_ => {
+				// Number or literal name.
+				while let Some((_, c)) = self.chars.peek() {
+					if c.is_alphanumeric() || *c == '-' || *c == '+' || *c == '.' {
+						self.chars.next();
+					} else {
+						break
+					}
+				}
+			}
+		}
+
+		Some(())
+	}
+
+	/// Find the value of the given entry in the object whose opening brace has been consumed,
+	/// and return its offset.
+	fn find_entry(&mut self, key: &str) -> Option<usize> {
+		loop {
+			self.expect('"')?;
+			let entry_key = self.string()?;
+			self.expect(':')?;
+			if entry_key == key {
+				return self.value_start()
+			}
+
+			self.skip_value()?;
+			self.expect(',')?;
+		}
+	}
+
+	/// Find the item at the given index in the array whose opening bracket has been consumed,
+	/// and return its offset.
+	fn find_item(&mut self, index: usize) -> Option<usize> {
+		for _ in 0..index {
+			self.skip_value()?;
+			self.expect(',')?;
+		}
+
+		match self.value_start()? {
+			_ if self.chars.peek()?.1 == ']' => None,
+			offset => Some(offset)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escaping() {
+		assert_eq!(json_pointer(Vec::new()), "");
+		assert_eq!(json_pointer(vec![""]), "/");
+		assert_eq!(json_pointer(vec!["a", "0", "@id"]), "/a/0/@id");
+		assert_eq!(json_pointer(vec!["a/b", "~c", "~1"]), "/a~1b/~0c/~01")
+	}
+
+	#[test]
+	fn positions() {
+		let source = "{\n\t\"a\": [1, {\"b\": true}],\n\t\"c/d\": {\"~\": \"x\"}\n}";
+		assert_eq!(json_pointer_position(source, Vec::new()), Some((1, 1)));
+		assert_eq!(json_pointer_position(source, vec!["a"]), Some((2, 7)));
+		assert_eq!(json_pointer_position(source, vec!["a", "0"]), Some((2, 8)));
+		assert_eq!(json_pointer_position(source, vec!["a", "1", "b"]), Some((2, 17)));
+		assert_eq!(json_pointer_position(source, vec!["c/d", "~"]), Some((3, 15)))
+	}
+
+	#[test]
+	fn skipped_values() {
+		// Nested values, strings containing structural characters and escape sequences are
+		// skipped while looking for the entry.
+		let source = r#"{"a": {"x": [[], {}], "y": "}],\"é"}, "é": [null, -1.5e+3, "é", {"z": 1}]}"#;
+		assert_eq!(json_pointer_position(source, vec!["é", "3", "z"]), Some((1, 71)));
+		assert_eq!(json_pointer_position(source, vec!["é", "2"]), Some((1, 60)));
+
+		let source = "[\"\\ud83d\\ude00\", {\"\u{1f600}\": 0}]";
+		assert_eq!(json_pointer_position(source, vec!["1", "\u{1f600}"]), Some((1, 24)))
+	}
+
+	#[test]
+	fn missing_values() {
+		let source = r#"{"a": [1, 2], "b": "c"}"#;
+		assert_eq!(json_pointer_position(source, vec!["d"]), None);
+		assert_eq!(json_pointer_position(source, vec!["a", "2"]), None);
+		assert_eq!(json_pointer_position(source, vec!["a", "x"]), None);
+		assert_eq!(json_pointer_position(source, vec!["b", "0"]), None);
+		assert_eq!(json_pointer_position("{\"a\": ", vec!["a"]), None);
+		assert_eq!(json_pointer_position("[]", vec!["0"]), None)
+	}
+}
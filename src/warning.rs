@@ -0,0 +1,368 @@
+use std::fmt;
+use std::sync::Mutex;
+use iref::IriBuf;
+
+/// Warning code.
+///
+/// Identifies the situations where the JSON-LD specification states that processors
+/// SHOULD generate a warning.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum WarningCode {
+	/// A term having the form of a keyword (`"@"1*ALPHA`) is defined in a context.
+	/// The term definition is ignored.
+	KeywordLikeTerm,
+
+	/// A value having the form of a keyword (but that is not a keyword) is used where an IRI
+	/// is expected, such as the `@id` or `@reverse` entry of a term definition, or a key.
+	/// It is ignored.
+	KeywordLikeValue,
+
+	/// A language tag is not well-formed according to section 2.2.9 of
+	/// [BCP47](https://tools.ietf.org/html/bcp47).
+	MalformedLanguageTag,
+
+	/// An entry has been dropped during expansion since its key does not expand to an
+	/// IRI, blank node identifier or keyword.
+	DroppedKey,
+
+	/// A value (such as the value of an `@id` or `@type` entry) does not expand to an IRI
+	/// or blank node identifier.
+	/// It is kept as is (see [`Lenient::Unknown`](crate::Lenient::Unknown)).
+	///
+	/// This warning is only emitted in the
+	/// [lenient-report expansion mode](crate::expansion::Policy::Report).
+	UnresolvedValue
+}
+
+impl WarningCode {
+	/// Get the warning code name.
+	pub fn as_str(&self) -> &str {
+		use WarningCode::*;
+		match self {
+			KeywordLikeTerm => "keyword-like term",
+			KeywordLikeValue => "keyword-like value",
+			MalformedLanguageTag => "malformed language tag",
+			DroppedKey => "dropped key",
+			UnresolvedValue => "unresolved value"
+		}
+	}
+}
+
+impl fmt::Display for WarningCode {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+/// Location of a warning.
+///
+/// Warnings emitted by the expansion algorithm, and while processing the local contexts of
+/// the document, are located by a [JSON Pointer](https://tools.ietf.org/html/rfc6901)
+/// (see [`Location::pointer`]), the same way errors are (see [`Error::pointer`](crate::Error::pointer)).
+/// Warnings emitted in a remote context are only located by the URL of the context and
+/// the key of the offending entry.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Location {
+	/// URL of the remote context or document in which the warning has been detected, if
+	/// known.
+	pub url: Option<IriBuf>,
+
+	/// Key of the entry in which the warning has been detected, if any.
+	///
+	/// In a context, this is the term being defined (or the context keyword, such as
+	/// `@language`).
+	/// In a document, this is the key of the entry holding the offending value.
+	pub key: Option<String>,
+
+	/// Segments of the JSON Pointer to the offending value, in reverse order.
+	///
+	/// Segments are added while the warning is forwarded up to the warning handler given
+	/// to the algorithm (see [`At`]).
+	path: Vec<String>,
+
+	/// Set if the warning has been detected in a remote context.
+	/// The path is then left empty.
+	remote: bool
+}
+
+impl Location {
+	/// Create a new location.
+	pub fn new(url: Option<IriBuf>, key: Option<String>) -> Location {
+		Location {
+			url,
+			key,
+			path: Vec::new(),
+			remote: false
+		}
+	}
+
+	/// Create the location of a warning detected in the remote context located at `url`.
+	pub(crate) fn in_remote_context(url: IriBuf, key: Option<String>) -> Location {
+		Location {
+			url: Some(url),
+			key,
+			path: Vec::new(),
+			remote: true
+		}
+	}
+
+	/// JSON Pointer to the offending value.
+	///
+	/// The pointer is relative to the processed document (or local context).
+	/// It is empty if the warning has been detected in a remote context, or is not related
+	/// to a particular value of the document.
+	pub fn pointer(&self) -> String {
+		crate::util::json_pointer(self.path.iter().rev().map(String::as_str))
+	}
+
+	/// Line and column (both starting at 1) of the offending value in the given JSON source
+	/// text of the processed document.
+	///
+	/// Returns `None` if the source is not valid JSON or does not contain the value.
+	pub fn position(&self, source: &str) -> Option<(usize, usize)> {
+		crate::util::json_pointer_position(source, self.path.iter().rev().map(String::as_str))
+	}
+
+	/// Add a parent segment to the pointer of the location.
+	///
+	/// The pointer is left untouched if the location is in a remote context.
+	pub(crate) fn at<S: ToString>(mut self, segment: S) -> Location {
+		if !self.remote {
+			self.path.push(segment.to_string())
+		}
+
+		self
+	}
+
+	/// Add the index of the item holding the offending value to the pointer of the location,
+	/// if the item is part of an array.
+	pub(crate) fn at_item(self, index: usize, in_array: bool) -> Location {
+		if in_array {
+			self.at(index)
+		} else {
+			self
+		}
+	}
+}
+
+impl fmt::Display for Location {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut parts = Vec::new();
+
+		if let Some(key) = &self.key {
+			parts.push(format!("`{}`", key))
+		}
+
+		if !self.path.is_empty() {
+			parts.push(format!("at `{}`", self.pointer()))
+		}
+
+		if let Some(url) = &self.url {
+			parts.push(format!("in <{}>", url))
+		}
+
+		if parts.is_empty() {
+			write!(f, "unknown location")
+		} else {
+			write!(f, "{}", parts.join(" "))
+		}
+	}
+}
+
+/// Warning.
+///
+/// Warnings do not stop the processing of a document.
+/// They are reported to the [`WarningHandler`] given in the processing options.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Warning {
+	/// Warning code.
+	code: WarningCode,
+
+	/// Offending value (term, IRI, language tag, key, etc.).
+	value: String,
+
+	/// Where the warning has been detected.
+	location: Location
+}
+
+impl Warning {
+	/// Create a new warning.
+	pub fn new<V: Into<String>>(code: WarningCode, value: V, location: Location) -> Warning {
+		Warning {
+			code,
+			value: value.into(),
+			location
+		}
+	}
+
+	/// Get the warning code.
+	pub fn code(&self) -> WarningCode {
+		self.code
+	}
+
+	/// Get the offending value.
+	pub fn value(&self) -> &str {
+		self.value.as_str()
+	}
+
+	/// Get the location of the warning.
+	pub fn location(&self) -> &Location {
+		&self.location
+	}
+
+	/// Add a parent segment to the pointer of the warning location.
+	pub(crate) fn at<S: ToString>(mut self, segment: S) -> Warning {
+		self.location = self.location.at(segment);
+		self
+	}
+}
+
+impl fmt::Display for Warning {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} `{}` ({})", self.code, self.value, self.location)
+	}
+}
+
+impl std::error::Error for Warning {}
+
+/// Warning handler.
+///
+/// Receives the warnings emitted by the processing algorithms.
+/// It is implemented by any `Fn(Warning)` closure, and by [`Warnings`], collecting them.
+pub trait WarningHandler: Send + Sync {
+	/// Handle a warning.
+	fn handle(&self, warning: Warning);
+}
+
+impl<F: Send + Sync + Fn(Warning)> WarningHandler for F {
+	fn handle(&self, warning: Warning) {
+		self(warning)
+	}
+}
+
+/// Report a warning to the given handler, or log it if there is none.
+pub(crate) fn report(handler: Option<&dyn WarningHandler>, warning: Warning) {
+	match handler {
+		Some(handler) => handler.handle(warning),
+		None => warn!("{}", warning)
+	}
+}
+
+/// Segment of a JSON Pointer.
+#[derive(Clone, Copy)]
+pub(crate) enum Segment<'a> {
+	/// Object entry.
+	Key(&'a str),
+
+	/// Array item.
+	Index(usize)
+}
+
+impl<'a> fmt::Display for Segment<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Segment::Key(key) => write!(f, "{}", key),
+			Segment::Index(i) => write!(f, "{}", i)
+		}
+	}
+}
+
+/// Warning handler locating the warnings it receives in a child of the value being processed.
+///
+/// The segment leading to the child is added to the location of each warning, which is then
+/// forwarded to the handler of the parent value (or logged if there is none).
+/// Errors are located while being propagated up to the root of the document.
+/// Warnings are reported as soon as they are detected, so the algorithms wrap the warning
+/// handler instead each time they process a child value.
+pub(crate) struct At<'a> {
+	handler: Option<&'a dyn WarningHandler>,
+	segment: Option<Segment<'a>>
+}
+
+impl<'a> At<'a> {
+	/// Locate the warnings in the entry with the given key.
+	pub(crate) fn key(handler: Option<&'a dyn WarningHandler>, key: &'a str) -> At<'a> {
+		At {
+			handler,
+			segment: Some(Segment::Key(key))
+		}
+	}
+
+	/// Locate the warnings in the item at the given index, if the item is part of an array.
+	///
+	/// JSON-LD often allows a single value in place of an array containing only this value,
+	/// in which case the warnings are forwarded untouched.
+	pub(crate) fn item(handler: Option<&'a dyn WarningHandler>, index: usize, in_array: bool) -> At<'a> {
+		At {
+			handler,
+			segment: if in_array { Some(Segment::Index(index)) } else { None }
+		}
+	}
+}
+
+impl<'a> WarningHandler for At<'a> {
+	fn handle(&self, warning: Warning) {
+		match self.segment {
+			Some(segment) => report(self.handler, warning.at(segment)),
+			None => report(self.handler, warning)
+		}
+	}
+}
+
+/// Collects every warning emitted during processing.
+///
+/// # Example
+/// ```
+/// use json_ld::{Document, NoLoader, Warnings, WarningCode, context::JsonContext, expansion};
+///
+/// # fn main() -> Result<(), json_ld::Error> {
+/// let context: JsonContext = JsonContext::new(None);
+/// let doc = json::parse("{ \"foo\": \"bar\" }").unwrap();
+///
+/// let warnings = Warnings::new();
+/// let mut options = expansion::Options::default();
+/// options.warnings = Some(&warnings);
+/// let expanded_doc = doc.expand_with_sync(None, &context, &mut NoLoader, options)?;
+/// assert!(expanded_doc.is_empty());
+///
+/// // `foo` is not defined, and is dropped.
+/// let warnings = warnings.into_vec();
+/// assert_eq!(warnings[0].code(), WarningCode::DroppedKey);
+/// assert_eq!(warnings[0].location().pointer(), "/foo");
+///
+/// for warning in warnings {
+///     eprintln!("{}: {}", warning.code(), warning.value());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Warnings(Mutex<Vec<Warning>>);
+
+impl Warnings {
+	/// Create a new empty warning collection.
+	pub fn new() -> Warnings {
+		Warnings::default()
+	}
+
+	/// Number of warnings collected so far.
+	pub fn len(&self) -> usize {
+		self.0.lock().unwrap().len()
+	}
+
+	/// Checks if no warning has been collected.
+	pub fn is_empty(&self) -> bool {
+		self.0.lock().unwrap().is_empty()
+	}
+
+	/// Consume the collection and return the warnings, in the order they have been emitted.
+	pub fn into_vec(self) -> Vec<Warning> {
+		self.0.into_inner().unwrap()
+	}
+}
+
+impl WarningHandler for Warnings {
+	fn handle(&self, warning: Warning) {
+		self.0.lock().unwrap().push(warning)
+	}
+}
@@ -0,0 +1,65 @@
+//! Tests of the `jsonld` command-line tool.
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const DOCUMENT: &str = r#"{
+	"@context": {"@vocab": "http://example.org/"},
+	"@graph": [
+		{"@id": "http://example.org/a", "name": "A", "knows": {"name": "anonymous"}},
+		{"@id": "http://example.org/b", "name": "B", "knows": {"@id": "http://example.org/a"}},
+		{"@id": "http://example.org/c", "name": "C"},
+		{"@id": "http://example.org/d", "name": "D", "tag": ["x", "y", "z"]},
+		{"@id": "_:e", "name": "E"}
+	]
+}"#;
+
+const CONTEXT: &str = r#"{"@context": {"@vocab": "http://example.org/"}}"#;
+
+const FRAME: &str = r#"{"@context": {"@vocab": "http://example.org/"}, "name": {}}"#;
+
+/// Write the test files in a fresh directory.
+fn fixtures(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("json-ld-cli-{}-{}", name, std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("doc.jsonld"), DOCUMENT).unwrap();
+	fs::write(dir.join("context.jsonld"), CONTEXT).unwrap();
+	fs::write(dir.join("frame.jsonld"), FRAME).unwrap();
+	dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> Vec<u8> {
+	let output = Command::new(env!("CARGO_BIN_EXE_jsonld"))
+		.current_dir(dir)
+		.args(args)
+		.output()
+		.unwrap();
+
+	assert!(output.status.success(), "jsonld {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+	output.stdout
+}
+
+/// Ordered processing must produce the exact same output on every run.
+#[test]
+fn ordered_output_is_stable() {
+	let dir = fixtures("ordered");
+	let commands: &[&[&str]] = &[
+		&["expand", "--ordered", "doc.jsonld"],
+		&["compact", "--ordered", "--context", "context.jsonld", "doc.jsonld"],
+		&["flatten", "--ordered", "doc.jsonld"],
+		&["flatten", "--ordered", "--context", "context.jsonld", "doc.jsonld"],
+		&["frame", "--ordered", "--frame", "frame.jsonld", "doc.jsonld"],
+		&["to-rdf", "--ordered", "doc.jsonld"]
+	];
+
+	for args in commands {
+		let expected = run(&dir, args);
+		for _ in 0..8 {
+			assert_eq!(run(&dir, args), expected, "jsonld {:?} is not deterministic", args)
+		}
+	}
+
+	fs::remove_dir_all(dir).unwrap()
+}
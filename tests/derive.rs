@@ -0,0 +1,188 @@
+//! Tests of the `LinkedData` derive macro.
+#![cfg(feature = "derive")]
+
+use std::convert::TryFrom;
+use futures::executor::block_on;
+use iref::IriBuf;
+use json_ld::{
+	Document,
+	NoLoader,
+	Node,
+	Object,
+	Reference,
+	Lenient,
+	LinkedData,
+	context::JsonContext,
+	expansion,
+	linked_data::Error
+};
+
+#[derive(LinkedData, PartialEq, Debug)]
+#[ld(type = "http://schema.org/PostalAddress", id_type = "IriBuf")]
+struct Address {
+	#[ld(iri = "http://schema.org/addressLocality")]
+	locality: String
+}
+
+#[derive(LinkedData, PartialEq, Debug)]
+#[ld(type = "http://schema.org/Person", id_type = "IriBuf")]
+struct Person {
+	#[ld(id)]
+	id: Option<Reference>,
+
+	#[ld(iri = "http://schema.org/name")]
+	name: String,
+
+	#[ld(iri = "http://schema.org/alternateName")]
+	nickname: Option<String>,
+
+	#[ld(iri = "http://schema.org/age")]
+	age: Option<u32>,
+
+	#[ld(iri = "http://schema.org/knows")]
+	knows: Vec<Reference>,
+
+	#[ld(iri = "http://schema.org/address")]
+	address: Option<Address>,
+
+	#[ld(skip)]
+	cache: Option<String>
+}
+
+/// Struct generic over the identifier type.
+#[derive(LinkedData, PartialEq, Debug)]
+struct Tag {
+	#[ld(iri = "http://schema.org/name")]
+	name: String,
+
+	#[ld(iri = "http://schema.org/keywords")]
+	keywords: Vec<String>
+}
+
+fn reference(iri: &str) -> Reference {
+	Reference::Id(IriBuf::new(iri).unwrap())
+}
+
+fn expand(doc: &str) -> Node {
+	let doc = json::parse(doc).unwrap();
+	let context: JsonContext = JsonContext::new(None);
+	let expanded = block_on(doc.expand_with(None, &context, &mut NoLoader, expansion::Options::default())).unwrap();
+	assert_eq!(expanded.len(), 1);
+	match expanded.into_iter().next().unwrap().into_inner() {
+		Object::Node(node) => node,
+		_ => panic!("expected a node object")
+	}
+}
+
+#[test]
+fn round_trip() {
+	let person = Person {
+		id: Some(reference("http://example.org/alice")),
+		name: "Alice".to_string(),
+		nickname: Some("Al".to_string()),
+		age: Some(42),
+		knows: vec![reference("http://example.org/bob"), reference("http://example.org/carol")],
+		address: Some(Address {
+			locality: "Paris".to_string()
+		}),
+		cache: None
+	};
+
+	let node: Node = Person::try_from(&Node::from(person)).map(Node::from).unwrap();
+	assert!(node.id() == Some(&Lenient::Ok(reference("http://example.org/alice"))));
+	assert!(node.types() == [Lenient::Ok(reference("http://schema.org/Person"))]);
+
+	let person = Person::try_from(&node).unwrap();
+	assert_eq!(person.name, "Alice");
+	assert_eq!(person.nickname.as_deref(), Some("Al"));
+	assert_eq!(person.age, Some(42));
+	assert_eq!(person.knows, vec![reference("http://example.org/bob"), reference("http://example.org/carol")]);
+	assert_eq!(person.address, Some(Address { locality: "Paris".to_string() }))
+}
+
+#[test]
+fn optional_and_vec_fields() {
+	let person = Person {
+		id: None,
+		name: "Anonymous".to_string(),
+		nickname: None,
+		age: None,
+		knows: Vec::new(),
+		address: None,
+		cache: Some("skipped".to_string())
+	};
+
+	let node = Node::from(person);
+	assert!(node.id().is_none());
+	assert_eq!(node.get(&reference("http://schema.org/name")).count(), 1);
+	assert!(node.get_any(&reference("http://schema.org/alternateName")).is_none());
+	assert!(node.get_any(&reference("http://schema.org/knows")).is_none());
+
+	let person = Person::try_from(&node).unwrap();
+	assert_eq!(person, Person {
+		id: None,
+		name: "Anonymous".to_string(),
+		nickname: None,
+		age: None,
+		knows: Vec::new(),
+		address: None,
+		cache: None
+	})
+}
+
+#[test]
+fn from_expanded_node() {
+	let node = expand(r#"{
+		"@context": {"@vocab": "http://schema.org/"},
+		"@id": "http://example.org/bob",
+		"@type": "Person",
+		"name": "Bob",
+		"age": 30,
+		"knows": [{"@id": "http://example.org/alice"}],
+		"address": {"@type": "PostalAddress", "addressLocality": "Lyon"}
+	}"#);
+
+	let person = Person::try_from(&node).unwrap();
+	assert_eq!(person, Person {
+		id: Some(reference("http://example.org/bob")),
+		name: "Bob".to_string(),
+		nickname: None,
+		age: Some(30),
+		knows: vec![reference("http://example.org/alice")],
+		address: Some(Address {
+			locality: "Lyon".to_string()
+		}),
+		cache: None
+	});
+
+	// Converting the struct back gives the same node.
+	assert!(Node::from(person) == node)
+}
+
+#[test]
+fn generic_id_type() {
+	let tag = Tag {
+		name: "rust".to_string(),
+		keywords: vec!["language".to_string(), "systems".to_string()]
+	};
+
+	let node: Node<IriBuf> = tag.into();
+	let tag = Tag::try_from(&node).unwrap();
+	assert_eq!(tag.name, "rust");
+	assert_eq!(tag.keywords.len(), 2)
+}
+
+#[test]
+fn errors() {
+	let node = expand(r#"{"@type": "http://schema.org/Person"}"#);
+	assert_eq!(Person::try_from(&node).unwrap_err(), Error::MissingProperty("http://schema.org/name".to_string()));
+
+	let node = expand(r#"{"http://schema.org/name": "Alice"}"#);
+	assert_eq!(Person::try_from(&node).unwrap_err(), Error::MissingType("http://schema.org/Person".to_string()));
+
+	let node = expand(r#"{"@type": "http://schema.org/Person", "http://schema.org/name": ["Alice", "Al"]}"#);
+	assert_eq!(Person::try_from(&node).unwrap_err(), Error::MultipleValues("http://schema.org/name".to_string()));
+
+	let node = expand(r#"{"@type": "http://schema.org/Person", "http://schema.org/name": "Alice", "http://schema.org/age": -1}"#);
+	assert!(matches!(Person::try_from(&node).unwrap_err(), Error::InvalidValue { property, .. } if property == "http://schema.org/age"))
+}
@@ -12,11 +12,7 @@ use json_ld::{{
 	ErrorCode,
 	ProcessingMode,
 	Document,
-	context::{{
-		JsonContext,
-		Local,
-		Loader as ContextLoader
-	}},
+	context::JsonContext,
 	expansion,
 	util::{{
 		AsJson,
@@ -31,11 +27,14 @@ struct Options<'a> {{
 	expand_context: Option<Iri<'a>>
 }}
 
-impl<'a> From<Options<'a>> for expansion::Options {{
-	fn from(options: Options<'a>) -> expansion::Options {{
+impl<'a> From<Options<'a>> for expansion::Options<'a> {{
+	fn from(options: Options<'a>) -> expansion::Options<'a> {{
 		expansion::Options {{
 			processing_mode: options.processing_mode,
-			ordered: false
+			ordered: false,
+			frame_expansion: false,
+			expand_context: options.expand_context.map(expansion::ExpandContext::Iri),
+			..expansion::Options::default()
 		}}
 	}}
 }}
@@ -46,12 +45,7 @@ fn positive_test(options: Options, input_url: Iri, base_url: Iri, output_url: Ir
 
 	let input = task::block_on(loader.load(input_url)).unwrap();
 	let output = task::block_on(loader.load(output_url)).unwrap();
-	let mut input_context: JsonContext<IriBuf> = JsonContext::new(Some(base_url));
-
-	if let Some(context_url) = options.expand_context {{
-		let local_context = task::block_on(loader.load_context(context_url)).unwrap().into_context();
-		input_context = task::block_on(local_context.process(&input_context, &mut loader, Some(base_url))).unwrap();
-	}}
+	let input_context: JsonContext<IriBuf> = JsonContext::new(Some(base_url));
 
 	let result = task::block_on(input.expand_with(Some(base_url), &input_context, &mut loader, options.into())).unwrap();
 
@@ -71,12 +65,7 @@ fn negative_test(options: Options, input_url: Iri, base_url: Iri, error_code: Er
 	loader.mount(iri!("https://w3c.github.io/json-ld-api"), "json-ld-api");
 
 	let input = task::block_on(loader.load(input_url)).unwrap();
-	let mut input_context: JsonContext<IriBuf> = JsonContext::new(Some(base_url));
-
-	if let Some(context_url) = options.expand_context {{
-		let local_context = task::block_on(loader.load_context(context_url)).unwrap().into_context();
-		input_context = task::block_on(local_context.process(&input_context, &mut loader, Some(base_url))).unwrap();
-	}}
+	let input_context: JsonContext<IriBuf> = JsonContext::new(Some(base_url));
 
 	match task::block_on(input.expand_with(Some(base_url), &input_context, &mut loader, options.into())) {{
 		Ok(result) => {{